@@ -0,0 +1,95 @@
+//! Benchmarks of the evaluator on small programs that stress function calls, loops and values.
+//! Every program is measured with both backends. Run them with `cargo bench`
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use halcon::evaluator::{Backend, Evaluator};
+use halcon::Engine;
+
+/// The backends measured, with the names used in the reports
+const BACKENDS: [(&str, Backend); 2] = [("ast", Backend::Ast), ("vm", Backend::Vm)];
+
+/**
+Measures a program with every backend. The definitions are evaluated first, so that only the measured code runs in the benchmark
+
+# Arguments
+* `c` - the benchmark runner
+* `name` - the name of the benchmark
+* `definitions` - the Halcon code that defines the values used by the benchmark
+* `code` - the Halcon code to measure
+*/
+fn bench_program(c: &mut Criterion, name: &str, definitions: &str, code: &str) {
+    let mut group = c.benchmark_group(name);
+
+    for (backend_name, backend) in BACKENDS {
+        let mut evaluator = Evaluator::new();
+        evaluator.set_backend(backend);
+        let mut engine = Engine::with_evaluator(evaluator);
+        engine.eval_str(definitions).unwrap();
+
+        group.bench_function(backend_name, |b| {
+            b.iter(|| engine.eval_str(black_box(code)).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn function_calls(c: &mut Criterion) {
+    bench_program(
+        c,
+        "fib(20)",
+        "let fib = fun(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } };",
+        "fib(20)",
+    );
+    bench_program(
+        c,
+        "tail calls",
+        "let sum = fun(n, acc) { if (n == 0) { acc } else { sum(n - 1, acc + n) } };",
+        "sum(10000, 0)",
+    );
+    bench_program(
+        c,
+        "closures",
+        "let counter = fun(step) { let count = 0; fun() { count += step; count } };",
+        "let next = counter(2); let total = 0; for (let i = 0; i < 10000; i++) { total += next(); } total",
+    );
+}
+
+fn loops(c: &mut Criterion) {
+    bench_program(
+        c,
+        "while loop",
+        "",
+        "let i = 0; let total = 0; while (i < 10000) { total += i * 2 % 7; i++; } total",
+    );
+    bench_program(
+        c,
+        "for loop",
+        "",
+        "let total = 0; for (let i = 0; i < 10000; i++) { if (i % 3 == 0) { total += i; } } total",
+    );
+}
+
+fn values(c: &mut Criterion) {
+    bench_program(
+        c,
+        "string building",
+        "",
+        r#"let text = ""; for (let i = 0; i < 1000; i++) { text = text + "ab"; } len(text)"#,
+    );
+    bench_program(
+        c,
+        "higher order builtins",
+        "let numbers = []; for (let i = 0; i < 1000; i++) { numbers = push(numbers, i); }",
+        "reduce(map(filter(numbers, fun(x) { x % 2 == 0 }), fun(x) { x * x }), fun(a, b) { a + b })",
+    );
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = function_calls, loops, values
+}
+criterion_main!(benches);
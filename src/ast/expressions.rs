@@ -1,12 +1,12 @@
-use std::collections::HashMap;
 use std::hash::Hash;
+use std::rc::Rc;
 
 use crate::ast::statements::BlockStatement;
 use crate::ast::Node;
-use crate::token::Token;
+use crate::token::{Span, Token};
 
-use super::AstNode;
 use super::statements::LetStatement;
+use super::AstNode;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PrefixExpression {
@@ -86,6 +86,41 @@ impl Node for IfExpression {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TryExpression {
+    pub token: Token,
+    pub block: BlockStatement,
+    pub catch: Option<(Identifier, BlockStatement)>,
+    pub finally: Option<BlockStatement>,
+}
+
+impl Node for TryExpression {
+    fn token_literal(&self) -> String {
+        self.token.to_string()
+    }
+
+    fn string(&self) -> String {
+        let res = format!("try {}", self.block.string());
+
+        let catch = match &self.catch {
+            None => res,
+            Some((parameter, handler)) => {
+                format!(
+                    "{} catch ({}) {}",
+                    res,
+                    parameter.string(),
+                    handler.string()
+                )
+            }
+        };
+
+        match &self.finally {
+            None => catch,
+            Some(finally) => format!("{} finally {}", catch, finally.string()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct IndexExpression {
     pub token: Token,
@@ -103,11 +138,57 @@ impl Node for IndexExpression {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PropagationExpression {
+    pub token: Token,
+    pub value: Box<AstNode>,
+}
+
+impl Node for PropagationExpression {
+    fn token_literal(&self) -> String {
+        self.token.to_string()
+    }
+
+    fn string(&self) -> String {
+        format!("({}?)", self.value.string())
+    }
+}
+
 //-------------------[literals]-------------------//
 
+/// Where the resolver found the declaration of a variable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    /// a variable of a function, in a slot of the call frame `depth` functions out from the use
+    Local { depth: usize, slot: usize },
+    /// a global variable, a registered function or a builtin, looked up by name
+    Global,
+}
+
+/// The call frames of a function, as laid out by the resolver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FunctionScope {
+    /// the number of slots of a call frame, the parameters take the first ones
+    pub slots: usize,
+    /// the slot that holds the function itself, if it is bound by a let statement and can call itself by that name
+    pub recursive: Option<usize>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Identifier {
     pub token: Token,
+    /// set by the resolver, unresolved variables are looked up by name
+    pub resolution: Option<Resolution>,
+}
+
+impl Identifier {
+    /// Returns the name of the identifier without copying it
+    pub fn name(&self) -> &str {
+        match &self.token {
+            Token::Id(id) => id,
+            _ => panic!(),
+        }
+    }
 }
 
 impl Node for Identifier {
@@ -131,6 +212,7 @@ impl Node for IntegerLiteral {
     fn token_literal(&self) -> String {
         match &self.token {
             Token::ConstInt(num) => num.to_string(),
+            Token::ConstBigInt(num) => num.to_string(),
             _ => panic!(),
         }
     }
@@ -161,8 +243,11 @@ impl Node for Boolean {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct FunctionLiteral {
     pub token: Token,
-    pub parameters: Vec<Identifier>,
-    pub body: BlockStatement,
+    /// the parameters and the body are shared with the functions created from the literal
+    pub parameters: Rc<Vec<Identifier>>,
+    pub body: Rc<BlockStatement>,
+    /// set by the resolver, functions without it keep their variables by name
+    pub scope: Option<FunctionScope>,
 }
 
 impl Node for FunctionLiteral {
@@ -183,12 +268,7 @@ impl Node for FunctionLiteral {
                     }
                 });
 
-        format!(
-            "{}({}) {{{}}}",
-            self.token.to_string(),
-            parameters,
-            self.body.string()
-        )
+        format!("{}({}) {{{}}}", self.token, parameters, self.body.string())
     }
 }
 
@@ -238,10 +318,53 @@ impl Node for ArrayLiteral {
     }
 }
 
+/// A tuple written as `(<exp>, <exp>...)`. A tuple of one element is written with a trailing
+/// comma, `(<exp>,)`, so it is not a grouped expression
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TupleLiteral {
+    pub token: Token,
+    pub elements: Vec<AstNode>,
+}
+
+impl Node for TupleLiteral {
+    fn token_literal(&self) -> String {
+        self.token.to_string()
+    }
+
+    fn string(&self) -> String {
+        match &self.elements[..] {
+            [element] => format!("({},)", element.string()),
+            elements => {
+                let elements: Vec<String> = elements.iter().map(AstNode::string).collect();
+                format!("({})", elements.join(", "))
+            }
+        }
+    }
+}
+
+/// A set written as `#{<exp>, <exp>...}`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SetLiteral {
+    pub token: Token,
+    pub elements: Vec<AstNode>,
+}
+
+impl Node for SetLiteral {
+    fn token_literal(&self) -> String {
+        self.token.to_string()
+    }
+
+    fn string(&self) -> String {
+        let elements: Vec<String> = self.elements.iter().map(AstNode::string).collect();
+        format!("#{{{}}}", elements.join(", "))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DictLiteral {
     pub token: Token,
-    pub pairs: HashMap<AstNode, AstNode>,
+    /// the key and value of each entry, in the order they are written
+    pub pairs: Vec<(AstNode, AstNode)>,
 }
 
 impl Node for DictLiteral {
@@ -277,6 +400,8 @@ pub struct CallExpression {
     pub token: Token,
     pub function: Box<AstNode>,
     pub arguments: Vec<AstNode>,
+    /// the position of the start of the called expression
+    pub span: Span,
 }
 
 impl Node for CallExpression {
@@ -304,6 +429,18 @@ impl Node for CallExpression {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PostIncrement {
     pub token: Token,
+    /// set by the resolver, unresolved variables are looked up by name
+    pub resolution: Option<Resolution>,
+}
+
+impl PostIncrement {
+    /// Returns the name of the incremented variable without copying it
+    pub fn name(&self) -> &str {
+        match &self.token {
+            Token::Id(id) => id,
+            _ => panic!(),
+        }
+    }
 }
 
 impl Node for PostIncrement {
@@ -322,6 +459,18 @@ impl Node for PostIncrement {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PostDecrement {
     pub token: Token,
+    /// set by the resolver, unresolved variables are looked up by name
+    pub resolution: Option<Resolution>,
+}
+
+impl PostDecrement {
+    /// Returns the name of the decremented variable without copying it
+    pub fn name(&self) -> &str {
+        match &self.token {
+            Token::Id(id) => id,
+            _ => panic!(),
+        }
+    }
 }
 
 impl Node for PostDecrement {
@@ -18,14 +18,19 @@ pub enum AstNode {
     InfixExpression(InfixExpression),
     Boolean(Boolean),
     IfExpression(IfExpression),
+    TryExpression(TryExpression),
     FunctionLiteral(FunctionLiteral),
     StringLiteral(StringLiteral),
     ArrayLiteral(ArrayLiteral),
+    TupleLiteral(TupleLiteral),
+    SetLiteral(SetLiteral),
     DictLiteral(DictLiteral),
     IndexExpression(IndexExpression),
+    PropagationExpression(PropagationExpression),
     CallExpression(CallExpression),
     LetStatement(LetStatement),
     ReturnStatement(ReturnStatement),
+    ThrowStatement(ThrowStatement),
     BlockStatement(BlockStatement),
     Assignation(Assignation),
     PostIncrement(PostIncrement),
@@ -46,14 +51,19 @@ impl Node for AstNode {
             AstNode::InfixExpression(expression) => expression.token_literal(),
             AstNode::Boolean(expression) => expression.token_literal(),
             AstNode::IfExpression(expression) => expression.token_literal(),
+            AstNode::TryExpression(expression) => expression.token_literal(),
             AstNode::FunctionLiteral(expression) => expression.token_literal(),
             AstNode::CallExpression(expression) => expression.token_literal(),
             AstNode::LetStatement(statement) => statement.token_literal(),
             AstNode::ReturnStatement(statement) => statement.token_literal(),
+            AstNode::ThrowStatement(statement) => statement.token_literal(),
             AstNode::BlockStatement(statement) => statement.token_literal(),
             AstNode::StringLiteral(statement) => statement.token_literal(),
             AstNode::ArrayLiteral(statement) => statement.token_literal(),
+            AstNode::TupleLiteral(statement) => statement.token_literal(),
+            AstNode::SetLiteral(statement) => statement.token_literal(),
             AstNode::IndexExpression(statement) => statement.token_literal(),
+            AstNode::PropagationExpression(statement) => statement.token_literal(),
             AstNode::DictLiteral(statement) => statement.token_literal(),
             AstNode::Assignation(statement) => statement.token_literal(),
             AstNode::PostIncrement(statement) => statement.token_literal(),
@@ -74,14 +84,19 @@ impl Node for AstNode {
             AstNode::InfixExpression(expression) => expression.string(),
             AstNode::Boolean(expression) => expression.string(),
             AstNode::IfExpression(expression) => expression.string(),
+            AstNode::TryExpression(expression) => expression.string(),
             AstNode::FunctionLiteral(expression) => expression.string(),
             AstNode::CallExpression(expression) => expression.string(),
             AstNode::LetStatement(statement) => statement.string(),
             AstNode::ReturnStatement(statement) => statement.string(),
+            AstNode::ThrowStatement(statement) => statement.string(),
             AstNode::BlockStatement(statement) => statement.string(),
             AstNode::StringLiteral(statement) => statement.string(),
             AstNode::ArrayLiteral(statement) => statement.string(),
+            AstNode::TupleLiteral(statement) => statement.string(),
+            AstNode::SetLiteral(statement) => statement.string(),
             AstNode::IndexExpression(statement) => statement.string(),
+            AstNode::PropagationExpression(statement) => statement.string(),
             AstNode::DictLiteral(statement) => statement.string(),
             AstNode::Assignation(statement) => statement.string(),
             AstNode::PostIncrement(statement) => statement.string(),
@@ -101,7 +116,7 @@ pub struct Program {
 
 impl Node for Program {
     fn token_literal(&self) -> String {
-        if self.statements.len() > 0 {
+        if !self.statements.is_empty() {
             self.statements[0].token_literal()
         } else {
             "".to_string()
@@ -37,6 +37,22 @@ impl Node for ReturnStatement {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ThrowStatement {
+    pub token: Token,
+    pub value: Box<AstNode>,
+}
+
+impl Node for ThrowStatement {
+    fn token_literal(&self) -> String {
+        self.token.to_string()
+    }
+
+    fn string(&self) -> String {
+        format!("throw {};", self.value.string())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BlockStatement {
     pub token: Token,
@@ -57,13 +73,13 @@ impl Node for BlockStatement {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Operation {
     Sum,
     Minus,
     Mult,
     Divide,
-    Assig
+    Assig,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -71,7 +87,7 @@ pub struct Assignation {
     pub token: Token,
     pub name: Identifier,
     pub value: Box<AstNode>,
-    pub operation: Operation
+    pub operation: Operation,
 }
 
 impl Node for Assignation {
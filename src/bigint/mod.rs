@@ -0,0 +1,413 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+#[cfg(test)]
+mod test;
+
+/// The base of the limbs of a magnitude
+const BASE: u64 = 1 << 32;
+/// The largest power of ten that fits in a limb, used to parse and print in chunks of 9 digits
+const DECIMAL_BASE: u32 = 1_000_000_000;
+const DECIMAL_DIGITS: usize = 9;
+
+/// An integer of any size, stored as a sign and a magnitude of 32 bit limbs, the least significant
+/// first. The magnitude never ends in a zero limb and zero is never negative, so every integer has a
+/// single representation and the derived equality and hash can be used
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    /**
+    Returns an integer from its sign and magnitude, removing the leading zero limbs
+
+    # Arguments
+    * `negative` - if the integer is negative, ignored for zero
+    * `magnitude` - the limbs of the absolute value, the least significant first
+    */
+    fn new(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        let negative = negative && !magnitude.is_empty();
+        Self {
+            negative,
+            magnitude,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /**
+    Returns the integer as an `i128`, or None if it doesn't fit
+
+    # Arguments
+
+    no arguments
+    */
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.magnitude.len() > 4 {
+            return None;
+        }
+        let magnitude = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0u128, |acc, &limb| (acc << 32) | limb as u128);
+
+        if self.negative {
+            0i128.checked_sub_unsigned(magnitude)
+        } else {
+            i128::try_from(magnitude).ok()
+        }
+    }
+
+    /**
+    Returns the number of bytes used by the limbs of the integer
+
+    # Arguments
+
+    no arguments
+    */
+    pub fn size(&self) -> usize {
+        self.magnitude.len() * std::mem::size_of::<u32>()
+    }
+
+    /**
+    Returns the quotient and the remainder of the division, truncated toward zero like the division of
+    `i128`: the remainder has the sign of the dividend. Panics if the divisor is zero
+
+    # Arguments
+    * `divisor` - the integer to divide by
+    */
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        assert!(!divisor.is_zero(), "division by zero");
+        let (quotient, remainder) = div_rem_magnitude(&self.magnitude, &divisor.magnitude);
+        (
+            Self::new(self.negative != divisor.negative, quotient),
+            Self::new(self.negative, remainder),
+        )
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(value: i128) -> Self {
+        let mut magnitude = value.unsigned_abs();
+        let mut limbs = vec![];
+        while magnitude > 0 {
+            limbs.push(magnitude as u32);
+            magnitude >>= 32;
+        }
+        Self::new(value < 0, limbs)
+    }
+}
+
+/// The error of parsing a string that is not a decimal integer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBigIntError;
+
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    /**
+    Parses a decimal integer with an optional leading minus sign
+
+    # Arguments
+    * `s` - the digits to parse
+    */
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        if digits.is_empty() || !digits.bytes().all(|digit| digit.is_ascii_digit()) {
+            return Err(ParseBigIntError);
+        }
+
+        let mut magnitude = vec![];
+        let first = digits.len() % DECIMAL_DIGITS;
+        let chunks = std::iter::once(&digits[..first]).chain(
+            digits.as_bytes()[first..]
+                .chunks(DECIMAL_DIGITS)
+                .map(|chunk| std::str::from_utf8(chunk).unwrap()),
+        );
+        for chunk in chunks.filter(|chunk| !chunk.is_empty()) {
+            let multiplier = 10u32.pow(chunk.len() as u32);
+            mul_add_small(&mut magnitude, multiplier, chunk.parse().unwrap());
+        }
+
+        Ok(Self::new(negative, magnitude))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut chunks = vec![];
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            chunks.push(div_rem_small(&mut magnitude, DECIMAL_BASE));
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        match chunks.split_last() {
+            None => write!(f, "0"),
+            Some((first, rest)) => {
+                write!(f, "{}", first)?;
+                for chunk in rest.iter().rev() {
+                    write!(f, "{:0width$}", chunk, width = DECIMAL_DIGITS)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => cmp_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(
+                self.negative,
+                add_magnitude(&self.magnitude, &other.magnitude),
+            );
+        }
+        match cmp_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::new(
+                other.negative,
+                sub_magnitude(&other.magnitude, &self.magnitude),
+            ),
+            _ => BigInt::new(
+                self.negative,
+                sub_magnitude(&self.magnitude, &other.magnitude),
+            ),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::new(
+            self.negative != other.negative,
+            mul_magnitude(&self.magnitude, &other.magnitude),
+        )
+    }
+}
+
+fn cmp_magnitude(left: &[u32], right: &[u32]) -> Ordering {
+    left.len()
+        .cmp(&right.len())
+        .then_with(|| left.iter().rev().cmp(right.iter().rev()))
+}
+
+fn add_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let (long, short) = if left.len() >= right.len() {
+        (left, right)
+    } else {
+        (right, left)
+    };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &limb) in long.iter().enumerate() {
+        let total = limb as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+/// Subtracts two magnitudes, the left one must not be smaller than the right one
+fn sub_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(left.len());
+    let mut borrow = 0i64;
+    for (i, &limb) in left.iter().enumerate() {
+        let mut total = limb as i64 - *right.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if total < 0 {
+            total += BASE as i64;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+    difference
+}
+
+fn mul_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; left.len() + right.len()];
+    for (i, &l) in left.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &r) in right.iter().enumerate() {
+            let total = l as u64 * r as u64 + product[i + j] as u64 + carry;
+            product[i + j] = total as u32;
+            carry = total >> 32;
+        }
+        product[i + right.len()] = carry as u32;
+    }
+    product
+}
+
+/// Multiplies a magnitude by a limb and adds another one to it, in place
+fn mul_add_small(magnitude: &mut Vec<u32>, multiplier: u32, addend: u32) {
+    let mut carry = addend as u64;
+    for limb in magnitude.iter_mut() {
+        let total = *limb as u64 * multiplier as u64 + carry;
+        *limb = total as u32;
+        carry = total >> 32;
+    }
+    if carry > 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+/// Divides a magnitude by a limb in place, removing its leading zeros, and returns the remainder
+fn div_rem_small(magnitude: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut remainder = 0u64;
+    for limb in magnitude.iter_mut().rev() {
+        let dividend = (remainder << 32) | *limb as u64;
+        *limb = (dividend / divisor as u64) as u32;
+        remainder = dividend % divisor as u64;
+    }
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+    remainder as u32
+}
+
+/// Shifts a magnitude left by less than a limb, adding a limb for the bits shifted out
+fn shl_bits(magnitude: &[u32], shift: u32) -> Vec<u32> {
+    let mut shifted = Vec::with_capacity(magnitude.len() + 1);
+    let mut carry = 0u32;
+    for &limb in magnitude {
+        shifted.push((limb << shift) | carry);
+        carry = if shift == 0 { 0 } else { limb >> (32 - shift) };
+    }
+    shifted.push(carry);
+    shifted
+}
+
+/**
+Returns the quotient and the remainder of two magnitudes with the long division of Knuth's
+algorithm D, which guesses each limb of the quotient from the leading limbs and corrects it
+
+# Arguments
+* `dividend` - the magnitude to divide
+* `divisor` - the magnitude to divide by, not zero
+*/
+fn div_rem_magnitude(dividend: &[u32], divisor: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_magnitude(dividend, divisor) == Ordering::Less {
+        return (vec![], dividend.to_vec());
+    }
+    if divisor.len() == 1 {
+        let mut quotient = dividend.to_vec();
+        let remainder = div_rem_small(&mut quotient, divisor[0]);
+        return (quotient, vec![remainder]);
+    }
+
+    // normalize so that the leading limb of the divisor has its top bit set
+    let shift = divisor.last().unwrap().leading_zeros();
+    let mut divisor = shl_bits(divisor, shift);
+    divisor.pop();
+    let mut remainder = shl_bits(dividend, shift);
+    let n = divisor.len();
+    let mut quotient = vec![0u32; remainder.len() - n];
+
+    for j in (0..quotient.len()).rev() {
+        let top = ((remainder[j + n] as u64) << 32) | remainder[j + n - 1] as u64;
+        let mut guess = top / divisor[n - 1] as u64;
+        let mut rest = top % divisor[n - 1] as u64;
+        while guess >= BASE
+            || guess * divisor[n - 2] as u64 > ((rest << 32) | remainder[j + n - 2] as u64)
+        {
+            guess -= 1;
+            rest += divisor[n - 1] as u64;
+            if rest >= BASE {
+                break;
+            }
+        }
+
+        // subtract the divisor times the guess from the current limbs
+        let mut borrow = 0i64;
+        for i in 0..n {
+            let product = guess * divisor[i] as u64;
+            let total = remainder[i + j] as i64 - borrow - (product & 0xFFFF_FFFF) as i64;
+            remainder[i + j] = total as u32;
+            borrow = (product >> 32) as i64 - (total >> 32);
+        }
+        let total = remainder[j + n] as i64 - borrow;
+        remainder[j + n] = total as u32;
+
+        // the guess was one too big, add the divisor back
+        if total < 0 {
+            guess -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let total = remainder[i + j] as u64 + divisor[i] as u64 + carry;
+                remainder[i + j] = total as u32;
+                carry = total >> 32;
+            }
+            remainder[j + n] = remainder[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = guess as u32;
+    }
+
+    remainder.truncate(n);
+    let remainder = (0..n)
+        .map(|i| {
+            let high = if shift == 0 {
+                0
+            } else {
+                *remainder.get(i + 1).unwrap_or(&0) << (32 - shift)
+            };
+            (remainder[i] >> shift) | high
+        })
+        .collect();
+    (quotient, remainder)
+}
@@ -0,0 +1,163 @@
+use super::*;
+
+#[test]
+fn test_parse_and_display() {
+    let tests = vec![
+        "0",
+        "7",
+        "-7",
+        "4294967296",
+        "170141183460469231731687303715884105728",
+        "-340282366920938463463374607431768211456",
+        "1000000000000000000000000000000000000000000000000000000001",
+    ];
+
+    for input in tests {
+        assert_eq!(input, big(input).to_string());
+    }
+
+    assert_eq!("0", big("-0").to_string());
+    assert_eq!("12", big("0000012").to_string());
+    assert!("".parse::<BigInt>().is_err());
+    assert!("12a".parse::<BigInt>().is_err());
+}
+
+#[test]
+fn test_i128_conversions() {
+    let tests = vec![0, 1, -1, 4294967296, i128::MAX, i128::MIN, i128::MIN + 1];
+
+    for value in tests {
+        let converted = BigInt::from(value);
+        assert_eq!(value.to_string(), converted.to_string());
+        assert_eq!(Some(value), converted.to_i128());
+    }
+
+    assert_eq!(
+        None,
+        big("170141183460469231731687303715884105728").to_i128()
+    );
+    assert_eq!(
+        None,
+        big("-170141183460469231731687303715884105729").to_i128()
+    );
+}
+
+#[test]
+fn test_arithmetic() {
+    let values = [
+        0,
+        1,
+        -1,
+        7,
+        -13,
+        4294967295,
+        -4294967296,
+        123456789012345678,
+        -9876543210987654321,
+        i64::MAX as i128,
+        i64::MIN as i128,
+    ];
+
+    for &left in &values {
+        for &right in &values {
+            let (l, r) = (BigInt::from(left), BigInt::from(right));
+            assert_eq!(BigInt::from(left + right), &l + &r, "{} + {}", left, right);
+            assert_eq!(BigInt::from(left - right), &l - &r, "{} - {}", left, right);
+            assert_eq!(BigInt::from(left * right), &l * &r, "{} * {}", left, right);
+            assert_eq!(left.cmp(&right), l.cmp(&r), "{} cmp {}", left, right);
+            if right != 0 {
+                let (quotient, remainder) = l.div_rem(&r);
+                assert_eq!(BigInt::from(left / right), quotient, "{} / {}", left, right);
+                assert_eq!(
+                    BigInt::from(left % right),
+                    remainder,
+                    "{} % {}",
+                    left,
+                    right
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_division_of_many_limbs() {
+    // pseudo random integers of every length up to 127 bits and both signs, to go through every
+    // path of the long division, checked against the division of `i128`
+    let mut seed = 0x2545_f491_4f6c_dd1du128;
+    let mut next = |bits: u32| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed as i128) >> (128 - bits)
+    };
+
+    for i in 0..2000 {
+        let left = next(1 + i % 127);
+        let right = next(1 + (i / 127) % 127);
+        if right == 0 {
+            continue;
+        }
+        let (quotient, remainder) = BigInt::from(left).div_rem(&BigInt::from(right));
+        assert_eq!(BigInt::from(left / right), quotient, "{} / {}", left, right);
+        assert_eq!(
+            BigInt::from(left % right),
+            remainder,
+            "{} % {}",
+            left,
+            right
+        );
+    }
+}
+
+#[test]
+fn test_big_arithmetic() {
+    let factorial = (1..=30).fold(BigInt::from(1), |acc, n| &acc * &BigInt::from(n));
+    assert_eq!("265252859812191058636308480000000", factorial.to_string());
+
+    let power = (0..200).fold(BigInt::from(1), |acc, _| &acc * &BigInt::from(2));
+    assert_eq!(
+        "1606938044258990275541962092341162602522202993782792835301376",
+        power.to_string()
+    );
+    assert_eq!(
+        big("1606938044258990275541962092341162602522202993782792835301375"),
+        &power - &BigInt::from(1)
+    );
+    assert_eq!(
+        "-1606938044258990275541962092341162602522202993782792835301376",
+        (-&power).to_string()
+    );
+
+    let dividend = big("123456789012345678901234567890123456789012345678901234567890");
+    let divisors = [
+        "3",
+        "-4294967297",
+        "98765432109876543210987654321",
+        "-340282366920938463463374607431768211457",
+        "123456789012345678901234567890123456789012345678901234567891",
+    ];
+    for divisor in divisors {
+        let divisor = big(divisor);
+        let (quotient, remainder) = dividend.div_rem(&divisor);
+        assert_eq!(dividend, &(&quotient * &divisor) + &remainder);
+        assert!(remainder.is_zero() || !remainder.is_negative());
+        assert_eq!(
+            Ordering::Less,
+            cmp_magnitude(&remainder.magnitude, &divisor.magnitude)
+        );
+    }
+
+    let (quotient, remainder) = (-&dividend).div_rem(&big("1000000000000"));
+    assert_eq!(
+        "-123456789012345678901234567890123456789012345678",
+        quotient.to_string()
+    );
+    assert_eq!("-901234567890", remainder.to_string());
+}
+
+//-------------------[Test helpers]-------------------//
+
+fn big(value: &str) -> BigInt {
+    value.parse().unwrap()
+}
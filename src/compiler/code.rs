@@ -0,0 +1,252 @@
+use std::fmt::Display;
+use std::rc::Rc;
+
+use crate::ast::expressions::FunctionLiteral;
+use crate::ast::statements::Operation;
+use crate::object::{Object, ObjectType};
+use crate::token::Span;
+
+/// Where the value of a variable is kept while a chunk runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Place {
+    /// a variable looked up by name, at an index of the names table
+    Global(u32),
+    /// a slot of the call frame of the running function
+    Local(u32),
+    /// a variable of an enclosing function, at an index of the values captured by the running closure
+    Upvalue(u32),
+}
+
+/// An infix operator, decoded when the chunk is compiled so that the virtual machine can evaluate it
+/// on integers without looking at its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+    /// any other operator, at an index of the names table
+    Other(u32),
+}
+
+/// A variable of an enclosing function that a closure captures when it is created
+#[derive(Debug, Clone, PartialEq)]
+pub struct Upvalue {
+    pub name: String,
+    /// where the variable is in the function that creates the closure, one of its locals or upvalues
+    pub source: Place,
+}
+
+/// An instruction of the virtual machine. The operands are indexes into the tables of the `Chunk`
+/// that contains the instruction, or positions of other instructions for the jumps.
+/// The instructions that produce an error or a return value start unwinding with it, so the values
+/// on the stack are never errors
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    /// pushes a constant
+    Constant(u32),
+    /// pushes null
+    Null,
+    /// pushes a break
+    Break,
+    /// discards the value on top of the stack
+    Pop,
+    /// counts a step of the program, taken by every call and every loop iteration
+    Step,
+    /// pushes the value of a variable, or the registered or builtin function with its name
+    GetVariable(Place),
+    /// binds the value on top of the stack to a variable, leaving it on the stack
+    Let(Place),
+    /// assigns the value on top of the stack to a variable and replaces it with the new value of the variable
+    Assign(Place, Operation),
+    /// adds one to an integer variable and pushes the new value
+    Increment(Place),
+    /// subtracts one from an integer variable and pushes the new value
+    Decrement(Place),
+    /// applies the prefix operator with the name to the value on top of the stack
+    Prefix(u32),
+    /// applies the infix operator to the two values on top of the stack
+    Infix(Operator),
+    /// replaces the values on top of the stack with an array of them
+    Array(u32),
+    /// replaces the values on top of the stack with a tuple of them
+    Tuple(u32),
+    /// replaces the values on top of the stack with a set of them
+    Set(u32),
+    /// replaces the key and value pairs on top of the stack with a dict of them
+    Dict(u32),
+    /// raises an error if the value on top of the stack can't be used as a dict key
+    CheckKey,
+    /// replaces the value and the index on top of the stack with the indexed element
+    Index,
+    /// unwraps the result on top of the stack, or returns it from the function if it is an err
+    Propagate,
+    /// raises the value on top of the stack as an error
+    Throw,
+    /// returns the value on top of the stack from the function
+    Return,
+    /// pushes a closure of the function at the index, capturing its upvalues
+    Closure(u32),
+    /// calls the function below the arguments on top of the stack
+    Call { argc: u32, span: u32 },
+    /// replaces the running call with a call to the function below the arguments on top of the stack
+    TailCall { argc: u32, span: u32 },
+    /// continues at the position
+    Jump(u32),
+    /// discards the value on top of the stack and continues at the position if it is not truthy
+    JumpIfFalse(u32),
+    /// discards the value on top of the stack and continues at the position if it is truthy
+    JumpIfTrue(u32),
+    /// unwinds if the value on top of the stack is an error or a return value
+    Unwind,
+    /// unwinds if the value on top of the stack is an error, a return value or a break,
+    /// which stop the statements of a block
+    UnwindStatement,
+    /// installs a handler that continues at the position when unwinding
+    PushHandler(u32),
+    /// removes the innermost handler
+    PopHandler,
+    /// handles the unwinding of a loop: a break is replaced with null and continues at the position,
+    /// anything else keeps unwinding
+    BreakLoop(u32),
+    /// handles the unwinding of a try block: an error is bound to the variable and discarded,
+    /// anything else continues at the position
+    Catch { variable: Place, end: u32 },
+    /// unbinds the global variable of a catch block after the block, binding again the one it shadowed
+    EndCatch(Place),
+    /// finishes the chunk with the value on top of the stack
+    Finish,
+}
+
+/// A function literal compiled ahead of time. The closures created from it share its bytecode
+#[derive(Debug, Clone)]
+pub struct Prototype {
+    pub literal: FunctionLiteral,
+    pub chunk: Rc<Chunk>,
+}
+
+/// The bytecode of a program or function body, with the tables its instructions refer to
+#[derive(Debug, Clone, Default)]
+pub struct Chunk {
+    pub instructions: Vec<Instruction>,
+    /// the literal values
+    pub constants: Vec<ObjectType>,
+    /// the names of the operators and of the variables looked up by name
+    pub names: Vec<String>,
+    /// the names of the local slots, the resolver gives each variable of a function its own slot
+    pub locals: Vec<String>,
+    /// the variables of the enclosing functions captured by the closures of the chunk
+    pub upvalues: Vec<Upvalue>,
+    /// the function literals of the chunk
+    pub functions: Vec<Prototype>,
+    /// the positions of the calls in the source code
+    pub spans: Vec<Span>,
+}
+
+impl Chunk {
+    /**
+    Returns the operator or variable at an index of the names table
+
+    # Arguments
+    * `index` - the index of the name
+    */
+    pub fn name(&self, index: u32) -> &str {
+        &self.names[index as usize]
+    }
+
+    /**
+    Returns the name of an infix operator, as written in the source code
+
+    # Arguments
+    * `operator` - the operator
+    */
+    pub fn operator(&self, operator: Operator) -> &str {
+        match operator {
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::Modulo => "%",
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+            Operator::Less => "<",
+            Operator::Greater => ">",
+            Operator::LessEqual => "<=",
+            Operator::GreaterEqual => ">=",
+            Operator::Other(index) => self.name(index),
+        }
+    }
+
+    /**
+    Returns the name of the variable kept in a place
+
+    # Arguments
+    * `place` - the place of the variable
+    */
+    pub fn variable(&self, place: Place) -> &str {
+        match place {
+            Place::Global(index) => self.name(index),
+            Place::Local(slot) => &self.locals[slot as usize],
+            Place::Upvalue(index) => &self.upvalues[index as usize].name,
+        }
+    }
+}
+
+/// Chunks are displayed as a listing of their instructions, one per line
+impl Display for Chunk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, instruction) in self.instructions.iter().enumerate() {
+            write!(f, "{:04} ", i)?;
+            match instruction {
+                Instruction::Constant(index) => {
+                    writeln!(f, "Constant {}", self.constants[*index as usize].inspect())
+                }
+                Instruction::GetVariable(place) => {
+                    writeln!(f, "GetVariable {:?} {}", place, self.variable(*place))
+                }
+                Instruction::Let(place) => {
+                    writeln!(f, "Let {:?} {}", place, self.variable(*place))
+                }
+                Instruction::Increment(place) => {
+                    writeln!(f, "Increment {:?} {}", place, self.variable(*place))
+                }
+                Instruction::Decrement(place) => {
+                    writeln!(f, "Decrement {:?} {}", place, self.variable(*place))
+                }
+                Instruction::Prefix(index) => writeln!(f, "Prefix {}", self.name(*index)),
+                Instruction::Infix(operator) => writeln!(f, "Infix {}", self.operator(*operator)),
+                Instruction::Assign(place, operation) => {
+                    writeln!(
+                        f,
+                        "Assign {:?} {} {:?}",
+                        place,
+                        self.variable(*place),
+                        operation
+                    )
+                }
+                Instruction::Catch { variable, end } => {
+                    writeln!(
+                        f,
+                        "Catch {:?} {} {}",
+                        variable,
+                        self.variable(*variable),
+                        end
+                    )
+                }
+                Instruction::EndCatch(place) => {
+                    writeln!(f, "EndCatch {:?} {}", place, self.variable(*place))
+                }
+                other => writeln!(f, "{:?}", other),
+            }?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,683 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ast::expressions::{
+    ForLoop, FunctionLiteral, IfExpression, Loop, Resolution, TryExpression, WhileLoop,
+};
+use crate::ast::statements::{BlockStatement, LetStatement};
+use crate::ast::{AstNode, Program};
+use crate::object::{BigInteger, Boolean, Integer, ObjectType, StringObject};
+use crate::token::Token;
+
+use self::code::{Chunk, Instruction, Operator, Place, Prototype, Upvalue};
+
+pub mod code;
+#[cfg(test)]
+mod test;
+
+/// The compiler translates the syntax tree to the bytecode run by the virtual machine of the `Evaluator`.
+/// The function literals are compiled ahead of time with the program that contains them. The variables the
+/// resolver gave a slot live in the call frames of the virtual machine, and closures capture the variables
+/// of the enclosing functions they use when they are created. The rest of the variables are looked up by name
+pub struct Compiler {
+    /// the chunk being compiled
+    chunk: Chunk,
+    /// the index of each constant in the chunk
+    constants: HashMap<ObjectType, u32>,
+    /// the index of each name in the chunk
+    names: HashMap<String, u32>,
+    /// the depth and slot of each upvalue of the chunk, as given by the resolver
+    captures: Vec<(usize, usize)>,
+}
+
+impl Compiler {
+    fn new() -> Self {
+        Self {
+            chunk: Chunk::default(),
+            constants: HashMap::new(),
+            names: HashMap::new(),
+            captures: vec![],
+        }
+    }
+
+    /**
+    Returns the bytecode of a program, with the bytecode of its functions. It finishes with the value
+    of the last statement, or with the first error or return value
+
+    # Arguments
+    * `program` - the program to compile
+
+    # Examples
+    ```
+    let program = parser.parse_program();
+    let chunk = Compiler::compile_program(&program);
+    ```
+    */
+    pub fn compile_program(program: &Program) -> Chunk {
+        let mut compiler = Self::new();
+        let last = program.statements.len().saturating_sub(1);
+
+        for (i, statement) in program.statements.iter().enumerate() {
+            compiler.node(statement);
+            if i != last {
+                compiler.emit(Instruction::Pop);
+            }
+        }
+        if program.statements.is_empty() {
+            compiler.emit(Instruction::Null);
+        }
+
+        compiler.finish()
+    }
+
+    /**
+    Compiles a function literal in a compiler of its own and returns its index in the functions of the chunk.
+    Calls in tail position finish the chunk with a tail call, see `Evaluator::eval_tail`.
+    The variables of enclosing functions used by the literal become upvalues of its chunk, taken from
+    the locals of this chunk or from its own upvalues
+
+    # Arguments
+    * `literal` - the function literal to compile
+    */
+    fn function(&mut self, literal: &FunctionLiteral) -> u32 {
+        let mut compiler = Self::new();
+        if let Some(scope) = literal.scope {
+            compiler.chunk.locals = vec![String::new(); scope.slots];
+            for (slot, parameter) in literal.parameters.iter().enumerate() {
+                compiler.chunk.locals[slot] = parameter.name().to_string();
+            }
+        }
+        compiler.emit(Instruction::Step);
+        compiler.tail_block(&literal.body, true);
+
+        let captures = std::mem::take(&mut compiler.captures);
+        let mut chunk = compiler.finish();
+        for (upvalue, (depth, slot)) in chunk.upvalues.iter_mut().zip(captures) {
+            upvalue.source = if depth == 1 {
+                self.local(slot, &upvalue.name)
+            } else {
+                Place::Upvalue(self.upvalue(depth - 1, slot, &upvalue.name))
+            };
+        }
+
+        self.chunk.functions.push(Prototype {
+            literal: literal.clone(),
+            chunk: Rc::new(chunk),
+        });
+        (self.chunk.functions.len() - 1) as u32
+    }
+
+    fn finish(mut self) -> Chunk {
+        self.emit(Instruction::Finish);
+        self.chunk
+    }
+
+    /**
+    Appends an instruction to the chunk and returns its position
+
+    # Arguments
+    * `instruction` - the instruction to append
+    */
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        self.chunk.instructions.push(instruction);
+        self.chunk.instructions.len() - 1
+    }
+
+    /**
+    Returns the position of the next instruction, as a jump target
+
+    # Arguments
+
+    no arguments
+    */
+    fn position(&self) -> u32 {
+        self.chunk.instructions.len() as u32
+    }
+
+    /**
+    Sets the target of the jump or handler at a position to the next instruction
+
+    # Arguments
+    * `at` - the position of the jump
+    */
+    fn patch(&mut self, at: usize) {
+        let target = self.position();
+        match &mut self.chunk.instructions[at] {
+            Instruction::Jump(position)
+            | Instruction::JumpIfFalse(position)
+            | Instruction::JumpIfTrue(position)
+            | Instruction::PushHandler(position)
+            | Instruction::BreakLoop(position)
+            | Instruction::Catch { end: position, .. } => *position = target,
+            other => panic!("not a jump: {:?}", other),
+        }
+    }
+
+    fn constant(&mut self, value: ObjectType) -> u32 {
+        if let Some(index) = self.constants.get(&value) {
+            return *index;
+        }
+        let index = self.chunk.constants.len() as u32;
+        self.chunk.constants.push(value.clone());
+        self.constants.insert(value, index);
+        index
+    }
+
+    fn name(&mut self, name: &str) -> u32 {
+        if let Some(index) = self.names.get(name) {
+            return *index;
+        }
+        let index = self.chunk.names.len() as u32;
+        self.chunk.names.push(name.to_string());
+        self.names.insert(name.to_string(), index);
+        index
+    }
+
+    /**
+    Returns the decoded infix operator with a name
+
+    # Arguments
+    * `name` - the name of the operator
+    */
+    fn operator(&mut self, name: &str) -> Operator {
+        match name {
+            "+" => Operator::Add,
+            "-" => Operator::Subtract,
+            "*" => Operator::Multiply,
+            "/" => Operator::Divide,
+            "%" => Operator::Modulo,
+            "==" => Operator::Equal,
+            "!=" => Operator::NotEqual,
+            "<" => Operator::Less,
+            ">" => Operator::Greater,
+            "<=" => Operator::LessEqual,
+            ">=" => Operator::GreaterEqual,
+            other => Operator::Other(self.name(other)),
+        }
+    }
+
+    /**
+    Returns the place of a local slot, naming the slot after its variable
+
+    # Arguments
+    * `slot` - the slot of the variable
+    * `name` - the name of the variable
+    */
+    fn local(&mut self, slot: usize, name: &str) -> Place {
+        if self.chunk.locals.len() <= slot {
+            self.chunk.locals.resize(slot + 1, String::new());
+        }
+        if self.chunk.locals[slot].is_empty() {
+            self.chunk.locals[slot] = name.to_string();
+        }
+        Place::Local(slot as u32)
+    }
+
+    /**
+    Returns the index of the upvalue of a variable declared by an enclosing function, adding it to the chunk
+    the first time it is used
+
+    # Arguments
+    * `depth` - the number of functions out from this one that declares the variable
+    * `slot` - the slot of the variable in that function
+    * `name` - the name of the variable
+    */
+    fn upvalue(&mut self, depth: usize, slot: usize, name: &str) -> u32 {
+        if let Some(index) = self
+            .captures
+            .iter()
+            .position(|capture| *capture == (depth, slot))
+        {
+            return index as u32;
+        }
+        self.captures.push((depth, slot));
+        self.chunk.upvalues.push(Upvalue {
+            name: name.to_string(),
+            source: Place::Local(slot as u32),
+        });
+        (self.captures.len() - 1) as u32
+    }
+
+    /**
+    Returns where a variable is kept, from the resolution given by the resolver
+
+    # Arguments
+    * `name` - the name of the variable
+    * `resolution` - where the resolver found the variable, if it was resolved
+    */
+    fn place(&mut self, name: &str, resolution: Option<Resolution>) -> Place {
+        match resolution {
+            Some(Resolution::Local { depth: 0, slot }) => self.local(slot, name),
+            Some(Resolution::Local { depth, slot }) => {
+                Place::Upvalue(self.upvalue(depth, slot, name))
+            }
+            _ => Place::Global(self.name(name)),
+        }
+    }
+
+    /**
+    Compiles a node so that it leaves its value on the stack, mirroring `Evaluator::eval`.
+    Errors and return values unwind from the instruction that produces them
+
+    # Arguments
+    * `node` - the node to compile
+    */
+    fn node(&mut self, node: &AstNode) {
+        match node {
+            AstNode::BlockStatement(block) => self.statements(&block.statements),
+            AstNode::PrefixExpression(prefix_expression) => {
+                self.node(&prefix_expression.right);
+                let operator = self.name(&prefix_expression.operator);
+                self.emit(Instruction::Prefix(operator));
+            }
+            AstNode::InfixExpression(infix_expression) => {
+                self.node(&infix_expression.left);
+                self.node(&infix_expression.right);
+                let operator = self.operator(&infix_expression.operator);
+                self.emit(Instruction::Infix(operator));
+            }
+            AstNode::IfExpression(if_expression) => self.if_expression(if_expression, None),
+            AstNode::TryExpression(try_expression) => self.try_expression(try_expression),
+            AstNode::FunctionLiteral(function_literal) => {
+                let index = self.function(function_literal);
+                self.emit(Instruction::Closure(index));
+            }
+            AstNode::CallExpression(call) => {
+                self.node(&call.function);
+                for argument in &call.arguments {
+                    self.node(argument);
+                }
+                let span = self.chunk.spans.len() as u32;
+                self.chunk.spans.push(call.span);
+                self.emit(Instruction::Call {
+                    argc: call.arguments.len() as u32,
+                    span,
+                });
+            }
+            AstNode::LetStatement(let_statement) => self.let_statement(let_statement),
+            AstNode::ReturnStatement(return_statement) => {
+                self.node(&return_statement.return_value);
+                self.emit(Instruction::Return);
+            }
+            AstNode::ThrowStatement(throw_statement) => {
+                self.node(&throw_statement.value);
+                self.emit(Instruction::Throw);
+            }
+            AstNode::Identifier(id) => {
+                let place = self.place(id.name(), id.resolution);
+                self.emit(Instruction::GetVariable(place));
+            }
+            AstNode::IntegerLiteral(integer_literal) => {
+                let value = match &integer_literal.token {
+                    Token::ConstInt(val) => ObjectType::Integer(Integer { value: *val }),
+                    Token::ConstBigInt(val) => {
+                        ObjectType::BigInteger(BigInteger { value: val.clone() })
+                    }
+                    _ => panic!("Not a valid number"),
+                };
+                let index = self.constant(value);
+                self.emit(Instruction::Constant(index));
+            }
+            AstNode::Boolean(boolean_literal) => {
+                let value = match boolean_literal.token {
+                    Token::ConstBool(val) => val,
+                    _ => panic!("Not a valid boolean"),
+                };
+                let index = self.constant(ObjectType::Boolean(Boolean { value }));
+                self.emit(Instruction::Constant(index));
+            }
+            AstNode::StringLiteral(string_literal) => {
+                let value = match &string_literal.token {
+                    Token::ConstStr(val) => val.clone(),
+                    _ => panic!("Not a valid string"),
+                };
+                let index = self.constant(ObjectType::String(StringObject { value }));
+                self.emit(Instruction::Constant(index));
+            }
+            AstNode::ArrayLiteral(array_literal) => {
+                for element in &array_literal.elements {
+                    self.node(element);
+                }
+                self.emit(Instruction::Array(array_literal.elements.len() as u32));
+            }
+            AstNode::TupleLiteral(tuple_literal) => {
+                for element in &tuple_literal.elements {
+                    self.node(element);
+                }
+                self.emit(Instruction::Tuple(tuple_literal.elements.len() as u32));
+            }
+            AstNode::SetLiteral(set_literal) => {
+                for element in &set_literal.elements {
+                    self.node(element);
+                    self.emit(Instruction::CheckKey);
+                }
+                self.emit(Instruction::Set(set_literal.elements.len() as u32));
+            }
+            AstNode::IndexExpression(index_expression) => {
+                self.node(&index_expression.left);
+                self.node(&index_expression.index);
+                self.emit(Instruction::Index);
+            }
+            AstNode::PropagationExpression(propagation) => {
+                self.node(&propagation.value);
+                self.emit(Instruction::Propagate);
+            }
+            AstNode::DictLiteral(dict) => {
+                for (key, value) in dict.pairs.iter() {
+                    self.node(key);
+                    self.emit(Instruction::CheckKey);
+                    self.node(value);
+                }
+                self.emit(Instruction::Dict(dict.pairs.len() as u32));
+            }
+            AstNode::Assignation(assig) => {
+                self.node(&assig.value);
+                let place = self.place(assig.name.name(), assig.name.resolution);
+                self.emit(Instruction::Assign(place, assig.operation));
+            }
+            AstNode::PostIncrement(inc) => {
+                let place = self.place(inc.name(), inc.resolution);
+                self.emit(Instruction::Increment(place));
+            }
+            AstNode::PostDecrement(dec) => {
+                let place = self.place(dec.name(), dec.resolution);
+                self.emit(Instruction::Decrement(place));
+            }
+            AstNode::ForLoop(for_loop) => self.for_loop(for_loop),
+            AstNode::WhileLoop(while_loop) => self.while_loop(while_loop),
+            AstNode::Loop(loop_exp) => self.loop_expression(loop_exp),
+            AstNode::Break => {
+                self.emit(Instruction::Break);
+            }
+            _ => panic!("Ast node not treated"),
+        }
+    }
+
+    /**
+    Compiles a list of statements, mirroring `Evaluator::eval_statements`. A break stops the statements,
+    so it is checked for after the statements that can evaluate to one
+
+    # Arguments
+    * `statements` - the statements to compile
+    */
+    fn statements(&mut self, statements: &[AstNode]) {
+        let last = statements.len().saturating_sub(1);
+
+        for (i, statement) in statements.iter().enumerate() {
+            self.node(statement);
+            if can_break(statement) {
+                self.emit(Instruction::UnwindStatement);
+            }
+            if i != last {
+                self.emit(Instruction::Pop);
+            }
+        }
+        if statements.is_empty() {
+            self.emit(Instruction::Null);
+        }
+    }
+
+    /**
+    Compiles a node of a function body, mirroring `Evaluator::eval_tail`
+
+    # Arguments
+    * `node` - the node to compile
+    * `tail` - if the value of the node is the value of the function
+    */
+    fn tail(&mut self, node: &AstNode, tail: bool) {
+        match node {
+            AstNode::BlockStatement(block) => self.tail_block(block, tail),
+            AstNode::IfExpression(if_expression) => self.if_expression(if_expression, Some(tail)),
+            AstNode::ReturnStatement(return_statement) => {
+                self.tail(&return_statement.return_value, true);
+                self.emit(Instruction::Return);
+            }
+            AstNode::CallExpression(call) if tail => {
+                self.node(&call.function);
+                for argument in &call.arguments {
+                    self.node(argument);
+                }
+                let span = self.chunk.spans.len() as u32;
+                self.chunk.spans.push(call.span);
+                self.emit(Instruction::TailCall {
+                    argc: call.arguments.len() as u32,
+                    span,
+                });
+            }
+            other => self.node(other),
+        }
+    }
+
+    /**
+    Compiles a block of a function body, mirroring `Evaluator::eval_tail_block`
+
+    # Arguments
+    * `block` - the block to compile
+    * `tail` - if the value of the block is the value of the function
+    */
+    fn tail_block(&mut self, block: &BlockStatement, tail: bool) {
+        let last = block.statements.len().saturating_sub(1);
+
+        for (i, statement) in block.statements.iter().enumerate() {
+            let tail = tail && i == last;
+            self.tail(statement, tail);
+            if can_break(statement) && !(tail && matches!(statement, AstNode::CallExpression(_))) {
+                self.emit(Instruction::UnwindStatement);
+            }
+            if i != last {
+                self.emit(Instruction::Pop);
+            }
+        }
+        if block.statements.is_empty() {
+            self.emit(Instruction::Null);
+        }
+    }
+
+    /**
+    Compiles a let statement, mirroring `Evaluator::eval_let_statement`
+
+    # Arguments
+    * `let_statement` - the let statement to compile
+    */
+    fn let_statement(&mut self, let_statement: &LetStatement) {
+        self.node(&let_statement.value);
+        let name = &let_statement.name;
+        let place = self.place(name.name(), name.resolution);
+        self.emit(Instruction::Let(place));
+    }
+
+    /**
+    Compiles an if expression. The branches are compiled as blocks, or as blocks of a function body
+    if `tail` is given
+
+    # Arguments
+    * `if_expression` - the if expression to compile
+    * `tail` - if the value of the expression is the value of the function, when it is part of a function body
+    */
+    fn if_expression(&mut self, if_expression: &IfExpression, tail: Option<bool>) {
+        let branch = |compiler: &mut Self, block: &BlockStatement| match tail {
+            Some(tail) => compiler.tail_block(block, tail),
+            None => compiler.statements(&block.statements),
+        };
+        let mut ends = vec![];
+
+        let branches = std::iter::once((&*if_expression.condition, &if_expression.consequence))
+            .chain(if_expression.elifs.iter().map(|(cond, cons)| (cond, cons)));
+        for (condition, consequence) in branches {
+            self.node(condition);
+            let next = self.emit(Instruction::JumpIfFalse(0));
+            branch(self, consequence);
+            ends.push(self.emit(Instruction::Jump(0)));
+            self.patch(next);
+        }
+
+        match &if_expression.alternative {
+            Some(alternative) => branch(self, alternative),
+            None => {
+                self.emit(Instruction::Null);
+            }
+        }
+        for end in ends {
+            self.patch(end);
+        }
+    }
+
+    /**
+    Compiles a try expression, mirroring `Evaluator::eval_try_expression`. The try and catch blocks
+    run inside handlers, so the value they unwind with becomes their value. An error that is not caught
+    and a return value keep unwinding once the finally block has run
+
+    # Arguments
+    * `try_expression` - the try expression to compile
+    */
+    fn try_expression(&mut self, try_expression: &TryExpression) {
+        let handler = self.emit(Instruction::PushHandler(0));
+        self.statements(&try_expression.block.statements);
+        self.emit(Instruction::PopHandler);
+        self.patch(handler);
+
+        if let Some((parameter, block)) = &try_expression.catch {
+            let variable = self.place(parameter.name(), parameter.resolution);
+            let catch = self.emit(Instruction::Catch { variable, end: 0 });
+            let handler = self.emit(Instruction::PushHandler(0));
+            self.statements(&block.statements);
+            self.emit(Instruction::PopHandler);
+            self.patch(handler);
+            if let Place::Global(_) = variable {
+                self.emit(Instruction::EndCatch(variable));
+            }
+            self.patch(catch);
+        }
+
+        if let Some(finally) = &try_expression.finally {
+            let handler = self.emit(Instruction::PushHandler(0));
+            self.statements(&finally.statements);
+            self.emit(Instruction::PopHandler);
+            self.patch(handler);
+            self.emit(Instruction::UnwindStatement);
+            self.emit(Instruction::Pop);
+        }
+
+        self.emit(Instruction::Unwind);
+    }
+
+    /**
+    Compiles a for loop, mirroring `Evaluator::eval_for_loop_expression`. The result of the step
+    expression is discarded even if it is an error
+
+    # Arguments
+    * `for_loop` - the for loop to compile
+    */
+    fn for_loop(&mut self, for_loop: &ForLoop) {
+        let handler = self.emit(Instruction::PushHandler(0));
+        self.let_statement(&for_loop.initialization);
+        self.emit(Instruction::Pop);
+        self.emit(Instruction::Null);
+        self.node(&for_loop.condition);
+        let end = self.emit(Instruction::JumpIfFalse(0));
+
+        let body = self.loop_body(&for_loop.statements);
+        let step = self.emit(Instruction::PushHandler(0));
+        self.node(&for_loop.step);
+        self.emit(Instruction::PopHandler);
+        self.patch(step);
+        self.emit(Instruction::Pop);
+        self.node(&for_loop.condition);
+        self.emit(Instruction::JumpIfTrue(body));
+
+        self.patch(end);
+        self.loop_exit(handler);
+    }
+
+    /**
+    Compiles a while loop, mirroring `Evaluator::eval_while_loop_expression`
+
+    # Arguments
+    * `while_loop` - the while loop to compile
+    */
+    fn while_loop(&mut self, while_loop: &WhileLoop) {
+        let handler = self.emit(Instruction::PushHandler(0));
+        self.emit(Instruction::Null);
+        self.node(&while_loop.condition);
+        let end = self.emit(Instruction::JumpIfFalse(0));
+
+        let body = self.loop_body(&while_loop.statements);
+        self.node(&while_loop.condition);
+        self.emit(Instruction::JumpIfTrue(body));
+
+        self.patch(end);
+        self.loop_exit(handler);
+    }
+
+    /**
+    Compiles an iteration of a loop with a condition and returns its position. The value of the previous
+    iteration, null before the first one, is replaced with the value of the body
+
+    # Arguments
+    * `body` - the body of the loop
+    */
+    fn loop_body(&mut self, body: &BlockStatement) -> u32 {
+        let start = self.position();
+        self.emit(Instruction::Step);
+        self.emit(Instruction::Pop);
+        self.statements(&body.statements);
+        start
+    }
+
+    /**
+    Compiles the end of a loop with a condition: the removal of the handler of the loop when the condition
+    is false, and the handler itself, which turns a break into null
+
+    # Arguments
+    * `handler` - the position of the instruction that installs the handler of the loop
+    */
+    fn loop_exit(&mut self, handler: usize) {
+        self.emit(Instruction::PopHandler);
+        let exit = self.emit(Instruction::Jump(0));
+        self.patch(handler);
+        let break_loop = self.emit(Instruction::BreakLoop(0));
+        self.patch(break_loop);
+        self.patch(exit);
+    }
+
+    /**
+    Compiles an infinite loop, mirroring `Evaluator::eval_loop_expression`
+
+    # Arguments
+    * `loop_exp` - the loop to compile
+    */
+    fn loop_expression(&mut self, loop_exp: &Loop) {
+        let handler = self.emit(Instruction::PushHandler(0));
+        let start = self.position();
+        self.emit(Instruction::Step);
+        self.statements(&loop_exp.statements.statements);
+        self.emit(Instruction::Pop);
+        self.emit(Instruction::Jump(start));
+
+        self.patch(handler);
+        let exit = self.emit(Instruction::BreakLoop(0));
+        self.patch(exit);
+    }
+}
+
+/**
+Returns if the value of a statement can be a break, which stops the block that contains it. Errors and
+return values unwind from the instruction that produces them, and the blocks of if and loop expressions
+stop at their own breaks, but the value of a variable or of a call can be a break too
+
+# Arguments
+* `statement` - the statement to check
+*/
+fn can_break(statement: &AstNode) -> bool {
+    match statement {
+        AstNode::Break
+        | AstNode::Identifier(_)
+        | AstNode::IndexExpression(_)
+        | AstNode::CallExpression(_)
+        | AstNode::PropagationExpression(_)
+        | AstNode::TryExpression(_) => true,
+        AstNode::LetStatement(let_statement) => can_break(&let_statement.value),
+        AstNode::Assignation(assig) => can_break(&assig.value),
+        _ => false,
+    }
+}
@@ -0,0 +1,155 @@
+use super::*;
+use crate::lexer::Lexer;
+use crate::object::Object;
+use crate::parser::Parser;
+use crate::resolver::Resolver;
+
+#[test]
+fn test_compile_expression() {
+    let chunk = compile("1 + x");
+
+    assert_eq!(
+        "0000 Constant 1
+0001 GetVariable Global(0) x
+0002 Infix +
+0003 Finish
+",
+        chunk.to_string()
+    );
+}
+
+#[test]
+fn test_shared_tables() {
+    let chunk = compile(r#"let x = 1; x = x + 1; [x, 1, "a", "a", x + 2]"#);
+
+    assert_eq!(
+        vec!["1", "a", "2"],
+        chunk
+            .constants
+            .iter()
+            .map(|constant| constant.inspect())
+            .collect::<Vec<String>>()
+    );
+    assert_eq!(vec!["x"], chunk.names);
+}
+
+#[test]
+fn test_resolved_variables() {
+    let chunk = compile("let f = fun(a) { let b = a; b++; b }");
+    let body = &chunk.functions[0].chunk;
+
+    assert_eq!(vec!["a", "", "b"], body.locals);
+    assert_eq!(
+        vec![
+            Instruction::GetVariable(Place::Local(0)),
+            Instruction::Let(Place::Local(2)),
+            Instruction::Increment(Place::Local(2)),
+            Instruction::GetVariable(Place::Local(2)),
+        ],
+        body.instructions
+            .iter()
+            .copied()
+            .filter(|instruction| matches!(
+                instruction,
+                Instruction::GetVariable(_) | Instruction::Let(_) | Instruction::Increment(_)
+            ))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_compile_loop() {
+    let chunk = compile("while (x) { break; }");
+
+    assert_eq!(
+        "0000 PushHandler(12)
+0001 Null
+0002 GetVariable Global(0) x
+0003 JumpIfFalse(10)
+0004 Step
+0005 Pop
+0006 Break
+0007 UnwindStatement
+0008 GetVariable Global(0) x
+0009 JumpIfTrue(4)
+0010 PopHandler
+0011 Jump(13)
+0012 BreakLoop(13)
+0013 Finish
+",
+        chunk.to_string()
+    );
+}
+
+#[test]
+fn test_compile_function() {
+    let chunk =
+        compile("let f = fun(n) { f(n); if (n) { return f(n); } let x = f(n); [f(n)]; f(n) }");
+    assert_eq!(1, chunk.functions.len());
+
+    let body = &chunk.functions[0].chunk;
+    let calls = |tail: bool| {
+        body.instructions
+            .iter()
+            .filter(|instruction| match instruction {
+                Instruction::Call { .. } => !tail,
+                Instruction::TailCall { .. } => tail,
+                _ => false,
+            })
+            .count()
+    };
+
+    assert_eq!(3, calls(false));
+    assert_eq!(2, calls(true));
+    assert_eq!(Some(&Instruction::Step), body.instructions.first());
+    assert_eq!(Some(&Instruction::Finish), body.instructions.last());
+}
+
+#[test]
+fn test_compile_upvalues() {
+    let chunk = compile("let f = fun(a) { let b = 1; fun() { fun() { a + b } } }");
+
+    let outer = &chunk.functions[0].chunk;
+    let middle = &outer.functions[0].chunk;
+    let inner = &middle.functions[0].chunk;
+
+    assert!(outer.upvalues.is_empty());
+    assert_eq!(
+        vec![
+            Upvalue {
+                name: "a".to_string(),
+                source: Place::Local(0)
+            },
+            Upvalue {
+                name: "b".to_string(),
+                source: Place::Local(2)
+            },
+        ],
+        middle.upvalues
+    );
+    assert_eq!(
+        vec![
+            Upvalue {
+                name: "a".to_string(),
+                source: Place::Upvalue(0)
+            },
+            Upvalue {
+                name: "b".to_string(),
+                source: Place::Upvalue(1)
+            },
+        ],
+        inner.upvalues
+    );
+}
+
+//-------------------[Test helpers]-------------------//
+
+fn compile(input: &str) -> Chunk {
+    let mut program = match Parser::new(Lexer::new(input.to_string())).parse_program() {
+        AstNode::Program(program) => program,
+        other => panic!("Expected a program, got {:?}", other),
+    };
+    assert!(Resolver::new(|_| true).resolve(&mut program).is_empty());
+
+    Compiler::compile_program(&program)
+}
@@ -0,0 +1,316 @@
+use std::fmt::Display;
+use std::io::Write;
+use std::{fs, io, path::Path};
+
+use crate::ast::AstNode;
+use crate::evaluator::Evaluator;
+use crate::lexer::Lexer;
+use crate::object::convert::TypedFunction;
+use crate::object::{Error, Object, ObjectType, NAME_ERROR};
+use crate::optimizer;
+use crate::parser::Parser;
+use crate::resolver::Resolver;
+
+#[cfg(test)]
+mod test;
+
+/// A Halcon value as seen by the host application
+pub type Value = ObjectType;
+
+/// The ways in which running Halcon code from the host application can fail
+#[derive(Debug)]
+pub enum HalconError {
+    /// the source file could not be read
+    Io(io::Error),
+    /// the source code has syntax errors, one message per error
+    Parse(Vec<String>),
+    /// the source code uses variables that are not defined or declares them twice, one message per error
+    Resolve(Vec<String>),
+    /// the program raised an error that was not caught
+    Runtime(Error),
+}
+
+impl Display for HalconError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HalconError::Io(error) => write!(f, "{}", error),
+            HalconError::Parse(errors) => write!(f, "syntax errors: {}", errors.join(", ")),
+            HalconError::Resolve(errors) => write!(f, "scope errors: {}", errors.join(", ")),
+            HalconError::Runtime(error) => write!(f, "{}", error.inspect()),
+        }
+    }
+}
+
+impl std::error::Error for HalconError {}
+
+impl From<io::Error> for HalconError {
+    fn from(error: io::Error) -> Self {
+        HalconError::Io(error)
+    }
+}
+
+impl From<Error> for HalconError {
+    fn from(error: Error) -> Self {
+        HalconError::Runtime(error)
+    }
+}
+
+/// The engine is the entry point for host applications that embed Halcon. It runs the
+/// Lexer -> Parser -> Resolver -> Optimizer -> Evaluator pipeline and keeps the global variables between runs
+pub struct Engine {
+    evaluator: Evaluator,
+    /// if the programs are optimized before they run
+    optimize: bool,
+}
+
+impl Engine {
+    /**
+    Returns an Engine with an empty global environment
+
+    # Arguments
+
+    no arguments
+
+    # Examples
+    ```
+    let mut engine = Engine::new();
+    ```
+    */
+    pub fn new() -> Self {
+        Self::with_evaluator(Evaluator::new())
+    }
+
+    /**
+    Returns an Engine that runs the code with the given `Evaluator`, keeping its environment and limits
+
+    # Arguments
+    * `evaluator` - the evaluator to use
+
+    # Examples
+    ```
+    let mut evaluator = Evaluator::new();
+    evaluator.set_max_depth(100);
+    let mut engine = Engine::with_evaluator(evaluator);
+    ```
+    */
+    pub fn with_evaluator(evaluator: Evaluator) -> Self {
+        Self {
+            evaluator,
+            optimize: true,
+        }
+    }
+
+    /**
+    Returns the `Evaluator` used by the engine, to configure its limits
+
+    # Arguments
+
+    no arguments
+
+    # Examples
+    ```
+    engine.evaluator().set_step_limit(Some(1_000_000));
+    ```
+    */
+    pub fn evaluator(&mut self) -> &mut Evaluator {
+        &mut self.evaluator
+    }
+
+    /**
+    Sets if the programs are optimized before they run, folding constant expressions and pruning
+    the branches that never run. It is enabled by default, disabling it runs the code as written
+
+    # Arguments
+    * `optimize` - if the programs are optimized
+
+    # Examples
+    ```
+    engine.set_optimization(false);
+    ```
+    */
+    pub fn set_optimization(&mut self, optimize: bool) {
+        self.optimize = optimize;
+    }
+
+    /**
+    Sets the writer that receives the output of the Halcon code, e.g. to capture it.
+    See `Evaluator::set_output`
+
+    # Arguments
+    * `output` - the writer to use
+
+    # Examples
+    ```
+    engine.set_output(io::stderr());
+    ```
+    */
+    pub fn set_output(&mut self, output: impl Write + 'static) {
+        self.evaluator.set_output(output);
+    }
+
+    /**
+    Runs the source code and returns the value of its last statement. The global variables
+    defined by the code are kept for later calls
+
+    # Arguments
+    * `source` - the Halcon code to run
+
+    # Examples
+    ```
+    let value = engine.eval_str("let x = 1 + 2; x")?;
+    ```
+    */
+    pub fn eval_str(&mut self, source: &str) -> Result<Value, HalconError> {
+        let mut parser = Parser::new(Lexer::new(source.to_string()));
+        let mut program = parser.parse_program();
+
+        if !parser.errors().is_empty() {
+            return Err(HalconError::Parse(parser.errors()));
+        }
+
+        if let AstNode::Program(program) = &mut program {
+            let evaluator = &self.evaluator;
+            let errors = Resolver::new(|name| evaluator.defines(name)).resolve(program);
+            if !errors.is_empty() {
+                return Err(HalconError::Resolve(errors));
+            }
+
+            if self.optimize {
+                optimizer::optimize(program);
+            }
+        }
+
+        into_result(self.evaluator.eval(&program))
+    }
+
+    /**
+    Runs the source code in a file and returns the value of its last statement
+
+    # Arguments
+    * `path` - the path of the file to run
+
+    # Examples
+    ```
+    let value = engine.eval_file("input.hc")?;
+    ```
+    */
+    pub fn eval_file(&mut self, path: impl AsRef<Path>) -> Result<Value, HalconError> {
+        let source = fs::read_to_string(path)?;
+        self.eval_str(&source)
+    }
+
+    /**
+    Binds a value to a global variable, replacing the previous value if there was one
+
+    # Arguments
+    * `name` - the name of the variable
+    * `value` - the value to bind
+
+    # Examples
+    ```
+    engine.set_global("limit", Value::Integer(Integer { value: 10 }));
+    ```
+    */
+    pub fn set_global(&mut self, name: &str, value: Value) {
+        self.evaluator.env.set(name, value);
+    }
+
+    /**
+    Returns the value of a global variable, or a `NameError` if it is not defined
+
+    # Arguments
+    * `name` - the name of the variable
+
+    # Examples
+    ```
+    let limit = engine.get_global("limit")?;
+    ```
+    */
+    pub fn get_global(&self, name: &str) -> Result<Value, HalconError> {
+        match self.evaluator.env.get(name) {
+            Some(value) => Ok(value.clone()),
+            None => Err(HalconError::Runtime(Error::new(
+                NAME_ERROR,
+                format!("identifier not found: {}", name),
+            ))),
+        }
+    }
+
+    /**
+    Registers a Rust closure as a function that the Halcon code can call by name.
+    See `Evaluator::register_function`
+
+    # Arguments
+    * `name` - the name of the function
+    * `function` - the closure to call with the evaluator and the arguments
+
+    # Examples
+    ```
+    engine.register_function("answer", |_, _| Ok(Value::Integer(Integer { value: 42 })));
+    ```
+    */
+    pub fn register_function<F>(&mut self, name: &str, function: F)
+    where
+        F: Fn(&mut Evaluator, Vec<Value>) -> Result<Value, Error> + 'static,
+    {
+        self.evaluator.register_function(name, function);
+    }
+
+    /**
+    Registers a Rust function with typed arguments that the Halcon code can call by name.
+    See `Evaluator::register_typed_function`
+
+    # Arguments
+    * `name` - the name of the function
+    * `function` - the function to call with the converted arguments
+
+    # Examples
+    ```
+    engine.register_typed_function("add", |a: i64, b: i64| a + b);
+    ```
+    */
+    pub fn register_typed_function<Args>(
+        &mut self,
+        name: &str,
+        function: impl TypedFunction<Args>,
+    ) {
+        self.evaluator.register_typed_function(name, function);
+    }
+
+    /**
+    Calls a global, registered or builtin function with the given arguments and returns the result
+
+    # Arguments
+    * `name` - the name of the function
+    * `args` - the arguments to pass to the function
+
+    # Examples
+    ```
+    engine.eval_str("let add = fun(a, b) { a + b };")?;
+    let sum = engine.call_function("add", vec![one, two])?;
+    ```
+    */
+    pub fn call_function(&mut self, name: &str, args: Vec<Value>) -> Result<Value, HalconError> {
+        let function = into_result(self.evaluator.resolve(name))?;
+        into_result(self.evaluator.call_function(function, args))
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+Returns the error of an `ObjectType::Error` as a `HalconError`, and any other object as a `Value`
+
+# Arguments
+* `obj` - the result of the evaluation
+*/
+fn into_result(obj: ObjectType) -> Result<Value, HalconError> {
+    match obj {
+        ObjectType::Error(error) => Err(HalconError::Runtime(error)),
+        value => Ok(value),
+    }
+}
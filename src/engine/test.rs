@@ -0,0 +1,343 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::rc::Rc;
+
+use super::*;
+use crate::evaluator::Backend;
+use crate::object::{
+    Integer, StringObject, ARGUMENT_ERROR, STACK_OVERFLOW_ERROR, TYPE_ERROR, ZERO_DIVISION_ERROR,
+};
+
+#[test]
+fn test_eval_str() {
+    let tests = vec![
+        ("1 + 2", "3"),
+        (r#""hola" + " " + "mundo""#, "hola mundo"),
+        ("let x = [1, 2]; push(x, 3)", "[1, 2, 3]"),
+        ("", "null"),
+    ];
+
+    for (input, expected) in tests {
+        let mut engine = Engine::new();
+
+        assert_eq!(expected, engine.eval_str(input).unwrap().inspect());
+    }
+}
+
+#[test]
+fn test_eval_str_errors() {
+    let mut engine = Engine::new();
+
+    match engine.eval_str("let = 5;") {
+        Err(HalconError::Parse(errors)) => assert!(!errors.is_empty()),
+        actual => panic!("Expected a parse error, got {:?}", actual),
+    }
+
+    match engine.eval_str("1 + true") {
+        Err(HalconError::Runtime(error)) => {
+            assert_eq!(TYPE_ERROR, error.kind);
+            assert_eq!("type mismatch: INTEGER + BOOLEAN", error.message);
+        }
+        actual => panic!("Expected a runtime error, got {:?}", actual),
+    }
+}
+
+#[test]
+fn test_deep_recursion_on_a_default_thread() {
+    for backend in [Backend::Ast, Backend::Vm] {
+        let result = std::thread::spawn(move || {
+            let mut engine = Engine::new();
+            engine.evaluator().set_backend(backend);
+            match engine.eval_str("let f = fun(x) { 1 + f(x + 1) }; f(0)") {
+                Err(HalconError::Runtime(error)) => error.kind,
+                actual => panic!("Expected a runtime error, got {:?}", actual),
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(STACK_OVERFLOW_ERROR, result, "{:?}", backend);
+    }
+}
+
+#[test]
+fn test_globals_are_kept() {
+    let mut engine = Engine::new();
+
+    engine.eval_str("let x = 5;").unwrap();
+    engine.eval_str("let double = fun(n) { n * 2 };").unwrap();
+
+    assert_eq!("10", engine.eval_str("double(x)").unwrap().inspect());
+}
+
+#[test]
+fn test_scope_errors() {
+    let mut engine = Engine::new();
+
+    match engine.eval_str("let x = 1; let x = y;") {
+        Err(HalconError::Resolve(errors)) => assert_eq!(
+            vec![
+                "variable x is already declared in this scope",
+                "variable y is not defined"
+            ],
+            errors
+        ),
+        actual => panic!("Expected scope errors, got {:?}", actual),
+    }
+
+    engine.set_global("y", Value::Integer(Integer { value: 2 }));
+    engine.register_function("answer", |_, _| Ok(Value::Integer(Integer { value: 42 })));
+    engine.eval_str("let x = answer() + y;").unwrap();
+    assert_eq!("44", engine.eval_str("let x = x + 0; x").unwrap().inspect());
+}
+
+#[test]
+fn test_optimization() {
+    let tests = vec![
+        ("60 * 60 * 24", "86400"),
+        (
+            r#"if (1 > 2) { "a" } elif (true) { "b" } else { "c" }"#,
+            "b",
+        ),
+        ("let x = 2; if (false) { x = 3 }; x", "2"),
+    ];
+
+    for optimize in [true, false] {
+        let mut engine = Engine::new();
+        engine.set_optimization(optimize);
+
+        for (input, expected) in tests.clone() {
+            assert_eq!(expected, engine.eval_str(input).unwrap().inspect());
+        }
+
+        match engine.eval_str("let f = fun() { 60 / (2 - 2) };\nf()") {
+            Err(HalconError::Runtime(error)) => {
+                assert_eq!(ZERO_DIVISION_ERROR, error.kind);
+                assert_eq!("    at f (<engine>:2:1)\n", error.trace("<engine>"));
+            }
+            actual => panic!("Expected a runtime error, got {:?}", actual),
+        }
+    }
+}
+
+#[test]
+fn test_eval_file() {
+    let path = std::env::temp_dir().join(format!("halcon_engine_{}.hc", std::process::id()));
+    fs::write(&path, "let x = 20;\nx + 1").unwrap();
+
+    let mut engine = Engine::new();
+    let result = engine.eval_file(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!("21", result.unwrap().inspect());
+
+    match engine.eval_file("does_not_exist.hc") {
+        Err(HalconError::Io(_)) => (),
+        actual => panic!("Expected an io error, got {:?}", actual),
+    }
+}
+
+#[test]
+fn test_set_and_get_global() {
+    let mut engine = Engine::new();
+
+    engine.set_global(
+        "name",
+        Value::String(StringObject {
+            value: "halcon".into(),
+        }),
+    );
+    engine
+        .eval_str(r#"let greeting = "hola " + name;"#)
+        .unwrap();
+
+    assert_eq!(
+        "hola halcon",
+        engine.get_global("greeting").unwrap().inspect()
+    );
+
+    match engine.get_global("missing") {
+        Err(HalconError::Runtime(error)) => {
+            assert_eq!(NAME_ERROR, error.kind);
+            assert_eq!("identifier not found: missing", error.message);
+        }
+        actual => panic!("Expected a name error, got {:?}", actual),
+    }
+}
+
+#[test]
+fn test_call_function() {
+    let mut engine = Engine::new();
+    engine
+        .eval_str("let add = fun(a, b) { a + b }; let fail = fun() { 1 + true };")
+        .unwrap();
+
+    let int = |value| Value::Integer(Integer { value });
+
+    assert_eq!(
+        int(5),
+        engine.call_function("add", vec![int(2), int(3)]).unwrap()
+    );
+    let array = engine.eval_str("[1, 2]").unwrap();
+    assert_eq!(int(2), engine.call_function("len", vec![array]).unwrap());
+
+    let errors = vec![
+        ("add", vec![int(1)], ARGUMENT_ERROR),
+        ("fail", vec![], TYPE_ERROR),
+        ("missing", vec![], NAME_ERROR),
+    ];
+
+    for (name, args, kind) in errors {
+        match engine.call_function(name, args) {
+            Err(HalconError::Runtime(error)) => assert_eq!(kind, error.kind, "{}", name),
+            actual => panic!("Expected a runtime error from {}, got {:?}", name, actual),
+        }
+    }
+}
+
+#[test]
+fn test_register_function() {
+    let config = Rc::new(RefCell::new(HashMap::new()));
+    config
+        .borrow_mut()
+        .insert("host".to_string(), "localhost".to_string());
+
+    let mut engine = Engine::new();
+
+    let reader = config.clone();
+    engine.register_function("config", move |_, args| match &args[..] {
+        [Value::String(key)] => match reader.borrow().get(&*key.value) {
+            Some(value) => Ok(Value::String(StringObject {
+                value: value.as_str().into(),
+            })),
+            None => Err(Error::new(
+                NAME_ERROR,
+                format!("no config for {}", key.value),
+            )),
+        },
+        _ => Err(Error::new(ARGUMENT_ERROR, "config expects a key")),
+    });
+
+    let writer = config.clone();
+    engine.register_function("set_config", move |_, args| match &args[..] {
+        [Value::String(key), Value::String(value)] => {
+            writer
+                .borrow_mut()
+                .insert(key.value.to_string(), value.value.to_string());
+            Ok(Value::Null)
+        }
+        _ => Err(Error::new(
+            ARGUMENT_ERROR,
+            "set_config expects a key and a value",
+        )),
+    });
+
+    assert_eq!(
+        "localhost",
+        engine.eval_str(r#"config("host")"#).unwrap().inspect()
+    );
+
+    engine.eval_str(r#"set_config("port", "8080")"#).unwrap();
+    assert_eq!(Some(&"8080".to_string()), config.borrow().get("port"));
+
+    match engine.eval_str(r#"let get = fun(key) { config(key) }; get("user")"#) {
+        Err(HalconError::Runtime(error)) => {
+            assert_eq!(NAME_ERROR, error.kind);
+            assert_eq!("no config for user", error.message);
+            assert_eq!(1, error.stack.len());
+        }
+        actual => panic!("Expected a runtime error, got {:?}", actual),
+    }
+}
+
+#[test]
+fn test_register_typed_function() {
+    let mut engine = Engine::new();
+    engine.register_typed_function("add", |a: i64, b: i64| a + b);
+    engine.register_typed_function("repeat", |text: String, times: usize| text.repeat(times));
+    engine.register_typed_function("total", |numbers: Vec<i64>| numbers.iter().sum::<i64>());
+    engine.register_typed_function("lookup", |table: HashMap<String, i64>, key: String| {
+        table.get(&key).copied()
+    });
+    engine.register_typed_function("checked_div", |a: i64, b: i64| match a.checked_div(b) {
+        Some(result) => Ok(result),
+        None => Err(Error::new(ARGUMENT_ERROR, "cannot divide by zero")),
+    });
+    engine.register_typed_function("version", || "1.0");
+
+    let tests = vec![
+        ("add(2, 3)", "5"),
+        (r#"repeat("ab", 3)"#, "ababab"),
+        ("total([1, 2, 3])", "6"),
+        (r#"lookup({"a": 1}, "a")"#, "1"),
+        (r#"lookup({"a": 1}, "b")"#, "null"),
+        ("checked_div(7, 2)", "3"),
+        ("version()", "1.0"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(
+            expected,
+            engine.eval_str(input).unwrap().inspect(),
+            "{}",
+            input
+        );
+    }
+
+    let errors = vec![
+        (
+            r#"add(1, "2")"#,
+            TYPE_ERROR,
+            "argument 2 of add: expected INTEGER, got STRING",
+        ),
+        (
+            "add(1)",
+            ARGUMENT_ERROR,
+            "wrong number of arguments. got: 1, want: 2",
+        ),
+        (
+            r#"total([1, "x"])"#,
+            TYPE_ERROR,
+            "argument 1 of total: element 1: expected INTEGER, got STRING",
+        ),
+        ("checked_div(1, 0)", ARGUMENT_ERROR, "cannot divide by zero"),
+    ];
+
+    for (input, kind, message) in errors {
+        match engine.eval_str(input) {
+            Err(HalconError::Runtime(error)) => {
+                assert_eq!(kind, error.kind, "{}", input);
+                assert_eq!(message, error.message, "{}", input);
+            }
+            actual => panic!("Expected a runtime error in {}, got {:?}", input, actual),
+        }
+    }
+}
+
+#[test]
+fn test_set_output() {
+    #[derive(Clone, Default)]
+    struct Captured(Rc<RefCell<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let captured = Captured::default();
+    let mut engine = Engine::new();
+    engine.set_output(captured.clone());
+
+    engine
+        .eval_str(r#"println("hola", "mundo"); print(1 + 2)"#)
+        .unwrap();
+
+    assert_eq!(b"hola mundo\n3", &captured.0.borrow()[..]);
+}
@@ -1,5 +1,9 @@
+use std::cmp::Ordering;
+use std::process::Command;
+use std::{env, fs};
+
 use super::*;
-use crate::object::{Builtin, ObjectType};
+use crate::object::{Builtin, ObjectType, ResultValue};
 
 pub fn get_builtin_function(id: &str) -> ObjectType {
     match id {
@@ -11,16 +15,49 @@ pub fn get_builtin_function(id: &str) -> ObjectType {
         "print" => ObjectType::Builtin(Builtin {
             function: printsout,
         }),
-        _ => return new_error(format!("identifier not found: {}", id)),
+        "println" => ObjectType::Builtin(Builtin { function: println }),
+        "ok" => ObjectType::Builtin(Builtin { function: ok }),
+        "err" => ObjectType::Builtin(Builtin { function: err }),
+        "is_ok" => ObjectType::Builtin(Builtin { function: is_ok }),
+        "is_err" => ObjectType::Builtin(Builtin { function: is_err }),
+        "unwrap" => ObjectType::Builtin(Builtin { function: unwrap }),
+        "unwrap_err" => ObjectType::Builtin(Builtin {
+            function: unwrap_err,
+        }),
+        "map" => ObjectType::Builtin(Builtin { function: map }),
+        "filter" => ObjectType::Builtin(Builtin { function: filter }),
+        "reduce" => ObjectType::Builtin(Builtin { function: reduce }),
+        "sort_by" => ObjectType::Builtin(Builtin { function: sort_by }),
+        "any" => ObjectType::Builtin(Builtin { function: any }),
+        "all" => ObjectType::Builtin(Builtin { function: all }),
+        "find" => ObjectType::Builtin(Builtin { function: find }),
+        "set" => ObjectType::Builtin(Builtin { function: set }),
+        "range" => ObjectType::Builtin(Builtin { function: range }),
+        "union" => ObjectType::Builtin(Builtin { function: union }),
+        "intersection" => ObjectType::Builtin(Builtin {
+            function: intersection,
+        }),
+        "difference" => ObjectType::Builtin(Builtin {
+            function: difference,
+        }),
+        "read_file" => ObjectType::Builtin(Builtin {
+            function: read_file,
+        }),
+        "write_file" => ObjectType::Builtin(Builtin {
+            function: write_file,
+        }),
+        "env" => ObjectType::Builtin(Builtin { function: env_var }),
+        "exec" => ObjectType::Builtin(Builtin { function: exec }),
+        _ => new_error(NAME_ERROR, format!("identifier not found: {}", id)),
     }
 }
 
-fn length(args: Vec<ObjectType>) -> ObjectType {
+fn length(_: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
     if args.len() != 1 {
-        return new_error(format!(
-            "wrong number of arguments. got: {}, want: 1",
-            args.len()
-        ));
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 1", args.len()),
+        );
     }
 
     match &args[0] {
@@ -30,135 +67,898 @@ fn length(args: Vec<ObjectType>) -> ObjectType {
         ObjectType::Array(a) => ObjectType::Integer(Integer {
             value: a.elements.len().try_into().unwrap(),
         }),
-        _ => new_error(format!(
-            "argument to len not supported, got {}",
-            args[0].object_type()
-        )),
+        ObjectType::Tuple(t) => ObjectType::Integer(Integer {
+            value: t.elements.len().try_into().unwrap(),
+        }),
+        ObjectType::Set(s) => ObjectType::Integer(Integer {
+            value: s.elements.len().try_into().unwrap(),
+        }),
+        ObjectType::Range(r) => match r.len() {
+            Some(value) => ObjectType::Integer(Integer { value }),
+            None => new_error(
+                ARGUMENT_ERROR,
+                format!("the length of {} is too big", r.inspect()),
+            ),
+        },
+        _ => new_error(
+            TYPE_ERROR,
+            format!(
+                "argument to len not supported, got {}",
+                args[0].object_type()
+            ),
+        ),
     }
 }
 
-fn first(args: Vec<ObjectType>) -> ObjectType {
+fn first(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
     if args.len() != 1 {
-        return new_error(format!(
-            "wrong number of arguments. got: {}, want: 1",
-            args.len()
-        ));
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 1", args.len()),
+        );
     }
 
     match &args[0] {
         ObjectType::String(s) => {
-            if s.value.len() > 0 {
-                ObjectType::String(StringObject {
-                    value: s.value.chars().next().unwrap().to_string(),
-                })
+            if !s.value.is_empty() {
+                evaluator.allocate(ObjectType::String(StringObject {
+                    value: s.value.chars().next().unwrap().to_string().into(),
+                }))
             } else {
                 ObjectType::Null
             }
         }
         ObjectType::Array(a) => {
-            if a.elements.len() > 0 {
+            if !a.elements.is_empty() {
                 a.elements[0].clone()
             } else {
                 ObjectType::Null
             }
         }
-        _ => new_error(format!(
-            "argument to first not supported, got {}",
-            args[0].object_type()
-        )),
+        _ => new_error(
+            TYPE_ERROR,
+            format!(
+                "argument to first not supported, got {}",
+                args[0].object_type()
+            ),
+        ),
     }
 }
 
-fn last(args: Vec<ObjectType>) -> ObjectType {
+fn last(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
     if args.len() != 1 {
-        return new_error(format!(
-            "wrong number of arguments. got: {}, want: 1",
-            args.len()
-        ));
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 1", args.len()),
+        );
     }
 
     match &args[0] {
         ObjectType::String(s) => {
-            if s.value.len() > 0 {
-                ObjectType::String(StringObject {
-                    value: s.value.chars().last().unwrap().to_string(),
-                })
+            if !s.value.is_empty() {
+                evaluator.allocate(ObjectType::String(StringObject {
+                    value: s.value.chars().last().unwrap().to_string().into(),
+                }))
             } else {
                 ObjectType::Null
             }
         }
         ObjectType::Array(a) => {
-            if a.elements.len() > 0 {
+            if !a.elements.is_empty() {
                 a.elements.last().unwrap().clone()
             } else {
                 ObjectType::Null
             }
         }
-        _ => new_error(format!(
-            "argument to first not supported, got {}",
-            args[0].object_type()
-        )),
+        _ => new_error(
+            TYPE_ERROR,
+            format!(
+                "argument to first not supported, got {}",
+                args[0].object_type()
+            ),
+        ),
     }
 }
 
-fn rest(args: Vec<ObjectType>) -> ObjectType {
+fn rest(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
     if args.len() != 1 {
-        return new_error(format!(
-            "wrong number of arguments. got: {}, want: 1",
-            args.len()
-        ));
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 1", args.len()),
+        );
     }
 
     match &args[0] {
         ObjectType::String(s) => {
-            if s.value.len() > 0 {
-                ObjectType::String(StringObject {
-                    value: s.value[1..].to_string(),
-                })
+            if !s.value.is_empty() {
+                evaluator.allocate(ObjectType::String(StringObject {
+                    value: s.value[1..].into(),
+                }))
             } else {
                 ObjectType::Null
             }
         }
         ObjectType::Array(a) => {
-            if a.elements.len() > 0 {
-                ObjectType::Array(Array {
-                    elements: a.elements[1..].to_vec(),
-                })
+            if !a.elements.is_empty() {
+                evaluator.allocate(ObjectType::Array(Array {
+                    elements: Rc::new(a.elements[1..].to_vec()),
+                }))
             } else {
                 ObjectType::Null
             }
         }
-        _ => new_error(format!(
-            "argument to first not supported, got {}",
-            args[0].object_type()
-        )),
+        _ => new_error(
+            TYPE_ERROR,
+            format!(
+                "argument to first not supported, got {}",
+                args[0].object_type()
+            ),
+        ),
     }
 }
 
-fn push(args: Vec<ObjectType>) -> ObjectType {
+fn push(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
     if args.len() != 2 {
-        return new_error(format!(
-            "wrong number of arguments. got: {}, want: 2",
-            args.len()
-        ));
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 2", args.len()),
+        );
     }
 
-    match &args[0] {
-        ObjectType::Array(a) => {
-            let mut new_arr = a.elements.clone();
-            new_arr.push(args[1].clone());
-            ObjectType::Array(Array { elements: new_arr })
+    let mut args = args;
+    let element = args.pop().unwrap();
+    match args.pop().unwrap() {
+        // the elements are only copied if another value shares them, otherwise only the new one is allocated
+        ObjectType::Array(mut a) => {
+            let copied = Rc::strong_count(&a.elements) > 1;
+            Rc::make_mut(&mut a.elements).push(element);
+            let array = ObjectType::Array(a);
+            if copied {
+                evaluator.allocate(array)
+            } else {
+                evaluator.allocate_bytes(std::mem::size_of::<ObjectType>(), array)
+            }
         }
-        _ => new_error(format!(
-            "argument to first not supported, got {}",
-            args[0].object_type()
+        other => new_error(
+            TYPE_ERROR,
+            format!(
+                "argument to first not supported, got {}",
+                other.object_type()
+            ),
+        ),
+    }
+}
+
+fn printsout(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    write_output(evaluator, args, "")
+}
+
+fn println(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    write_output(evaluator, args, "\n")
+}
+
+/**
+Writes the arguments separated by spaces to the output of the evaluator, followed by `end`
+
+# Arguments
+* `evaluator` - the evaluator that owns the output
+* `args` - the objects to write
+* `end` - the text written after the last argument
+*/
+fn write_output(evaluator: &mut Evaluator, args: Vec<ObjectType>, end: &str) -> ObjectType {
+    let text = args
+        .iter()
+        .map(|arg| arg.inspect())
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    let written =
+        write!(evaluator.output, "{}{}", text, end).and_then(|_| evaluator.output.flush());
+    match written {
+        Ok(_) => ObjectType::Null,
+        Err(error) => new_error(IO_ERROR, format!("could not write the output: {}", error)),
+    }
+}
+
+fn ok(_: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    new_result(true, args)
+}
+
+fn err(_: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    new_result(false, args)
+}
+
+fn new_result(ok: bool, mut args: Vec<ObjectType>) -> ObjectType {
+    if args.len() != 1 {
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 1", args.len()),
+        );
+    }
+
+    ObjectType::Result(ResultValue {
+        ok,
+        value: Box::new(args.remove(0)),
+    })
+}
+
+fn is_ok(_: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    if args.len() != 1 {
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 1", args.len()),
+        );
+    }
+
+    match &args[0] {
+        ObjectType::Result(result) => ObjectType::Boolean(Boolean { value: result.ok }),
+        _ => new_error(
+            TYPE_ERROR,
+            format!(
+                "argument to is_ok not supported, got {}",
+                args[0].object_type()
+            ),
+        ),
+    }
+}
+
+fn is_err(_: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    if args.len() != 1 {
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 1", args.len()),
+        );
+    }
+
+    match &args[0] {
+        ObjectType::Result(result) => ObjectType::Boolean(Boolean { value: !result.ok }),
+        _ => new_error(
+            TYPE_ERROR,
+            format!(
+                "argument to is_err not supported, got {}",
+                args[0].object_type()
+            ),
+        ),
+    }
+}
+
+fn unwrap(_: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    if args.len() != 1 {
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 1", args.len()),
+        );
+    }
+
+    match &args[0] {
+        ObjectType::Result(result) if result.ok => *result.value.clone(),
+        ObjectType::Result(result) => new_error(
+            GENERIC_ERROR,
+            format!("called unwrap on {}", result.inspect()),
+        ),
+        _ => new_error(
+            TYPE_ERROR,
+            format!(
+                "argument to unwrap not supported, got {}",
+                args[0].object_type()
+            ),
+        ),
+    }
+}
+
+fn unwrap_err(_: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    if args.len() != 1 {
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 1", args.len()),
+        );
+    }
+
+    match &args[0] {
+        ObjectType::Result(result) if !result.ok => *result.value.clone(),
+        ObjectType::Result(result) => new_error(
+            GENERIC_ERROR,
+            format!("called unwrap_err on {}", result.inspect()),
+        ),
+        _ => new_error(
+            TYPE_ERROR,
+            format!(
+                "argument to unwrap_err not supported, got {}",
+                args[0].object_type()
+            ),
+        ),
+    }
+}
+
+/**
+Returns the elements of the array or set and the function passed to a builtin that calls back a function
+for the elements of an array, or the error if the arguments are not valid.
+The elements of a set are returned in the order they were inserted
+
+# Arguments
+* `name` - the name of the builtin
+* `args` - the arguments passed to the builtin, the array is the first one and the function the second one
+*/
+fn callback_arguments(
+    name: &str,
+    mut args: Vec<ObjectType>,
+) -> Result<(Vec<ObjectType>, ObjectType), Error> {
+    let function = args.remove(1);
+    if function.object_type() != FUNCTION && function.object_type() != BUILTIN {
+        return Err(Error::new(
+            TYPE_ERROR,
+            format!(
+                "second argument to {} must be a function, got {}",
+                name,
+                function.object_type()
+            ),
+        ));
+    }
+
+    match args.remove(0) {
+        ObjectType::Array(array) => Ok((Rc::unwrap_or_clone(array.elements), function)),
+        ObjectType::Set(set) => Ok((set.elements.iter().cloned().collect(), function)),
+        other => Err(Error::new(
+            TYPE_ERROR,
+            format!(
+                "argument to {} not supported, got {}",
+                name,
+                other.object_type()
+            ),
         )),
     }
 }
 
-fn printsout(args: Vec<ObjectType>) -> ObjectType {
-    for arg in args {
-        println!("{:?}", arg.inspect());
+fn map(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    if args.len() != 2 {
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 2", args.len()),
+        );
+    }
+
+    let (elements, function) = match callback_arguments("map", args) {
+        Ok(arguments) => arguments,
+        Err(error) => return ObjectType::Error(error),
+    };
+
+    let mut mapped = vec![];
+    for element in elements {
+        let result = evaluator.apply(function.clone(), vec![element]);
+        if result.object_type() == ERROR {
+            return result;
+        }
+        mapped.push(result);
+    }
+
+    evaluator.allocate(ObjectType::Array(Array {
+        elements: Rc::new(mapped),
+    }))
+}
+
+fn filter(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    if args.len() != 2 {
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 2", args.len()),
+        );
+    }
+
+    let (elements, function) = match callback_arguments("filter", args) {
+        Ok(arguments) => arguments,
+        Err(error) => return ObjectType::Error(error),
+    };
+
+    let mut filtered = vec![];
+    for element in elements {
+        let result = evaluator.apply(function.clone(), vec![element.clone()]);
+        if result.object_type() == ERROR {
+            return result;
+        }
+        if is_truthy(&result) {
+            filtered.push(element);
+        }
+    }
+
+    evaluator.allocate(ObjectType::Array(Array {
+        elements: Rc::new(filtered),
+    }))
+}
+
+fn reduce(evaluator: &mut Evaluator, mut args: Vec<ObjectType>) -> ObjectType {
+    if args.len() != 2 && args.len() != 3 {
+        return new_error(
+            ARGUMENT_ERROR,
+            format!(
+                "wrong number of arguments. got: {}, want: 2 or 3",
+                args.len()
+            ),
+        );
+    }
+
+    let initial = if args.len() == 3 { args.pop() } else { None };
+    let (elements, function) = match callback_arguments("reduce", args) {
+        Ok(arguments) => arguments,
+        Err(error) => return ObjectType::Error(error),
+    };
+
+    let mut elements = elements.into_iter();
+    let mut accumulated = match initial.or_else(|| elements.next()) {
+        Some(initial) => initial,
+        None => {
+            return new_error(
+                ARGUMENT_ERROR,
+                "reduce of an empty array with no initial value".to_string(),
+            )
+        }
+    };
+
+    for element in elements {
+        accumulated = evaluator.apply(function.clone(), vec![accumulated, element]);
+        if accumulated.object_type() == ERROR {
+            return accumulated;
+        }
+    }
+
+    accumulated
+}
+
+fn sort_by(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    if args.len() != 2 {
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 2", args.len()),
+        );
+    }
+
+    let (elements, function) = match callback_arguments("sort_by", args) {
+        Ok(arguments) => arguments,
+        Err(error) => return ObjectType::Error(error),
+    };
+
+    let mut keyed = vec![];
+    for element in elements {
+        let key = evaluator.apply(function.clone(), vec![element.clone()]);
+        match &key {
+            ObjectType::Error(_) => return key,
+            ObjectType::Integer(_) | ObjectType::BigInteger(_) | ObjectType::String(_) => (),
+            other => {
+                return new_error(
+                    TYPE_ERROR,
+                    format!(
+                        "sort_by keys must be INTEGER or STRING, got {}",
+                        other.object_type()
+                    ),
+                )
+            }
+        }
+        if let Some((first, _)) = keyed.first() {
+            if key.object_type() != ObjectType::object_type(first) {
+                return new_error(
+                    TYPE_ERROR,
+                    format!(
+                        "sort_by keys must have the same type, got {} and {}",
+                        ObjectType::object_type(first),
+                        key.object_type()
+                    ),
+                );
+            }
+        }
+        keyed.push((key, element));
+    }
+
+    keyed.sort_by(|(a, _), (b, _)| compare(a, "<", b).unwrap_or(Ordering::Equal));
+
+    evaluator.allocate(ObjectType::Array(Array {
+        elements: Rc::new(keyed.into_iter().map(|(_, element)| element).collect()),
+    }))
+}
+
+fn any(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    if args.len() != 2 {
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 2", args.len()),
+        );
+    }
+
+    let (elements, function) = match callback_arguments("any", args) {
+        Ok(arguments) => arguments,
+        Err(error) => return ObjectType::Error(error),
+    };
+
+    for element in elements {
+        let result = evaluator.apply(function.clone(), vec![element]);
+        if result.object_type() == ERROR {
+            return result;
+        }
+        if is_truthy(&result) {
+            return ObjectType::Boolean(Boolean { value: true });
+        }
+    }
+
+    ObjectType::Boolean(Boolean { value: false })
+}
+
+fn all(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    if args.len() != 2 {
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 2", args.len()),
+        );
+    }
+
+    let (elements, function) = match callback_arguments("all", args) {
+        Ok(arguments) => arguments,
+        Err(error) => return ObjectType::Error(error),
+    };
+
+    for element in elements {
+        let result = evaluator.apply(function.clone(), vec![element]);
+        if result.object_type() == ERROR {
+            return result;
+        }
+        if !is_truthy(&result) {
+            return ObjectType::Boolean(Boolean { value: false });
+        }
+    }
+
+    ObjectType::Boolean(Boolean { value: true })
+}
+
+fn find(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    if args.len() != 2 {
+        return new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 2", args.len()),
+        );
+    }
+
+    let (elements, function) = match callback_arguments("find", args) {
+        Ok(arguments) => arguments,
+        Err(error) => return ObjectType::Error(error),
+    };
+
+    for element in elements {
+        let result = evaluator.apply(function.clone(), vec![element.clone()]);
+        if result.object_type() == ERROR {
+            return result;
+        }
+        if is_truthy(&result) {
+            return element;
+        }
     }
+
     ObjectType::Null
 }
+
+fn set(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    let elements: &[ObjectType] = match &args[..] {
+        [] => &[],
+        [ObjectType::Array(array)] => &array.elements,
+        [ObjectType::Tuple(tuple)] => &tuple.elements,
+        [ObjectType::Set(set)] => return ObjectType::Set(set.clone()),
+        [other] => {
+            return new_error(
+                TYPE_ERROR,
+                format!("argument to set not supported, got {}", other.object_type()),
+            )
+        }
+        _ => {
+            return new_error(
+                ARGUMENT_ERROR,
+                format!(
+                    "wrong number of arguments. got: {}, want: 0 or 1",
+                    args.len()
+                ),
+            )
+        }
+    };
+
+    let mut set = IndexSet::new();
+    for element in elements {
+        if let Some(error) = unusable_key_error(element) {
+            return error;
+        }
+        set.insert(element.clone());
+    }
+
+    evaluator.allocate(ObjectType::Set(Set {
+        elements: Rc::new(set),
+    }))
+}
+
+fn range(_: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    let mut bounds = vec![];
+    for arg in &args {
+        match arg {
+            ObjectType::Integer(int) => bounds.push(int.value),
+            ObjectType::BigInteger(int) => {
+                return new_error(
+                    ARGUMENT_ERROR,
+                    format!("the argument {} to range is too big", int.inspect()),
+                )
+            }
+            other => {
+                return new_error(
+                    TYPE_ERROR,
+                    format!(
+                        "arguments to range must be INTEGER, got {}",
+                        other.object_type()
+                    ),
+                )
+            }
+        }
+    }
+
+    let (start, end, step) = match bounds[..] {
+        [end] => (0, end, 1),
+        [start, end] => (start, end, 1),
+        [start, end, step] => (start, end, step),
+        _ => {
+            return new_error(
+                ARGUMENT_ERROR,
+                format!(
+                    "wrong number of arguments. got: {}, want: 1 to 3",
+                    args.len()
+                ),
+            )
+        }
+    };
+
+    if step == 0 {
+        return new_error(ARGUMENT_ERROR, "the step of a range can't be 0".to_string());
+    }
+
+    ObjectType::Range(Range { start, end, step })
+}
+
+/**
+Returns the two sets passed to a builtin that combines sets, or the error if the arguments are not valid
+
+# Arguments
+* `name` - the name of the builtin
+* `args` - the arguments passed to the builtin
+*/
+fn set_arguments<'a>(name: &str, args: &'a [ObjectType]) -> Result<(&'a Set, &'a Set), ObjectType> {
+    match args {
+        [ObjectType::Set(left), ObjectType::Set(right)] => Ok((left, right)),
+        [left, right] => Err(new_error(
+            TYPE_ERROR,
+            format!(
+                "arguments to {} must be SET, got {} and {}",
+                name,
+                left.object_type(),
+                right.object_type()
+            ),
+        )),
+        _ => Err(new_error(
+            ARGUMENT_ERROR,
+            format!("wrong number of arguments. got: {}, want: 2", args.len()),
+        )),
+    }
+}
+
+fn union(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    let (left, right) = match set_arguments("union", &args) {
+        Ok(sets) => sets,
+        Err(error) => return error,
+    };
+
+    evaluator.allocate(ObjectType::Set(Set {
+        elements: Rc::new(left.elements.union(&right.elements).cloned().collect()),
+    }))
+}
+
+fn intersection(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    let (left, right) = match set_arguments("intersection", &args) {
+        Ok(sets) => sets,
+        Err(error) => return error,
+    };
+
+    evaluator.allocate(ObjectType::Set(Set {
+        elements: Rc::new(
+            left.elements
+                .intersection(&right.elements)
+                .cloned()
+                .collect(),
+        ),
+    }))
+}
+
+fn difference(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    let (left, right) = match set_arguments("difference", &args) {
+        Ok(sets) => sets,
+        Err(error) => return error,
+    };
+
+    evaluator.allocate(ObjectType::Set(Set {
+        elements: Rc::new(left.elements.difference(&right.elements).cloned().collect()),
+    }))
+}
+
+fn read_file(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    let path = match &args[..] {
+        [ObjectType::String(path)] => &*path.value,
+        [other] => {
+            return new_error(
+                TYPE_ERROR,
+                format!(
+                    "argument to read_file not supported, got {}",
+                    other.object_type()
+                ),
+            )
+        }
+        _ => {
+            return new_error(
+                ARGUMENT_ERROR,
+                format!("wrong number of arguments. got: {}, want: 1", args.len()),
+            )
+        }
+    };
+
+    if let Err(error) = evaluator.permissions.check_read(path) {
+        return ObjectType::Error(error);
+    }
+
+    match fs::read_to_string(path) {
+        Ok(content) => evaluator.allocate(ObjectType::String(StringObject {
+            value: content.into(),
+        })),
+        Err(error) => new_error(IO_ERROR, format!("could not read {}: {}", path, error)),
+    }
+}
+
+fn write_file(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    let (path, content) = match &args[..] {
+        [ObjectType::String(path), ObjectType::String(content)] => (&*path.value, &*content.value),
+        [path, content] => {
+            return new_error(
+                TYPE_ERROR,
+                format!(
+                    "arguments to write_file not supported, got {} and {}",
+                    path.object_type(),
+                    content.object_type()
+                ),
+            )
+        }
+        _ => {
+            return new_error(
+                ARGUMENT_ERROR,
+                format!("wrong number of arguments. got: {}, want: 2", args.len()),
+            )
+        }
+    };
+
+    if let Err(error) = evaluator.permissions.check_write(path) {
+        return ObjectType::Error(error);
+    }
+
+    match fs::write(path, content) {
+        Ok(_) => ObjectType::Null,
+        Err(error) => new_error(IO_ERROR, format!("could not write {}: {}", path, error)),
+    }
+}
+
+fn env_var(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    let name = match &args[..] {
+        [ObjectType::String(name)] => &*name.value,
+        [other] => {
+            return new_error(
+                TYPE_ERROR,
+                format!("argument to env not supported, got {}", other.object_type()),
+            )
+        }
+        _ => {
+            return new_error(
+                ARGUMENT_ERROR,
+                format!("wrong number of arguments. got: {}, want: 1", args.len()),
+            )
+        }
+    };
+
+    if let Err(error) = evaluator.permissions.check_env(name) {
+        return ObjectType::Error(error);
+    }
+
+    match env::var(name) {
+        Ok(value) => evaluator.allocate(ObjectType::String(StringObject {
+            value: value.into(),
+        })),
+        Err(_) => ObjectType::Null,
+    }
+}
+
+fn exec(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType {
+    let (command, arguments) = match &args[..] {
+        [ObjectType::String(command)] => (&*command.value, vec![]),
+        [ObjectType::String(command), ObjectType::Array(arguments)] => {
+            let mut strings = vec![];
+            for argument in arguments.elements.iter() {
+                match argument {
+                    ObjectType::String(string) => strings.push(string.value.to_string()),
+                    other => {
+                        return new_error(
+                            TYPE_ERROR,
+                            format!(
+                                "arguments of the command must be STRING, got {}",
+                                other.object_type()
+                            ),
+                        )
+                    }
+                }
+            }
+            (&*command.value, strings)
+        }
+        [command] | [command, _] if command.object_type() != STRING => {
+            return new_error(
+                TYPE_ERROR,
+                format!(
+                    "argument to exec not supported, got {}",
+                    command.object_type()
+                ),
+            )
+        }
+        [_, arguments] => {
+            return new_error(
+                TYPE_ERROR,
+                format!(
+                    "arguments of the command must be an ARRAY, got {}",
+                    arguments.object_type()
+                ),
+            )
+        }
+        _ => {
+            return new_error(
+                ARGUMENT_ERROR,
+                format!(
+                    "wrong number of arguments. got: {}, want: 1 or 2",
+                    args.len()
+                ),
+            )
+        }
+    };
+
+    if let Err(error) = evaluator.permissions.check_exec(command) {
+        return ObjectType::Error(error);
+    }
+
+    let output = match Command::new(command).args(&arguments).output() {
+        Ok(output) => output,
+        Err(error) => return new_error(IO_ERROR, format!("could not run {}: {}", command, error)),
+    };
+
+    let string = |value: &str| {
+        ObjectType::String(StringObject {
+            value: value.into(),
+        })
+    };
+
+    let mut pairs = IndexMap::new();
+    pairs.insert(
+        string("status"),
+        match output.status.code() {
+            Some(code) => ObjectType::Integer(Integer { value: code.into() }),
+            None => ObjectType::Null,
+        },
+    );
+    pairs.insert(
+        string("stdout"),
+        string(&String::from_utf8_lossy(&output.stdout)),
+    );
+    pairs.insert(
+        string("stderr"),
+        string(&String::from_utf8_lossy(&output.stderr)),
+    );
+
+    // the strings of the output are new too
+    let strings = pairs
+        .keys()
+        .chain(pairs.values())
+        .map(ObjectType::size)
+        .sum::<usize>();
+    let dict = ObjectType::Dict(Dict {
+        pairs: Rc::new(pairs),
+    });
+    evaluator.allocate_bytes(strings + dict.size(), dict)
+}
@@ -1,28 +1,111 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::cmp;
 use std::collections::HashMap;
+use std::io::{self, Write};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crate::ast::expressions::{DictLiteral, ForLoop, Identifier, IfExpression, Loop, WhileLoop};
-use crate::ast::statements::{Assignation, Operation};
+use indexmap::{IndexMap, IndexSet};
+
+use crate::ast::expressions::{
+    DictLiteral, ForLoop, Identifier, IfExpression, Loop, Resolution, SetLiteral, TryExpression,
+    WhileLoop,
+};
+use crate::ast::statements::{Assignation, BlockStatement, LetStatement, Operation};
+use crate::bigint::BigInt;
+use crate::object::convert::TypedFunction;
 use crate::object::{
-    Array, Dict, Function, Object, StringObject, ARRAY, BREAK, BUILTIN, DICT, FUNCTION, STRING,
+    Array, BigInteger, Dict, Frame, Function, HeldValues, Native, Object, Range, Set, StringObject,
+    TailCall, Tuple, ANONYMOUS_FUNCTION, ARGUMENT_ERROR, BUILTIN, EXECUTION_LIMIT_ERROR, FUNCTION,
+    GENERIC_ERROR, INDEX_ERROR, INTERRUPT_ERROR, IO_ERROR, MEMORY_ERROR, NAME_ERROR,
+    STACK_OVERFLOW_ERROR, STRING, TYPE_ERROR, ZERO_DIVISION_ERROR,
 };
 
 use crate::{
-    ast::{AstNode, Node},
+    ast::AstNode,
     object::environment::Environment,
-    object::{Boolean, Error, Integer, ObjectType, ReturnValue, ERROR, INTEGER, RETURN},
-    token::Token,
+    object::{Boolean, Error, Integer, ObjectType, ReturnValue, ERROR, INTEGER},
+    token::{Span, Token},
 };
 
 use self::builtin::get_builtin_function;
+use self::permissions::Permissions;
 
 mod builtin;
+pub mod permissions;
 #[cfg(test)]
 mod test;
+mod vm;
+
+/// The maximum depth of nested function calls used when no other limit is set
+pub const DEFAULT_MAX_DEPTH: usize = 1000;
+
+/// The native stack a function call needs to have left, otherwise it runs on a new stack segment.
+/// It must hold everything evaluated between two nested calls, which is large in debug builds
+const STACK_RED_ZONE: usize = 1024 * 1024;
+
+/// The size of the stack segments added when a function call runs low on native stack
+const STACK_SEGMENT_SIZE: usize = 16 * 1024 * 1024;
+
+/// The number of steps between two checks of the time limit, reading the clock on every step is too slow
+const TIME_CHECK_INTERVAL: u64 = 1024;
+
+/// The ways in which the evaluator can run a program. Both give the same results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// walks the syntax tree
+    #[default]
+    Ast,
+    /// compiles the syntax tree to bytecode and runs it in a stack based virtual machine
+    Vm,
+}
 
 /// The evaluator struct is the responsable of evaluating the parsed program
 pub struct Evaluator {
     /// the `env` variable holds the active environment of the program
     pub env: Environment,
+    /// the function calls being evaluated, outermost first
+    frames: Vec<Frame>,
+    /// the active environments of the callers of the function calls being evaluated, outermost first
+    callers: Vec<Environment>,
+    /// the local slots of the chunks run by the virtual machine, empty until their variable is bound
+    locals: Vec<Option<ObjectType>>,
+    /// the maximum number of nested function calls before a stack overflow error is raised
+    max_depth: usize,
+    /// the maximum number of steps a program can take, if any
+    step_limit: Option<u64>,
+    /// the maximum time a program can run for, if any
+    time_limit: Option<Duration>,
+    /// the steps taken by the program being evaluated
+    steps: u64,
+    /// the instant at which the program being evaluated runs out of time
+    deadline: Option<Instant>,
+    /// the error that stopped the program being evaluated, if it exceeded a limit or was interrupted
+    stopped: Option<ObjectType>,
+    /// the maximum approximate number of bytes the program being evaluated can hold, if any
+    memory_limit: Option<usize>,
+    /// the approximate number of bytes held by the program being evaluated, an upper bound that
+    /// counts the values created since it was last measured as held
+    allocated: Cell<usize>,
+    /// the flag that is set from outside the evaluator to interrupt the program being evaluated
+    interrupt: Option<Arc<AtomicBool>>,
+    /// the functions registered by the host application, by name
+    natives: HashMap<String, Native>,
+    /// the position of the call to the builtin or registered function being evaluated
+    native_span: Span,
+    /// the writer that receives the output of the program
+    output: Box<dyn Write>,
+    /// the I/O that the builtins are allowed to do
+    permissions: Permissions,
+    /// the global variables while a function runs, the functions see the ones declared after them here
+    globals: Option<Environment>,
+    /// the way programs and functions are run
+    backend: Backend,
+    /// the virtual machines of finished calls from builtins and natives, reused by the next ones
+    machines: Vec<vm::Machine>,
 }
 
 #[allow(unreachable_patterns)]
@@ -40,6 +123,25 @@ impl Evaluator {
     pub fn new() -> Self {
         Self {
             env: Environment::new(),
+            frames: vec![],
+            callers: vec![],
+            locals: vec![],
+            max_depth: DEFAULT_MAX_DEPTH,
+            step_limit: None,
+            time_limit: None,
+            steps: 0,
+            deadline: None,
+            stopped: None,
+            memory_limit: None,
+            allocated: Cell::new(0),
+            interrupt: None,
+            natives: HashMap::new(),
+            native_span: Span::default(),
+            output: Box::new(io::stdout()),
+            permissions: Permissions::all(),
+            globals: None,
+            backend: Backend::default(),
+            machines: vec![],
         }
     }
 
@@ -56,7 +158,168 @@ impl Evaluator {
     ```
     */
     pub fn new_env(env: Environment) -> Self {
-        Self { env }
+        Self {
+            env,
+            frames: vec![],
+            callers: vec![],
+            locals: vec![],
+            max_depth: DEFAULT_MAX_DEPTH,
+            step_limit: None,
+            time_limit: None,
+            steps: 0,
+            deadline: None,
+            stopped: None,
+            memory_limit: None,
+            allocated: Cell::new(0),
+            interrupt: None,
+            natives: HashMap::new(),
+            native_span: Span::default(),
+            output: Box::new(io::stdout()),
+            permissions: Permissions::all(),
+            globals: None,
+            backend: Backend::default(),
+            machines: vec![],
+        }
+    }
+
+    /**
+    Sets the maximum depth of nested function calls. Calling a function deeper than this
+    raises a stack overflow error instead of exhausting the native stack. Calls in tail position
+    don't nest, so they don't count towards it: a step limit stops a runaway tail recursion
+
+    # Arguments
+    * `max_depth` - the maximum number of nested function calls
+
+    # Examples
+    ```
+    let mut evaluator = Evaluator::new();
+    evaluator.set_max_depth(100);
+    ```
+    */
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    /**
+    Sets the maximum number of steps each evaluated program can take. With `Backend::Ast` every evaluated
+    node and every loop iteration is a step, with `Backend::Vm` every function call and every loop iteration.
+    Programs that take more steps stop with an execution limit error
+
+    # Arguments
+    * `step_limit` - the maximum number of steps, or `None` for no limit
+
+    # Examples
+    ```
+    let mut evaluator = Evaluator::new();
+    evaluator.set_step_limit(Some(1_000_000));
+    ```
+    */
+    pub fn set_step_limit(&mut self, step_limit: Option<u64>) {
+        self.step_limit = step_limit;
+    }
+
+    /**
+    Sets the maximum time each evaluated program can run for. Programs that run for longer
+    stop with an execution limit error
+
+    # Arguments
+    * `time_limit` - the maximum time, or `None` for no limit
+
+    # Examples
+    ```
+    let mut evaluator = Evaluator::new();
+    evaluator.set_time_limit(Some(Duration::from_secs(5)));
+    ```
+    */
+    pub fn set_time_limit(&mut self, time_limit: Option<Duration>) {
+        self.time_limit = time_limit;
+    }
+
+    /**
+    Sets the maximum approximate number of bytes each evaluated program can hold in strings, arrays,
+    tuples, dicts, sets, big integers and closures. Creating a value that takes it over the limit
+    raises a memory error instead, the values the program no longer holds don't count
+
+    # Arguments
+    * `memory_limit` - the maximum number of bytes, or `None` for no limit
+
+    # Examples
+    ```
+    let mut evaluator = Evaluator::new();
+    evaluator.set_memory_limit(Some(64 * 1024 * 1024));
+    ```
+    */
+    pub fn set_memory_limit(&mut self, memory_limit: Option<usize>) {
+        self.memory_limit = memory_limit;
+    }
+
+    /**
+    Sets the writer that receives the output of the program, written by `print` and `println`.
+    The output goes to the standard output by default
+
+    # Arguments
+    * `output` - the writer to use
+
+    # Examples
+    ```
+    let mut evaluator = Evaluator::new();
+    evaluator.set_output(Vec::new());
+    ```
+    */
+    pub fn set_output(&mut self, output: impl Write + 'static) {
+        self.output = Box::new(output);
+    }
+
+    /**
+    Sets the I/O that the file, environment and process builtins are allowed to do. Blocked operations
+    raise a permission error. Everything is allowed by default
+
+    # Arguments
+    * `permissions` - the allowed operations
+
+    # Examples
+    ```
+    let mut evaluator = Evaluator::new();
+    evaluator.set_permissions(Permissions::none());
+    ```
+    */
+    pub fn set_permissions(&mut self, permissions: Permissions) {
+        self.permissions = permissions;
+    }
+
+    /**
+    Sets the way programs are run, by walking the syntax tree or by compiling it to bytecode
+    for the virtual machine. The syntax tree is walked by default
+
+    # Arguments
+    * `backend` - the backend to use
+
+    # Examples
+    ```
+    let mut evaluator = Evaluator::new();
+    evaluator.set_backend(Backend::Vm);
+    ```
+    */
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.backend = backend;
+    }
+
+    /**
+    Sets the flag used to interrupt the evaluation, e.g. from a signal handler. When the flag is set
+    the program being evaluated stops with an interrupt error. The flag is cleared when a program starts
+
+    # Arguments
+    * `interrupt` - the flag to check
+
+    # Examples
+    ```
+    let interrupt = Arc::new(AtomicBool::new(false));
+    let mut evaluator = Evaluator::new();
+    evaluator.set_interrupt_flag(interrupt.clone());
+    ```
+    */
+    pub fn set_interrupt_flag(&mut self, interrupt: Arc<AtomicBool>) {
+        self.interrupt = Some(interrupt);
     }
 
     /**
@@ -76,79 +339,90 @@ impl Evaluator {
     let evaluated = evaluator.eval(program);
     ```
     */
-    pub fn eval(&mut self, node: AstNode) -> ObjectType {
+    pub fn eval(&mut self, node: &AstNode) -> ObjectType {
+        if let AstNode::Program(program) = node {
+            return match self.backend {
+                Backend::Ast => self.eval_program(&program.statements),
+                Backend::Vm => self.run_program(program),
+            };
+        }
+        if let Some(error) = self.step() {
+            return error;
+        }
+
         match node {
-            AstNode::BlockStatement(expressions) => self.eval_statements(expressions.statements),
-            AstNode::Program(program) => self.eval_program(program.statements),
+            AstNode::BlockStatement(block) => self.eval_statements(&block.statements),
 
             AstNode::PrefixExpression(prefix_expression) => {
-                let right = self.eval(*prefix_expression.right);
-                if is_error(&right) {
+                let right = self.eval(&prefix_expression.right);
+                if is_unwinding(&right) {
                     return right;
                 }
-                self.eval_prefix_expression(prefix_expression.operator, right)
+                self.eval_prefix_expression(&prefix_expression.operator, right)
             }
             AstNode::InfixExpression(infix_expression) => {
-                let left = self.eval(*infix_expression.left);
-                if is_error(&left) {
+                let left = self.eval(&infix_expression.left);
+                if is_unwinding(&left) {
                     return left;
                 }
-                let right = self.eval(*infix_expression.right);
-                if is_error(&right) {
+                let right = self.eval(&infix_expression.right);
+                if is_unwinding(&right) {
                     return right;
                 }
-                self.eval_infix_expression(left, infix_expression.operator, right)
+                self.eval_infix_expression(left, &infix_expression.operator, right)
             }
             AstNode::IfExpression(if_expression) => self.eval_if_expression(if_expression),
+            AstNode::TryExpression(try_expression) => self.eval_try_expression(try_expression),
             AstNode::FunctionLiteral(function_literal) => {
-                let parameters = function_literal.parameters;
-                let body = function_literal.body;
-                ObjectType::Function(Function {
-                    parameters,
-                    body,
+                self.allocate(ObjectType::Function(Function {
+                    name: None,
+                    parameters: function_literal.parameters.clone(),
+                    body: function_literal.body.clone(),
+                    scope: function_literal.scope,
                     env: self.env.clone(),
-                })
+                    chunk: None,
+                }))
             }
             AstNode::CallExpression(call) => {
-                let function = self.eval(*call.function);
-                if is_error(&function) {
+                let function = self.eval(&call.function);
+                if is_unwinding(&function) {
                     return function;
                 }
-                let args = self.eval_list_expressions(call.arguments.clone());
-                if args.len() == 1 && is_error(&args[0]) {
-                    return args[0].clone();
+                let mut args = self.eval_list_expressions(&call.arguments);
+                if args.len() == 1 && is_unwinding(&args[0]) {
+                    return args.remove(0);
                 }
 
-                self.apply_function(function, args)
+                self.apply_function(function, args, call.span)
             }
-            AstNode::LetStatement(let_statement) => {
-                let val = self.eval(*let_statement.value);
-                if is_error(&val) {
+            AstNode::LetStatement(let_statement) => self.eval_let_statement(let_statement),
+            AstNode::ReturnStatement(return_statement) => {
+                let val = self.eval(&return_statement.return_value);
+                if is_unwinding(&val) {
                     return val;
                 }
 
-                self.env
-                    .set(let_statement.name.token_literal().as_str(), val.clone());
-                val
+                ObjectType::Return(ReturnValue {
+                    value: Box::new(val),
+                })
             }
-            AstNode::ReturnStatement(return_statement) => {
-                let val = self.eval(*return_statement.return_value);
-                if is_error(&val) {
+            AstNode::ThrowStatement(throw_statement) => {
+                let val = self.eval(&throw_statement.value);
+                if is_unwinding(&val) {
                     return val;
                 }
 
-                return ObjectType::Return(ReturnValue {
-                    value: Box::new(val),
-                });
+                thrown_error(val)
             }
 
             AstNode::Identifier(id) => self.eval_identifier(id),
-            AstNode::IntegerLiteral(integer_literal) => ObjectType::Integer(Integer {
-                value: match integer_literal.token {
-                    Token::ConstInt(val) => val,
-                    _ => panic!("Not a valid number"),
-                },
-            }),
+            AstNode::IntegerLiteral(integer_literal) => match &integer_literal.token {
+                Token::ConstInt(val) => ObjectType::Integer(Integer { value: *val }),
+                Token::ConstBigInt(val) => {
+                    ObjectType::BigInteger(BigInteger { value: val.clone() })
+                }
+                _ => panic!("Not a valid number"),
+            },
             AstNode::Boolean(boolean_literal) => ObjectType::Boolean(Boolean {
                 value: match boolean_literal.token {
                     Token::ConstBool(val) => val,
@@ -156,71 +430,56 @@ impl Evaluator {
                 },
             }),
             AstNode::StringLiteral(string_literal) => ObjectType::String(StringObject {
-                value: match string_literal.token {
-                    Token::ConstStr(val) => val,
+                value: match &string_literal.token {
+                    Token::ConstStr(val) => val.clone(),
                     _ => panic!("Not a valid boolean"),
                 },
             }),
             AstNode::ArrayLiteral(array_literal) => {
-                let elements = self.eval_list_expressions(array_literal.elements);
-                if elements.len() == 1 && is_error(&elements[0]) {
-                    elements[0].clone()
+                let mut elements = self.eval_list_expressions(&array_literal.elements);
+                if elements.len() == 1 && is_unwinding(&elements[0]) {
+                    elements.remove(0)
                 } else {
-                    ObjectType::Array(Array { elements })
+                    self.allocate(ObjectType::Array(Array {
+                        elements: Rc::new(elements),
+                    }))
+                }
+            }
+            AstNode::TupleLiteral(tuple_literal) => {
+                let mut elements = self.eval_list_expressions(&tuple_literal.elements);
+                if elements.len() == 1 && is_unwinding(&elements[0]) {
+                    elements.remove(0)
+                } else {
+                    self.allocate(ObjectType::Tuple(Tuple {
+                        elements: elements.into(),
+                    }))
                 }
             }
             AstNode::IndexExpression(index_expression) => {
-                let left = self.eval(*index_expression.left);
-                if is_error(&left) {
+                let left = self.eval(&index_expression.left);
+                if is_unwinding(&left) {
                     return left;
                 }
-                let index = self.eval(*index_expression.index);
-                if is_error(&index) {
-                    return left;
+                let index = self.eval(&index_expression.index);
+                if is_unwinding(&index) {
+                    return index;
                 }
 
                 self.eval_index_expression(left, index)
             }
-            AstNode::DictLiteral(dict) => self.eval_dict_literal(dict),
-            AstNode::Assignation(assig) => self.eval_assignation_literal(assig),
-            AstNode::PostIncrement(inc) => {
-                let obj = match self.env.get(inc.string()) {
-                    Some(value) => value,
-                    None => return new_error(format!("{} is not in scope", inc.string())),
-                };
-                let val = match obj {
-                    ObjectType::Integer(int) => int.value,
-                    _ => {
-                        return new_error(format!(
-                            "post increment is not a valid operation for {}",
-                            obj.object_type()
-                        ))
-                    }
-                };
-
-                let new_val = ObjectType::Integer(Integer { value: val + 1 });
-                self.env.set(inc.string().as_str(), new_val.clone());
-                new_val
-            }
-            AstNode::PostDecrement(dec) => {
-                let obj = match self.env.get(dec.string()) {
-                    Some(value) => value,
-                    None => return new_error(format!("{} is not in scope", dec.string())),
-                };
-                let val = match obj {
-                    ObjectType::Integer(int) => int.value,
-                    _ => {
-                        return new_error(format!(
-                            "post increment is not a valid operation for {}",
-                            obj.object_type()
-                        ))
-                    }
-                };
+            AstNode::PropagationExpression(propagation) => {
+                let value = self.eval(&propagation.value);
+                if is_unwinding(&value) {
+                    return value;
+                }
 
-                let new_val = ObjectType::Integer(Integer { value: val - 1 });
-                self.env.set(dec.string().as_str(), new_val.clone());
-                new_val
+                eval_propagation(value)
             }
+            AstNode::DictLiteral(dict) => self.eval_dict_literal(dict),
+            AstNode::SetLiteral(set) => self.eval_set_literal(set),
+            AstNode::Assignation(assig) => self.eval_assignation_literal(assig),
+            AstNode::PostIncrement(inc) => self.eval_post_operation(inc.name(), inc.resolution, 1),
+            AstNode::PostDecrement(dec) => self.eval_post_operation(dec.name(), dec.resolution, -1),
             AstNode::ForLoop(for_loop) => self.eval_for_loop_expression(for_loop),
             AstNode::WhileLoop(while_loop) => self.eval_while_loop_expression(while_loop),
             AstNode::Loop(loop_exp) => self.eval_loop_expression(loop_exp),
@@ -230,257 +489,767 @@ impl Evaluator {
     }
 
     /**
-    Evaluates every statement in the program and returns the `ObjectType` of the last statement,
-    of the return statement (unpacking the contents) or an ObjectType::Error if an error has occured.
+    Registers a Rust closure as a function that Halcon programs can call by name. Like builtins,
+    registered functions can be shadowed by variables. Registering a name again replaces the function
 
     # Arguments
-    * `statements` - The list of statements to evaluate
-    */
-    fn eval_program(&mut self, statements: Vec<AstNode>) -> ObjectType {
-        let mut result = None;
+    * `name` - the name of the function
+    * `function` - the closure to call with the evaluator and the arguments
 
-        for statement in statements {
-            let partial_result = self.eval(statement);
+    # Examples
+    ```
+    evaluator.register_function("double", |_, args| match &args[..] {
+        [ObjectType::Integer(int)] => Ok(ObjectType::Integer(Integer { value: int.value * 2 })),
+        _ => Err(Error::new(TYPE_ERROR, "double expects an integer")),
+    });
+    ```
+    */
+    pub fn register_function<F>(&mut self, name: &str, function: F)
+    where
+        F: Fn(&mut Evaluator, Vec<ObjectType>) -> Result<ObjectType, Error> + 'static,
+    {
+        self.natives.insert(
+            name.to_string(),
+            Native {
+                name: name.to_string(),
+                function: Rc::new(function),
+            },
+        );
+    }
 
-            if let ObjectType::Return(ret) = partial_result {
-                return *ret.value;
-            }
-            if let ObjectType::Error(_) = partial_result {
-                return partial_result;
-            }
+    /**
+    Registers a Rust function with typed arguments as a Halcon function. The arguments are converted
+    with `FromHalcon` and the result with `IntoHalcon`, so returning an `Err` raises the error
 
-            result = Some(partial_result)
-        }
+    # Arguments
+    * `name` - the name of the function
+    * `function` - the function to call with the converted arguments
 
-        match result {
-            Some(res) => res,
-            None => panic!("no statements in program"),
-        }
+    # Examples
+    ```
+    evaluator.register_typed_function("repeat", |text: String, times: usize| text.repeat(times));
+    ```
+    */
+    pub fn register_typed_function<Args>(
+        &mut self,
+        name: &str,
+        function: impl TypedFunction<Args>,
+    ) {
+        self.natives.insert(
+            name.to_string(),
+            Native {
+                name: name.to_string(),
+                function: function.into_native(name),
+            },
+        );
     }
 
     /**
-    Evaluates every statement in a list of statements and returns the `ObjectType` of the last statement,
-    of the return statement or an ObjectType::Error if an error has occured.
+    Calls a function, either user defined or built in, from outside of a program and returns the result.
+    Like a program, the call gets its own execution limits
 
     # Arguments
-    * `statements` - The list of statements to evaluate
+    * `function` - the function to call
+    * `args` - the arguments to pass to the function
+
+    # Examples
+    ```
+    let function = evaluator.resolve("fibonacci");
+    let result = evaluator.call_function(function, vec![ObjectType::Integer(Integer { value: 10 })]);
+    ```
     */
-    fn eval_statements(&mut self, statements: Vec<AstNode>) -> ObjectType {
-        let mut result = None;
+    pub fn call_function(&mut self, function: ObjectType, args: Vec<ObjectType>) -> ObjectType {
+        self.start_program();
+        self.apply_function(function, args, Span::default())
+    }
 
-        for statement in statements {
-            let partial_result = self.eval(statement);
+    /**
+    Calls a function from inside a builtin or registered function, e.g. the callback of `map`, and returns
+    the result. The call counts towards the limits of the running program and, in stack traces,
+    it is located at the call to the builtin
 
-            if partial_result.object_type() == BREAK
-                || partial_result.object_type() == RETURN
-                || partial_result.object_type() == ERROR
-            {
-                return partial_result;
-            }
-            result = Some(partial_result)
-        }
+    # Arguments
+    * `function` - the function to call
+    * `args` - the arguments to pass to the function
 
-        match result {
-            Some(res) => res,
-            None => panic!("no statements in program"),
-        }
+    # Examples
+    ```
+    evaluator.register_function("twice", |evaluator, mut args| {
+        let function = args.remove(0);
+        evaluator.apply(function.clone(), vec![]);
+        Ok(evaluator.apply(function, vec![]))
+    });
+    ```
+    */
+    pub fn apply(&mut self, function: ObjectType, args: Vec<ObjectType>) -> ObjectType {
+        self.apply_function(function, args, self.native_span)
     }
 
     /**
-    Evaluates an assigment expression and returns the result. This includes:
-    * not
-    * minus
+    Returns the value bound to a name in the active `Environment`, the registered or builtin function
+    with that name, or an `ObjectType::Error` if there is none
 
     # Arguments
-    * `operator` - the prefix operator to parse
-    * `right` - the Object to apply the operator
+    * `name` - the name to search
+
+    # Examples
+    ```
+    let function = evaluator.resolve("len");
+    ```
     */
-    fn eval_assignation_literal(&mut self, assig: Assignation) -> ObjectType {
-        let val = self.eval(*assig.value);
-        if is_error(&val) {
-            return val;
+    pub fn resolve(&self, name: &str) -> ObjectType {
+        if let Some(obj) = self.variable(name) {
+            return obj.clone();
+        };
+        if let Some(native) = self.natives.get(name) {
+            return ObjectType::Native(native.clone());
         }
 
-        let left_object = match self.env.get(assig.name.token_literal()) {
-            Some(value) => value,
-            None => return new_error(format!("{} is not in scope", assig.name.token_literal())),
-        };
+        get_builtin_function(name)
+    }
 
-        if assig.operation == Operation::Assig {
-            self.env
-                .set(assig.name.token_literal().as_str(), val.clone());
-            val
-        } else {
-            let mut left_val = match left_object {
-                ObjectType::Integer(int) => int.value,
-                _ => {
-                    return new_error(format!(
-                        "sum assign is not a valid operation for {}",
-                        left_object.object_type()
-                    ))
-                }
-            };
+    /**
+    Returns if a name is bound to a variable, a registered function or a builtin function,
+    i.e. if `resolve` finds it
 
-            let right_val = match val {
-                ObjectType::Integer(int) => int.value,
-                _ => {
-                    return new_error(format!(
-                        "sum assign is not a valid operation for {}",
-                        left_object.object_type()
-                    ))
-                }
-            };
+    # Arguments
+    * `name` - the name to search
 
-            match assig.operation {
-                crate::ast::statements::Operation::Sum => {
-                    left_val += right_val;
-                }
-                crate::ast::statements::Operation::Minus => {
-                    left_val -= right_val;
-                }
-                crate::ast::statements::Operation::Mult => {
-                    left_val *= right_val;
-                }
-                crate::ast::statements::Operation::Divide => {
-                    left_val /= right_val;
-                }
-                _ => panic!(),
-            }
+    # Examples
+    ```
+    assert!(evaluator.defines("len"));
+    ```
+    */
+    pub fn defines(&self, name: &str) -> bool {
+        self.variable(name).is_some()
+            || self.natives.contains_key(name)
+            || !matches!(get_builtin_function(name), ObjectType::Error(_))
+    }
 
-            let new_val = ObjectType::Integer(Integer { value: left_val });
+    /**
+    Returns the value of a variable kept by name. Inside a function the variables of the function
+    are searched first, then the global variables
 
-            self.env
-                .set(assig.name.token_literal().as_str(), new_val.clone());
-            new_val
-        }
+    # Arguments
+    * `name` - the name of the variable
+    */
+    fn variable(&self, name: &str) -> Option<&ObjectType> {
+        self.env
+            .get(name)
+            .or_else(|| self.globals.as_ref().and_then(|globals| globals.get(name)))
     }
 
     /**
-    Evaluates a prefix operator expression and returns the result. This includes:
-    * not
-    * minus
+    Returns the value of a variable, in its slot if it was resolved to one or by name otherwise.
+    An `ObjectType::Error` is returned if it is not bound
 
     # Arguments
-    * `operator` - the prefix operator to parse
-    * `right` - the Object to apply the operator
+    * `name` - the name of the variable
+    * `resolution` - where the resolver found the variable, if it was resolved
     */
-    fn eval_prefix_expression(&mut self, operator: String, right: ObjectType) -> ObjectType {
-        match operator.as_str() {
-            "not" => self.eval_not_operator(right),
-            "-" => self.eval_minus_prefix_operator(right),
-            _ => new_error(format!(
-                "unknown operator: {} {}",
-                operator,
-                right.object_type()
-            )),
+    fn lookup(&self, name: &str, resolution: Option<Resolution>) -> ObjectType {
+        match resolution {
+            Some(Resolution::Local { depth, slot }) => match self.env.get_slot(depth, slot) {
+                Some(obj) => obj.clone(),
+                None => not_found(name),
+            },
+            _ => self.resolve(name),
         }
     }
 
     /**
-    Evaluates an infix operator expression and returns the result. This includes:
-    * boolean operatorions -> true == true
-    * numerical operations -> 1 + 3
+    Stores the value of a variable, in its slot if it was resolved to one or by name in the active `Environment` otherwise
 
     # Arguments
-    * `left` - the left Object to apply the operator
-    * `operator` - the prefix operator to parse
-    * `right` - the right Object to apply the operator
+    * `name` - the name of the variable
+    * `resolution` - where the resolver found the variable, if it was resolved
+    * `val` - the value to store
     */
-    fn eval_infix_expression(
-        &mut self,
-        left: ObjectType,
-        operator: String,
-        right: ObjectType,
-    ) -> ObjectType {
-        if left.object_type() == INTEGER && right.object_type() == INTEGER {
-            self.eval_infix_integer_expression(left, operator, right)
-        } else if left.object_type() == STRING && right.object_type() == STRING {
-            self.eval_infix_string_expression(left, operator, right)
-        } else if left.object_type() != right.object_type() {
-            new_error(format!(
-                "type mismatch: {} {} {}",
-                left.object_type(),
-                operator,
-                right.object_type()
-            ))
-        } else if operator == "==" {
-            ObjectType::Boolean(Boolean {
-                value: left.inspect() == right.inspect(),
-            })
-        } else if operator == "!=" {
-            ObjectType::Boolean(Boolean {
-                value: left.inspect() != right.inspect(),
-            })
-        } else {
-            new_error(format!(
-                "unknown operator: {} {} {}",
-                left.object_type(),
-                operator,
-                right.object_type()
-            ))
+    fn store(&mut self, name: &str, resolution: Option<Resolution>, val: ObjectType) {
+        match resolution {
+            Some(Resolution::Local { depth, slot }) => self.env.set_slot(depth, slot, val),
+            _ => self.env.set(name, val),
         }
     }
 
     /**
-    Evaluates an if else expression returns the result.
+    Resets the execution limits before a program starts, and discards earlier interruptions
 
     # Arguments
-    * `if_expression` - the if expression to evaluate
+
+    no arguments
     */
-    fn eval_if_expression(&mut self, if_expression: IfExpression) -> ObjectType {
-        let condition = self.eval(*if_expression.condition);
-        if is_error(&condition) {
-            return condition;
+    fn start_program(&mut self) {
+        self.steps = 0;
+        self.allocated.set(0);
+        self.deadline = self.time_limit.map(|limit| Instant::now() + limit);
+        self.stopped = None;
+        if let Some(interrupt) = &self.interrupt {
+            interrupt.store(false, Ordering::Relaxed);
         }
-        if is_truthy(&condition) {
-            self.eval(AstNode::BlockStatement(if_expression.consequence))
-        } else {
-            for (elif_cond, elif_cons) in if_expression.elifs {
-                let condition = self.eval(elif_cond);
+    }
 
-                if is_error(&condition) {
-                    return condition;
-                }
+    /**
+    Evaluates every statement in the program and returns the `ObjectType` of the last statement,
+    of the return statement (unpacking the contents) or an ObjectType::Error if an error has occured.
+    An empty program evaluates to null
+
+    # Arguments
+    * `statements` - The list of statements to evaluate
+    */
+    fn eval_program(&mut self, statements: &[AstNode]) -> ObjectType {
+        self.start_program();
+
+        let mut result = ObjectType::Null;
+
+        for statement in statements {
+            let partial_result = self.eval(statement);
+
+            if let ObjectType::Return(ret) = partial_result {
+                return *ret.value;
+            }
+            if let ObjectType::Error(_) = partial_result {
+                return partial_result;
+            }
+
+            result = partial_result
+        }
+
+        result
+    }
+
+    /**
+    Evaluates every statement in a list of statements and returns the `ObjectType` of the last statement,
+    of the return statement or an ObjectType::Error if an error has occured. An empty list evaluates to null.
+
+    # Arguments
+    * `statements` - The list of statements to evaluate
+    */
+    fn eval_statements(&mut self, statements: &[AstNode]) -> ObjectType {
+        let mut result = ObjectType::Null;
+
+        for statement in statements {
+            let partial_result = self.eval(statement);
+
+            if matches!(
+                partial_result,
+                ObjectType::Break | ObjectType::Return(_) | ObjectType::Error(_)
+            ) {
+                return partial_result;
+            }
+            result = partial_result
+        }
+
+        result
+    }
+
+    /**
+    Evaluates a block of statements as a node, counting it as a step of the program
+
+    # Arguments
+    * `block` - the block to evaluate
+    */
+    fn eval_block(&mut self, block: &BlockStatement) -> ObjectType {
+        if let Some(error) = self.step() {
+            return error;
+        }
+        self.eval_statements(&block.statements)
+    }
+
+    /**
+    Evaluates a let statement, binding the value to the name in the active `Environment`, and returns the value.
+    Functions take the name they are bound to, unless they already had one
+
+    # Arguments
+    * `let_statement` - the let statement to evaluate
+    */
+    fn eval_let_statement(&mut self, let_statement: &LetStatement) -> ObjectType {
+        let val = self.eval(&let_statement.value);
+        if is_unwinding(&val) {
+            return val;
+        }
+
+        self.bind(&let_statement.name, val)
+    }
+
+    /**
+    Binds a value to the variable declared by a let statement and returns the value.
+    Functions take the name they are bound to, unless they already had one
+
+    # Arguments
+    * `name` - the declared variable
+    * `val` - the value to bind
+    */
+    fn bind(&mut self, name: &Identifier, val: ObjectType) -> ObjectType {
+        let val = name_function(val, name.name());
+        self.store(name.name(), name.resolution, val.clone());
+        val
+    }
+
+    /**
+    Evaluates a post increment or decrement, adding `delta` to the integer variable, and returns the new value
+
+    # Arguments
+    * `name` - the name of the variable
+    * `resolution` - where the resolver found the variable, if it was resolved
+    * `delta` - the amount to add
+    */
+    fn eval_post_operation(
+        &mut self,
+        name: &str,
+        resolution: Option<Resolution>,
+        delta: i128,
+    ) -> ObjectType {
+        let current = self.current_value(name, resolution).cloned();
+        let new_val = self.post_operation_value(name, current, delta);
+        if is_unwinding(&new_val) {
+            return new_val;
+        }
+        self.store(name, resolution, new_val.clone());
+        new_val
+    }
+
+    /**
+    Returns the new value of a variable after a post increment or decrement, without storing it
+
+    # Arguments
+    * `name` - the name of the variable
+    * `current` - the value of the variable, if it is bound
+    * `delta` - the amount to add
+    */
+    fn post_operation_value(
+        &mut self,
+        name: &str,
+        current: Option<ObjectType>,
+        delta: i128,
+    ) -> ObjectType {
+        match current {
+            Some(ObjectType::Integer(int)) => {
+                self.eval_infix_integer_expression(int.value, "+", delta)
+            }
+            Some(ObjectType::BigInteger(int)) => {
+                self.eval_infix_big_integer_expression(&int.value, "+", &BigInt::from(delta))
+            }
+            Some(obj) => new_error(
+                TYPE_ERROR,
+                format!(
+                    "post increment is not a valid operation for {}",
+                    obj.object_type()
+                ),
+            ),
+            None => new_error(NAME_ERROR, format!("{} is not in scope", name)),
+        }
+    }
+
+    /**
+    Evaluates a node of a function body. Calls in tail position are not applied, an `ObjectType::TailCall`
+    is returned instead so that `apply_function` can run them without nesting a new call. The tail positions
+    are the operand of a return statement and, if `tail` is true, the last statement of a block.
+    The branches of if expressions keep the position of the expression, any other node is evaluated with `eval`
+
+    # Arguments
+    * `node` - the node to evaluate
+    * `tail` - if the value of the node is the value of the function
+    */
+    fn eval_tail(&mut self, node: &AstNode, tail: bool) -> ObjectType {
+        match node {
+            AstNode::BlockStatement(block) => self.eval_tail_block(block, tail),
+            AstNode::IfExpression(if_expression) => {
+                let condition = self.eval(&if_expression.condition);
+                if is_unwinding(&condition) {
+                    return condition;
+                }
+                if is_truthy(&condition) {
+                    return self.eval_tail_block(&if_expression.consequence, tail);
+                }
+
+                for (elif_cond, elif_cons) in &if_expression.elifs {
+                    let condition = self.eval(elif_cond);
+                    if is_unwinding(&condition) {
+                        return condition;
+                    }
+                    if is_truthy(&condition) {
+                        return self.eval_tail_block(elif_cons, tail);
+                    }
+                }
+
+                match &if_expression.alternative {
+                    Some(alternative) => self.eval_tail_block(alternative, tail),
+                    None => ObjectType::Null,
+                }
+            }
+            AstNode::ReturnStatement(return_statement) => {
+                let val = self.eval_tail(&return_statement.return_value, true);
+                if is_unwinding(&val) || matches!(val, ObjectType::TailCall(_)) {
+                    return val;
+                }
+
+                ObjectType::Return(ReturnValue {
+                    value: Box::new(val),
+                })
+            }
+            AstNode::CallExpression(call) if tail => {
+                let function = self.eval(&call.function);
+                if is_unwinding(&function) {
+                    return function;
+                }
+                let mut args = self.eval_list_expressions(&call.arguments);
+                if args.len() == 1 && is_unwinding(&args[0]) {
+                    return args.remove(0);
+                }
+
+                ObjectType::TailCall(TailCall {
+                    function: Box::new(function),
+                    args,
+                    span: call.span,
+                })
+            }
+            other => self.eval(other),
+        }
+    }
+
+    /**
+    Evaluates the statements of a block of a function body with `eval_tail`, the last one keeping the position of the block
+
+    # Arguments
+    * `block` - the block to evaluate
+    * `tail` - if the value of the block is the value of the function
+    */
+    fn eval_tail_block(&mut self, block: &BlockStatement, tail: bool) -> ObjectType {
+        let mut result = ObjectType::Null;
+        let last = block.statements.len().saturating_sub(1);
+
+        for (i, statement) in block.statements.iter().enumerate() {
+            result = self.eval_tail(statement, tail && i == last);
+
+            if matches!(
+                result,
+                ObjectType::Break
+                    | ObjectType::Return(_)
+                    | ObjectType::Error(_)
+                    | ObjectType::TailCall(_)
+            ) {
+                return result;
+            }
+        }
+
+        result
+    }
+
+    /**
+    Evaluates an assigment expression and returns the result. This includes:
+    * not
+    * minus
+
+    # Arguments
+    * `operator` - the prefix operator to parse
+    * `right` - the Object to apply the operator
+    */
+    fn eval_assignation_literal(&mut self, assig: &Assignation) -> ObjectType {
+        let val = self.eval(&assig.value);
+        if is_unwinding(&val) {
+            return val;
+        }
+
+        self.assign(&assig.name, assig.operation, val)
+    }
+
+    /**
+    Assigns a value to a variable in scope, or combines it with the integer in the variable,
+    and returns the new value of the variable
+
+    # Arguments
+    * `name` - the name of the variable
+    * `operation` - the kind of assignment
+    * `val` - the assigned value
+    */
+    fn assign(&mut self, name: &Identifier, operation: Operation, val: ObjectType) -> ObjectType {
+        let current = self.current_value(name.name(), name.resolution).cloned();
+        let new_val = self.assigned_value(name.name(), current, operation, val);
+        if is_unwinding(&new_val) {
+            return new_val;
+        }
+        self.store(name.name(), name.resolution, new_val.clone());
+        new_val
+    }
+
+    /**
+    Returns the new value of a variable after an assignment, without storing it
+
+    # Arguments
+    * `name` - the name of the variable
+    * `current` - the value of the variable, if it is bound
+    * `operation` - the kind of assignment
+    * `val` - the assigned value
+    */
+    fn assigned_value(
+        &mut self,
+        name: &str,
+        current: Option<ObjectType>,
+        operation: Operation,
+        val: ObjectType,
+    ) -> ObjectType {
+        let left_val = match current {
+            None => return new_error(NAME_ERROR, format!("{} is not in scope", name)),
+            Some(_) if operation == Operation::Assig => return val,
+            Some(int @ (ObjectType::Integer(_) | ObjectType::BigInteger(_))) => int,
+            Some(left_object) => {
+                return new_error(
+                    TYPE_ERROR,
+                    format!(
+                        "sum assign is not a valid operation for {}",
+                        left_object.object_type()
+                    ),
+                )
+            }
+        };
+
+        if !matches!(val, ObjectType::Integer(_) | ObjectType::BigInteger(_)) {
+            return new_error(
+                TYPE_ERROR,
+                format!("sum assign is not a valid operation for {}", INTEGER),
+            );
+        }
+
+        let operator = match operation {
+            Operation::Sum => "+",
+            Operation::Minus => "-",
+            Operation::Mult => "*",
+            Operation::Divide => "/",
+            Operation::Assig => unreachable!(),
+        };
+
+        self.eval_infix_expression(left_val, operator, val)
+    }
+
+    /**
+    Returns the value of a variable, in its slot if it was resolved to one or by name otherwise
+
+    # Arguments
+    * `name` - the name of the variable
+    * `resolution` - where the resolver found the variable, if it was resolved
+    */
+    fn current_value(&self, name: &str, resolution: Option<Resolution>) -> Option<&ObjectType> {
+        match resolution {
+            Some(Resolution::Local { depth, slot }) => self.env.get_slot(depth, slot),
+            _ => self.variable(name),
+        }
+    }
+
+    /**
+    Evaluates a prefix operator expression and returns the result. This includes:
+    * not
+    * minus
+
+    # Arguments
+    * `operator` - the prefix operator to parse
+    * `right` - the Object to apply the operator
+    */
+    fn eval_prefix_expression(&mut self, operator: &str, right: ObjectType) -> ObjectType {
+        match operator {
+            "not" => self.eval_not_operator(right),
+            "-" => self.eval_minus_prefix_operator(right),
+            _ => new_error(
+                TYPE_ERROR,
+                format!("unknown operator: {} {}", operator, right.object_type()),
+            ),
+        }
+    }
+
+    /**
+    Evaluates an infix operator expression and returns the result. This includes:
+    * boolean operatorions -> true == true
+    * numerical operations -> 1 + 3
+    * comparisons of strings and arrays -> [1, 2] < [1, 3]
+
+    # Arguments
+    * `left` - the left Object to apply the operator
+    * `operator` - the prefix operator to parse
+    * `right` - the right Object to apply the operator
+    */
+    fn eval_infix_expression(
+        &mut self,
+        left: ObjectType,
+        operator: &str,
+        right: ObjectType,
+    ) -> ObjectType {
+        if operator == "in" {
+            eval_in_expression(left, right)
+        } else if operator == "not in" {
+            match eval_in_expression(left, right) {
+                ObjectType::Boolean(boolean) => ObjectType::Boolean(Boolean {
+                    value: !boolean.value,
+                }),
+                error => error,
+            }
+        } else if let (ObjectType::Integer(left), ObjectType::Integer(right)) = (&left, &right) {
+            self.eval_infix_integer_expression(left.value, operator, right.value)
+        } else if let (Some(left), Some(right)) = (big_integer(&left), big_integer(&right)) {
+            self.eval_infix_big_integer_expression(&left, operator, &right)
+        } else if operator == "==" {
+            ObjectType::Boolean(Boolean {
+                value: equals(&left, &right),
+            })
+        } else if operator == "!=" {
+            ObjectType::Boolean(Boolean {
+                value: !equals(&left, &right),
+            })
+        } else if matches!(operator, "<" | ">" | "<=" | ">=") {
+            match compare(&left, operator, &right) {
+                Ok(ordering) => ObjectType::Boolean(Boolean {
+                    value: match operator {
+                        "<" => ordering.is_lt(),
+                        ">" => ordering.is_gt(),
+                        "<=" => ordering.is_le(),
+                        _ => ordering.is_ge(),
+                    },
+                }),
+                Err(error) => error,
+            }
+        } else if let (ObjectType::String(left), ObjectType::String(right)) = (&left, &right) {
+            let result = self.eval_infix_string_expression(left, operator, right);
+            self.allocate(result)
+        } else {
+            operator_error(&left, operator, &right)
+        }
+    }
+
+    /**
+    Evaluates an if else expression returns the result.
+
+    # Arguments
+    * `if_expression` - the if expression to evaluate
+    */
+    fn eval_if_expression(&mut self, if_expression: &IfExpression) -> ObjectType {
+        let condition = self.eval(&if_expression.condition);
+        if is_unwinding(&condition) {
+            return condition;
+        }
+        if is_truthy(&condition) {
+            self.eval_block(&if_expression.consequence)
+        } else {
+            for (elif_cond, elif_cons) in &if_expression.elifs {
+                let condition = self.eval(elif_cond);
+
+                if is_unwinding(&condition) {
+                    return condition;
+                }
 
                 if is_truthy(&condition) {
-                    return self.eval(AstNode::BlockStatement(elif_cons));
+                    return self.eval_block(elif_cons);
                 }
             }
-            match if_expression.alternative {
-                Some(alternative) => self.eval(AstNode::BlockStatement(alternative)),
+            match &if_expression.alternative {
+                Some(alternative) => self.eval_block(alternative),
                 None => ObjectType::Null,
             }
         }
     }
 
+    /**
+    Evaluates a try expression and returns the result of the try block, or the result of the catch block
+    if the try block raised an error. The error is bound to the catch parameter as a dict with the
+    `message`, `kind` and `stack` keys. The finally block is always evaluated and its result is discarded
+    unless it raises an error, returns or breaks.
+
+    # Arguments
+    * `try_expression` - the try expression to evaluate
+    */
+    fn eval_try_expression(&mut self, try_expression: &TryExpression) -> ObjectType {
+        let mut result = self.eval_block(&try_expression.block);
+
+        if let (ObjectType::Error(_), Some((parameter, handler))) = (&result, &try_expression.catch)
+        {
+            result = match self.attach_trace(result) {
+                ObjectType::Error(error) => {
+                    self.eval_catch_block(parameter, error_object(&error), handler)
+                }
+                other => other,
+            };
+        }
+
+        if let Some(finally) = &try_expression.finally {
+            let finally_result = self.eval_block(finally);
+
+            if matches!(
+                finally_result,
+                ObjectType::Break | ObjectType::Return(_) | ObjectType::Error(_)
+            ) {
+                return finally_result;
+            }
+        }
+
+        result
+    }
+
+    /**
+    Evaluates a catch block with the caught error bound to its parameter, and returns the result of the block.
+    The parameter is only bound inside the block: a parameter kept by name is unbound afterwards, and the
+    variable it shadowed is bound again
+
+    # Arguments
+    * `parameter` - the variable the error is bound to
+    * `error` - the caught error, as a dict
+    * `handler` - the catch block
+    */
+    fn eval_catch_block(
+        &mut self,
+        parameter: &Identifier,
+        error: ObjectType,
+        handler: &BlockStatement,
+    ) -> ObjectType {
+        if let Some(Resolution::Local { depth, slot }) = parameter.resolution {
+            self.env.set_slot(depth, slot, error);
+            return self.eval_block(handler);
+        }
+
+        let shadowed = self.env.remove(parameter.name());
+        self.env.set(parameter.name(), error);
+        let result = self.eval_block(handler);
+        self.env.remove(parameter.name());
+        if let Some(shadowed) = shadowed {
+            self.env.set(parameter.name(), shadowed);
+        }
+        result
+    }
+
     /**
     Evaluates a for loop executing the contents until the condition is fulfilled and returns the result of the last statement.
     # Arguments
     * `for_loop` - the for loop to evaluate*/
-    fn eval_for_loop_expression(&mut self, for_loop: ForLoop) -> ObjectType {
-        self.eval(AstNode::LetStatement(for_loop.initialization));
-        let mut condition = self.eval(*for_loop.condition.clone());
-        if is_error(&condition) {
+    fn eval_for_loop_expression(&mut self, for_loop: &ForLoop) -> ObjectType {
+        if let Some(error) = self.step() {
+            return error;
+        }
+        let initialization = self.eval_let_statement(&for_loop.initialization);
+        if is_unwinding(&initialization) {
+            return initialization;
+        }
+        let mut condition = self.eval(&for_loop.condition);
+        if is_unwinding(&condition) {
             return condition;
         }
 
         let mut res = ObjectType::Null;
 
         while is_truthy(&condition) {
-            res = self.eval_statements(for_loop.statements.statements.clone());
+            if let Some(error) = self.step() {
+                return error;
+            }
+            res = self.eval_statements(&for_loop.statements.statements);
 
             if res == ObjectType::Break {
                 return ObjectType::Null;
             }
-            if res.object_type() == RETURN || res.object_type() == ERROR {
+            if matches!(res, ObjectType::Return(_) | ObjectType::Error(_)) {
                 return res;
             }
 
-            self.eval(*for_loop.step.clone());
+            self.eval(&for_loop.step);
 
-            condition = self.eval(*for_loop.condition.clone());
+            condition = self.eval(&for_loop.condition);
 
-            if is_error(&condition) {
+            if is_unwinding(&condition) {
                 return condition;
             }
         }
@@ -493,27 +1262,30 @@ impl Evaluator {
 
     # Arguments
     * `while_loop` - the while loop to evaluate*/
-    fn eval_while_loop_expression(&mut self, while_loop: WhileLoop) -> ObjectType {
-        let mut condition = self.eval(*while_loop.condition.clone());
-        if is_error(&condition) {
+    fn eval_while_loop_expression(&mut self, while_loop: &WhileLoop) -> ObjectType {
+        let mut condition = self.eval(&while_loop.condition);
+        if is_unwinding(&condition) {
             return condition;
         }
 
         let mut res = ObjectType::Null;
 
         while is_truthy(&condition) {
-            res = self.eval_statements(while_loop.statements.statements.clone());
+            if let Some(error) = self.step() {
+                return error;
+            }
+            res = self.eval_statements(&while_loop.statements.statements);
 
             if res == ObjectType::Break {
                 return ObjectType::Null;
             }
-            if res.object_type() == RETURN || res.object_type() == ERROR {
+            if matches!(res, ObjectType::Return(_) | ObjectType::Error(_)) {
                 return res;
             }
 
-            condition = self.eval(*while_loop.condition.clone());
+            condition = self.eval(&while_loop.condition);
 
-            if is_error(&condition) {
+            if is_unwinding(&condition) {
                 return condition;
             }
         }
@@ -527,14 +1299,17 @@ impl Evaluator {
     # Arguments
     * `loop_exp` - the loop to evaluate
     */
-    fn eval_loop_expression(&mut self, loop_exp: Loop) -> ObjectType {
+    fn eval_loop_expression(&mut self, loop_exp: &Loop) -> ObjectType {
         loop {
-            let res = self.eval_statements(loop_exp.statements.statements.clone());
+            if let Some(error) = self.step() {
+                return error;
+            }
+            let res = self.eval_statements(&loop_exp.statements.statements);
 
             if res == ObjectType::Break {
                 return ObjectType::Null;
             }
-            if res.object_type() == RETURN || res.object_type() == ERROR {
+            if matches!(res, ObjectType::Return(_) | ObjectType::Error(_)) {
                 return res;
             }
         }
@@ -546,12 +1321,12 @@ impl Evaluator {
     # Arguments
     * `expressions` - the expressions to evaluate
     */
-    fn eval_list_expressions(&mut self, expressions: Vec<AstNode>) -> Vec<ObjectType> {
+    fn eval_list_expressions(&mut self, expressions: &[AstNode]) -> Vec<ObjectType> {
         let mut result = vec![];
 
         for argument in expressions {
             let evaluated = self.eval(argument);
-            if is_error(&evaluated) {
+            if is_unwinding(&evaluated) {
                 return vec![evaluated];
             }
 
@@ -568,15 +1343,51 @@ impl Evaluator {
     * `index` - the index to apply
     */
     fn eval_index_expression(&mut self, left: ObjectType, index: ObjectType) -> ObjectType {
-        if left.object_type() == ARRAY && index.object_type() == INTEGER {
-            self.eval_array_index_expression(left, index)
-        } else if left.object_type() == DICT {
-            self.eval_dictionary_index_expression(left, index)
-        } else {
-            new_error(format!(
-                "index operator not supported: {}",
-                left.object_type()
-            ))
+        match (&left, &index) {
+            (ObjectType::Array(array), ObjectType::Integer(index)) => {
+                self.eval_array_index_expression(&array.elements, index.value)
+            }
+            (ObjectType::Tuple(tuple), ObjectType::Integer(index)) => {
+                self.eval_array_index_expression(&tuple.elements, index.value)
+            }
+            (ObjectType::Range(range), ObjectType::Integer(index)) => {
+                match range.get(index.value) {
+                    Some(value) => ObjectType::Integer(Integer { value }),
+                    None => new_error(
+                        INDEX_ERROR,
+                        format!("index: {} out of bounds: {}", index.value, range.inspect()),
+                    ),
+                }
+            }
+            (ObjectType::Array(array), ObjectType::BigInteger(index)) => new_error(
+                INDEX_ERROR,
+                format!(
+                    "index: {} out of bounds: {}",
+                    index.inspect(),
+                    array.elements.len()
+                ),
+            ),
+            (ObjectType::Tuple(tuple), ObjectType::BigInteger(index)) => new_error(
+                INDEX_ERROR,
+                format!(
+                    "index: {} out of bounds: {}",
+                    index.inspect(),
+                    tuple.elements.len()
+                ),
+            ),
+            (ObjectType::Range(range), ObjectType::BigInteger(index)) => new_error(
+                INDEX_ERROR,
+                format!(
+                    "index: {} out of bounds: {}",
+                    index.inspect(),
+                    range.inspect()
+                ),
+            ),
+            (ObjectType::Dict(_), _) => self.eval_dictionary_index_expression(left, index),
+            _ => new_error(
+                TYPE_ERROR,
+                format!("index operator not supported: {}", left.object_type()),
+            ),
         }
     }
 
@@ -586,61 +1397,76 @@ impl Evaluator {
     # Arguments
     * `dict` - the dictionary to evaluate
     */
-    fn eval_dict_literal(&mut self, dict: DictLiteral) -> ObjectType {
-        let mut pairs = HashMap::new();
+    fn eval_dict_literal(&mut self, dict: &DictLiteral) -> ObjectType {
+        let mut pairs = IndexMap::new();
 
         for (key_node, val_node) in dict.pairs.iter() {
-            let key = self.eval(key_node.clone());
+            let key = self.eval(key_node);
 
-            if is_error(&key) {
-                return new_error(format!("unusable as hash key: {}", key.object_type()));
+            if is_unwinding(&key) {
+                return key;
             }
 
-            match key.object_type().as_str() {
-                FUNCTION | ERROR | ARRAY | BUILTIN | RETURN | DICT => {
-                    return new_error(format!("unusable as hash key: {}", key.object_type()))
-                }
-                _ => (),
+            if let Some(error) = unusable_key_error(&key) {
+                return error;
             }
 
-            let value = self.eval(val_node.clone());
-            if is_error(&value) {
-                return new_error(format!("unusable as hash key: {}", value.object_type()));
+            let value = self.eval(val_node);
+            if is_unwinding(&value) {
+                return value;
             }
 
             pairs.insert(key, value);
         }
 
-        ObjectType::Dict(Dict { pairs })
+        self.allocate(ObjectType::Dict(Dict {
+            pairs: Rc::new(pairs),
+        }))
     }
 
     /**
-    Evaluates an array index expressionand returns the result
+    Evaluates the set literal. If an unhashable element is used an `ObjectType::Error` is returned
 
     # Arguments
-    * `left` - the Array to index
-    * `index` - the index to apply
+    * `set` - the set to evaluate
     */
-    fn eval_array_index_expression(&self, arr: ObjectType, index: ObjectType) -> ObjectType {
-        let array = match arr {
-            ObjectType::Array(array) => array,
-            _ => panic!("Should be an array"),
-        };
+    fn eval_set_literal(&mut self, set: &SetLiteral) -> ObjectType {
+        let mut elements = IndexSet::new();
 
-        let idx = match index {
-            ObjectType::Integer(array) => array.value,
-            _ => panic!("Should be an array"),
-        };
+        for node in set.elements.iter() {
+            let element = self.eval(node);
+            if is_unwinding(&element) {
+                return element;
+            }
+
+            if let Some(error) = unusable_key_error(&element) {
+                return error;
+            }
+
+            elements.insert(element);
+        }
+
+        self.allocate(ObjectType::Set(Set {
+            elements: Rc::new(elements),
+        }))
+    }
+
+    /**
+    Evaluates an array or tuple index expression and returns the result
 
-        if idx < 0 || idx as usize >= array.elements.len() {
-            return new_error(format!(
-                "index: {} out of bounds: {}",
-                idx,
-                array.elements.len()
-            ));
+    # Arguments
+    * `elements` - the elements of the array or tuple to index
+    * `idx` - the index to apply
+    */
+    fn eval_array_index_expression(&self, elements: &[ObjectType], idx: i128) -> ObjectType {
+        if idx < 0 || idx as usize >= elements.len() {
+            return new_error(
+                INDEX_ERROR,
+                format!("index: {} out of bounds: {}", idx, elements.len()),
+            );
         }
 
-        array.elements[idx as usize].clone()
+        elements[idx as usize].clone()
     }
 
     /**
@@ -656,11 +1482,8 @@ impl Evaluator {
             _ => panic!(),
         };
 
-        match index.object_type().as_str() {
-            FUNCTION | ERROR | ARRAY | BUILTIN | RETURN | DICT => {
-                return new_error(format!("unusable as hash key: {}", index.object_type()))
-            }
-            _ => (),
+        if let Some(error) = unusable_key_error(&index) {
+            return error;
         }
 
         dict.pairs.get(&index).unwrap_or(&ObjectType::Null).clone()
@@ -668,48 +1491,282 @@ impl Evaluator {
 
     /**
     Executes the call to a function, either user defined or built in, and returns the result.
-    the environment of the function will be updated with the current active environment
+    the environment of the function will be updated with the current active environment.
+    While a user defined function is evaluated, a `Frame` for the call is kept in `self.frames`.
+    If there are already `self.max_depth` frames a stack overflow error is returned.
+    Calls in tail position are run in a loop that reuses the frame of the call, so they don't count towards the depth.
+    When the native stack of the thread runs low the call continues on a new stack segment, so the depth limit is
+    reached before the thread can overflow its stack, whatever its size
+
+    # Arguments
+    * `fun` - the function to call
+    * `args` - the arguments to pass to the function
+    * `span` - the position of the call in the source code
+    */
+    fn apply_function(&mut self, fun: ObjectType, args: Vec<ObjectType>, span: Span) -> ObjectType {
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || {
+            self.apply_on_current_stack(fun, args, span)
+        })
+    }
+
+    /**
+    Executes the call to a function on the current native stack, see `apply_function`
 
     # Arguments
     * `fun` - the function to call
     * `args` - the arguments to pass to the function
+    * `span` - the position of the call in the source code
     */
-    fn apply_function(&mut self, fun: ObjectType, args: Vec<ObjectType>) -> ObjectType {
+    fn apply_on_current_stack(
+        &mut self,
+        fun: ObjectType,
+        args: Vec<ObjectType>,
+        span: Span,
+    ) -> ObjectType {
         match fun {
+            ObjectType::Function(_) if self.frames.len() >= self.max_depth => {
+                self.attach_trace(new_error(
+                    STACK_OVERFLOW_ERROR,
+                    format!(
+                        "stack overflow: maximum call depth of {} exceeded",
+                        self.max_depth
+                    ),
+                ))
+            }
+            ObjectType::Function(function) if function.chunk.is_some() => {
+                self.run_function(function, args, span)
+            }
             ObjectType::Function(mut function) => {
-                let previous_env = self.env.clone();
-                function.env.update(&previous_env);
-                let extended_env = self.extended_function_env(function.clone(), args);
+                self.frames.push(Frame {
+                    function: function
+                        .name
+                        .clone()
+                        .unwrap_or(ANONYMOUS_FUNCTION.to_string()),
+                    span,
+                });
+
+                self.callers.push(self.env.clone());
+                let previous_globals = self.globals.clone();
+                if self.frames.len() == 1 {
+                    self.globals = self.callers.last().cloned();
+                }
+                let mut args = args;
+
+                let evaluated = loop {
+                    if args.len() != function.parameters.len() {
+                        break wrong_arguments(args.len(), function.parameters.len());
+                    }
+
+                    if function.scope.is_none() {
+                        if let Some(caller) = self.callers.last() {
+                            function.env.update(caller);
+                        }
+                    }
+                    self.env = self.extended_function_env(&function, args);
+
+                    let body = function.body.clone();
+                    match self.eval_tail_block(&body, true) {
+                        ObjectType::TailCall(call) => match *call.function {
+                            ObjectType::Function(next) if next.chunk.is_none() => {
+                                if let Some(frame) = self.frames.last_mut() {
+                                    frame.function =
+                                        next.name.clone().unwrap_or(ANONYMOUS_FUNCTION.to_string());
+                                    frame.span = call.span;
+                                }
+                                function = next;
+                                args = call.args;
+                            }
+                            other => break self.apply_function(other, call.args, call.span),
+                        },
+                        evaluated => break evaluated,
+                    }
+                };
+                self.env = self.callers.pop().expect("the caller is missing");
+                self.globals = previous_globals;
+
+                let result = self.attach_trace(unwrap_return_value(evaluated));
+                self.frames.pop();
+                result
+            }
+            ObjectType::Builtin(function) => {
+                let previous_span = std::mem::replace(&mut self.native_span, span);
+                let result = (function.function)(self, args);
+                self.native_span = previous_span;
+
+                result
+            }
+            ObjectType::Native(native) => {
+                let previous_span = std::mem::replace(&mut self.native_span, span);
+                let result = (native.function)(self, args);
+                self.native_span = previous_span;
+
+                match result {
+                    Ok(result) => self.allocate(result),
+                    Err(error) => ObjectType::Error(error),
+                }
+            }
+            actual => new_error(
+                TYPE_ERROR,
+                format!("not a function {}", actual.object_type().as_str()),
+            ),
+        }
+    }
+
+    /**
+    Counts a step of the program and returns an error if the program has run out of steps or time,
+    or if it has been interrupted. Once the program is stopped every following step fails, so the error can't be caught
+
+    # Arguments
+
+    no arguments
+    */
+    fn step(&mut self) -> Option<ObjectType> {
+        self.steps += 1;
+
+        if self.stopped.is_none() {
+            if let Some(limit) = self.step_limit.filter(|limit| self.steps > *limit) {
+                self.stopped = Some(new_error(
+                    EXECUTION_LIMIT_ERROR,
+                    format!("execution limit exceeded: step limit of {} steps", limit),
+                ));
+            } else if self
+                .interrupt
+                .as_ref()
+                .is_some_and(|interrupt| interrupt.load(Ordering::Relaxed))
+            {
+                self.stopped = Some(new_error(INTERRUPT_ERROR, "interrupted".to_string()));
+            } else if let Some(deadline) = self.deadline {
+                if self.steps.is_multiple_of(TIME_CHECK_INTERVAL) && Instant::now() >= deadline {
+                    self.stopped = Some(new_error(
+                        EXECUTION_LIMIT_ERROR,
+                        format!(
+                            "execution limit exceeded: time limit of {} ms",
+                            self.time_limit.unwrap_or_default().as_millis()
+                        ),
+                    ));
+                }
+            }
+        }
+
+        self.stopped.clone()
+    }
+
+    /**
+    Adds the size of a created object to the bytes held by the program, and returns the object
+    unchanged if they fit in the memory limit or a memory error if they don't.
+    Every string, array, tuple, dict, set, big integer and closure created by the program is
+    counted before it can be stored
+
+    # Arguments
+    * `obj` - the created object
+    */
+    fn allocate(&self, obj: ObjectType) -> ObjectType {
+        let size = obj.size();
+        self.allocate_bytes(size, obj)
+    }
 
-                self.env = extended_env;
-                let evaluated = self.eval(AstNode::BlockStatement(function.body));
-                self.env = previous_env;
+    /**
+    Adds a number of bytes to the bytes held by the program, and returns the object they were
+    allocated for unchanged if they fit in the memory limit or a memory error if they don't.
+    The count only grows between measures, so when it goes over the limit the values the program
+    still holds are measured again, and the ones it dropped stop counting
 
-                unwrap_return_value(evaluated)
+    # Arguments
+    * `bytes` - the number of bytes allocated
+    * `obj` - the object that holds them
+    */
+    fn allocate_bytes(&self, bytes: usize, obj: ObjectType) -> ObjectType {
+        let total = self.allocated.get().saturating_add(bytes);
+        match self.memory_limit {
+            Some(limit) if total > limit => {
+                let held = self.held_bytes(&obj);
+                self.allocated.set(held);
+                if held <= limit {
+                    return obj;
+                }
+                new_error(
+                    MEMORY_ERROR,
+                    format!(
+                        "memory limit exceeded: {} of {} bytes takes the program to {} bytes, over the limit of {} bytes",
+                        obj.object_type(),
+                        bytes,
+                        held,
+                        limit
+                    ),
+                )
+            }
+            _ => {
+                self.allocated.set(total);
+                obj
             }
-            ObjectType::Builtin(function) => (function.function)(args),
-            actual => {
-                return new_error(format!("not a function {}", actual.object_type().as_str()))
+        }
+    }
+
+    /**
+    Returns the approximate number of bytes held by the program with a new object: the values that
+    can be reached from the active environment, the environments of the callers, the globals and the
+    locals of the virtual machine. The values being evaluated that are not stored yet are not included
+
+    # Arguments
+    * `obj` - the new object
+    */
+    fn held_bytes(&self, obj: &ObjectType) -> usize {
+        let mut held = HeldValues::default();
+        held.add(obj);
+        self.env.hold(&mut held);
+        for env in self.callers.iter().chain(&self.globals) {
+            env.hold(&mut held);
+        }
+        for local in self.locals.iter().flatten() {
+            held.add(local);
+        }
+        held.bytes()
+    }
+
+    /**
+    Returns the object unchanged, unless it is an error raised inside the active function call
+    that still has no stack trace. In that case the active function calls are stored in the error.
+    Errors are raised in the innermost call, so the trace is attached before leaving it
+
+    # Arguments
+    * `obj` - the object to check
+    */
+    fn attach_trace(&self, obj: ObjectType) -> ObjectType {
+        match obj {
+            ObjectType::Error(mut error) if error.stack.is_empty() => {
+                error.stack = self.frames.iter().rev().cloned().collect();
+                ObjectType::Error(error)
             }
+            other => other,
         }
     }
 
     /**
     Returns a new `Environment` with the functions environment as the outer environment and the paramenters
-    as the inner environment
+    as the inner environment. Resolved functions get a call frame with the arguments in the first slots
+    and the function itself in its recursive slot, if it has one
 
     # Arguments
     * `function` - the functions with the `Environment` to extend
     * `args` - the arguments to include in the new `Environment`
     */
-    fn extended_function_env(&self, function: Function, args: Vec<ObjectType>) -> Environment {
+    fn extended_function_env(&self, function: &Function, args: Vec<ObjectType>) -> Environment {
+        if let Some(scope) = function.scope {
+            let mut slots: Vec<Option<ObjectType>> = args.into_iter().map(Some).collect();
+            slots.resize(scope.slots, None);
+            if let Some(slot) = scope.recursive {
+                slots[slot] = Some(ObjectType::Function(function.clone()));
+            }
+            return Environment::new_frame(&function.env, slots);
+        }
+
         let mut env = Environment::new_enclosed_environment(&function.env);
 
         for (i, param) in function.parameters.iter().enumerate() {
-            env.set(param.token_literal().as_str(), args[i].clone());
+            env.set(param.name(), args[i].clone());
         }
 
-        return env;
+        env
     }
 
     /**
@@ -719,13 +1776,8 @@ impl Evaluator {
     # Arguments
     * `id` - the id to evaluate
     */
-    fn eval_identifier(&mut self, id: Identifier) -> ObjectType {
-        match self.env.get(id.token_literal()) {
-            Some(obj) => return obj.clone(),
-            None => {}
-        };
-
-        get_builtin_function(id.token_literal().as_str())
+    fn eval_identifier(&mut self, id: &Identifier) -> ObjectType {
+        self.lookup(id.name(), id.resolution)
     }
 
     /**
@@ -748,9 +1800,16 @@ impl Evaluator {
     */
     fn eval_minus_prefix_operator(&self, right: ObjectType) -> ObjectType {
         match right {
-            ObjectType::Integer(int) => ObjectType::Integer(Integer { value: -int.value }),
-
-            other => new_error(format!("unknown operator: -{}", other.object_type())),
+            ObjectType::Integer(int) => match int.value.checked_neg() {
+                Some(value) => ObjectType::Integer(Integer { value }),
+                None => BigInteger::object(-&BigInt::from(int.value)),
+            },
+            ObjectType::BigInteger(int) => BigInteger::object(-&*int.value),
+
+            other => new_error(
+                TYPE_ERROR,
+                format!("unknown operator: -{}", other.object_type()),
+            ),
         }
     }
 
@@ -758,99 +1817,471 @@ impl Evaluator {
     Evaluates the infix integers operators. If the operator is not supported an `ObjectType::Error` is returned
 
     # Arguments
-    * `right` - the right `ObjectType` to evaluate
+    * `right` - the right integer to evaluate
     * `operator` - the operator to evaluate
-    * `left` - the left `ObjectType` to evaluate
+    * `left` - the left integer to evaluate
     */
-    fn eval_infix_integer_expression(
-        &self,
-        left: ObjectType,
-        operator: String,
-        right: ObjectType,
-    ) -> ObjectType {
-        let left_val: i128 = left.inspect().parse().unwrap();
-        let right_val: i128 = right.inspect().parse().unwrap();
-        match operator.as_str() {
-            "+" => ObjectType::Integer(Integer {
-                value: left_val + right_val,
-            }),
-            "-" => ObjectType::Integer(Integer {
-                value: left_val - right_val,
-            }),
-            "*" => ObjectType::Integer(Integer {
-                value: left_val * right_val,
-            }),
-            "%" => ObjectType::Integer(Integer {
-                value: left_val % right_val,
-            }),
-            "/" => ObjectType::Integer(Integer {
-                value: left_val / right_val,
-            }),
-            "<" => ObjectType::Boolean(Boolean {
-                value: left_val < right_val,
-            }),
-            ">" => ObjectType::Boolean(Boolean {
-                value: left_val > right_val,
-            }),
-            ">=" => ObjectType::Boolean(Boolean {
-                value: left_val >= right_val,
-            }),
-            "<=" => ObjectType::Boolean(Boolean {
-                value: left_val <= right_val,
-            }),
-            "==" => ObjectType::Boolean(Boolean {
-                value: left_val == right_val,
-            }),
-            "!=" => ObjectType::Boolean(Boolean {
-                value: left_val != right_val,
-            }),
-            _ => new_error(format!(
-                "unknown operator: {} {} {}",
-                left.object_type(),
+    fn eval_infix_integer_expression(&self, left: i128, operator: &str, right: i128) -> ObjectType {
+        let value = match operator {
+            "+" => left.checked_add(right),
+            "-" => left.checked_sub(right),
+            "*" => left.checked_mul(right),
+            "%" | "/" if right == 0 => {
+                return new_error(ZERO_DIVISION_ERROR, "division by zero".to_string())
+            }
+            "%" => left.checked_rem(right),
+            "/" => left.checked_div(right),
+            "<" => {
+                return ObjectType::Boolean(Boolean {
+                    value: left < right,
+                })
+            }
+            ">" => {
+                return ObjectType::Boolean(Boolean {
+                    value: left > right,
+                })
+            }
+            ">=" => {
+                return ObjectType::Boolean(Boolean {
+                    value: left >= right,
+                })
+            }
+            "<=" => {
+                return ObjectType::Boolean(Boolean {
+                    value: left <= right,
+                })
+            }
+            "==" => {
+                return ObjectType::Boolean(Boolean {
+                    value: left == right,
+                })
+            }
+            "!=" => {
+                return ObjectType::Boolean(Boolean {
+                    value: left != right,
+                })
+            }
+            _ => {
+                return new_error(
+                    TYPE_ERROR,
+                    format!("unknown operator: {} {} {}", INTEGER, operator, INTEGER),
+                )
+            }
+        };
+
+        match value {
+            Some(value) => ObjectType::Integer(Integer { value }),
+            None => self.eval_infix_big_integer_expression(
+                &BigInt::from(left),
                 operator,
-                right.object_type()
-            )),
+                &BigInt::from(right),
+            ),
         }
     }
 
+    /**
+    Evaluates the infix integers operators on integers that don't fit in an `i128`, or whose result
+    doesn't. The result is an `ObjectType::Integer` again if it fits
+
+    # Arguments
+    * `left` - the left integer
+    * `operator` - the operator to evaluate
+    * `right` - the right integer
+    */
+    fn eval_infix_big_integer_expression(
+        &self,
+        left: &BigInt,
+        operator: &str,
+        right: &BigInt,
+    ) -> ObjectType {
+        let value = match operator {
+            "+" => left + right,
+            "-" => left - right,
+            "*" => left * right,
+            "%" | "/" if right.is_zero() => {
+                return new_error(ZERO_DIVISION_ERROR, "division by zero".to_string())
+            }
+            "%" => left.div_rem(right).1,
+            "/" => left.div_rem(right).0,
+            "<" => {
+                return ObjectType::Boolean(Boolean {
+                    value: left < right,
+                })
+            }
+            ">" => {
+                return ObjectType::Boolean(Boolean {
+                    value: left > right,
+                })
+            }
+            ">=" => {
+                return ObjectType::Boolean(Boolean {
+                    value: left >= right,
+                })
+            }
+            "<=" => {
+                return ObjectType::Boolean(Boolean {
+                    value: left <= right,
+                })
+            }
+            "==" => {
+                return ObjectType::Boolean(Boolean {
+                    value: left == right,
+                })
+            }
+            "!=" => {
+                return ObjectType::Boolean(Boolean {
+                    value: left != right,
+                })
+            }
+            _ => {
+                return new_error(
+                    TYPE_ERROR,
+                    format!("unknown operator: {} {} {}", INTEGER, operator, INTEGER),
+                )
+            }
+        };
+
+        self.allocate(BigInteger::object(value))
+    }
+
     /**
     Evaluates the infix string expressions. If the operator is not supported an `ObjectType::Error` is returned
 
     # Arguments
-    * `right` - the right `ObjectType` to evaluate
+    * `right` - the right string to evaluate
     * `operator` - the operator to evaluate
-    * `left` - the left `ObjectType` to evaluate
+    * `left` - the left string to evaluate
     */
     fn eval_infix_string_expression(
         &self,
-        left: ObjectType,
-        operator: String,
-        right: ObjectType,
+        left: &StringObject,
+        operator: &str,
+        right: &StringObject,
     ) -> ObjectType {
-        let left_val: String = left.inspect().parse().unwrap();
-        let right_val: String = right.inspect().parse().unwrap();
-        match operator.as_str() {
+        match operator {
             "+" => ObjectType::String(StringObject {
-                value: left_val + &right_val,
+                value: format!("{}{}", left.value, right.value).into(),
             }),
-            _ => new_error(format!(
-                "unknown operator: {} {} {}",
-                left.object_type(),
-                operator,
-                right.object_type()
-            )),
+            _ => new_error(
+                TYPE_ERROR,
+                format!("unknown operator: {} {} {}", STRING, operator, STRING),
+            ),
         }
     }
 }
 
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /**
-Returns an `ObjectType::Error` with the specified message
+Returns an `ObjectType::Error` of the specified kind with the specified message
 
 # Arguments
+* `kind` - the kind of error, e.g. `TYPE_ERROR`
 * `message` - the error message
 */
-fn new_error(message: String) -> ObjectType {
-    ObjectType::Error(Error { message })
+fn new_error(kind: &str, message: String) -> ObjectType {
+    ObjectType::Error(Error::new(kind, message))
+}
+
+/**
+Returns the error of a call with the wrong number of arguments
+
+# Arguments
+* `got` - the number of arguments of the call
+* `want` - the number of parameters of the function
+*/
+fn wrong_arguments(got: usize, want: usize) -> ObjectType {
+    new_error(
+        ARGUMENT_ERROR,
+        format!("wrong number of arguments. got: {}, want: {}", got, want),
+    )
+}
+
+/**
+Returns the error of an identifier that is not bound
+
+# Arguments
+* `name` - the name of the identifier
+*/
+fn not_found(name: &str) -> ObjectType {
+    new_error(NAME_ERROR, format!("identifier not found: {}", name))
+}
+
+/**
+Gives a function the name of the variable it is bound to, unless it already had one
+
+# Arguments
+* `val` - the bound value
+* `name` - the name of the variable
+*/
+fn name_function(mut val: ObjectType, name: &str) -> ObjectType {
+    if let ObjectType::Function(function) = &mut val {
+        if function.name.is_none() {
+            function.name = Some(name.to_string());
+        }
+    }
+
+    val
+}
+
+/**
+Returns the `ObjectType::Error` raised by a throw statement.
+* strings are used as the message of a generic error
+* dicts can set the `message` and `kind` of the error, so a caught error can be thrown again
+* other objects are inspected to build the message of a generic error
+
+# Arguments
+* `value` - the thrown object
+*/
+fn thrown_error(value: ObjectType) -> ObjectType {
+    match value {
+        ObjectType::String(string) => new_error(GENERIC_ERROR, string.value.to_string()),
+        ObjectType::Dict(dict) => {
+            let field = |name: &str| match dict
+                .pairs
+                .get(&ObjectType::String(StringObject { value: name.into() }))
+            {
+                Some(ObjectType::String(string)) => Some(string.value.to_string()),
+                _ => None,
+            };
+
+            let kind = field("kind").unwrap_or(GENERIC_ERROR.to_string());
+            let message = field("message").unwrap_or(dict.inspect());
+
+            new_error(kind.as_str(), message)
+        }
+        other => new_error(GENERIC_ERROR, other.inspect()),
+    }
+}
+
+/**
+Returns the dict that represents an error inside a catch block, with the `message`, `kind` and `stack` keys.
+Each element of the stack is a dict with the `function`, `line` and `column` of the call
+
+# Arguments
+* `error` - the caught error
+*/
+fn error_object(error: &Error) -> ObjectType {
+    let string = |value: &str| {
+        ObjectType::String(StringObject {
+            value: value.into(),
+        })
+    };
+
+    let stack = error
+        .stack
+        .iter()
+        .map(|frame| {
+            let mut pairs = IndexMap::new();
+            pairs.insert(string("function"), string(&frame.function));
+            pairs.insert(
+                string("line"),
+                ObjectType::Integer(Integer {
+                    value: frame.span.line as i128,
+                }),
+            );
+            pairs.insert(
+                string("column"),
+                ObjectType::Integer(Integer {
+                    value: frame.span.column as i128,
+                }),
+            );
+            ObjectType::Dict(Dict {
+                pairs: Rc::new(pairs),
+            })
+        })
+        .collect();
+
+    let mut pairs = IndexMap::new();
+    pairs.insert(string("message"), string(&error.message));
+    pairs.insert(string("kind"), string(&error.kind));
+    pairs.insert(
+        string("stack"),
+        ObjectType::Array(Array {
+            elements: Rc::new(stack),
+        }),
+    );
+
+    ObjectType::Dict(Dict {
+        pairs: Rc::new(pairs),
+    })
+}
+
+/**
+Returns a type error if the object can't be used as a dict key
+
+# Arguments
+* `key` - the object to check
+*/
+fn unusable_key_error(key: &ObjectType) -> Option<ObjectType> {
+    if key.is_hashable() {
+        None
+    } else {
+        Some(new_error(
+            TYPE_ERROR,
+            format!("unusable as hash key: {}", key.object_type()),
+        ))
+    }
+}
+
+/**
+Returns if a value is in a container:
+* an element of an array, tuple or set
+* a key of a dict
+* a substring of a string
+* an integer of a range
+
+# Arguments
+* `left` - the value to look for
+* `right` - the container to look in
+*/
+fn eval_in_expression(left: ObjectType, right: ObjectType) -> ObjectType {
+    let value = match (&left, &right) {
+        (_, ObjectType::Array(array)) => array.elements.iter().any(|e| equals(e, &left)),
+        (_, ObjectType::Tuple(tuple)) => tuple.elements.iter().any(|e| equals(e, &left)),
+        (_, ObjectType::Set(set)) => match unusable_key_error(&left) {
+            Some(error) => return error,
+            None => set.elements.contains(&left),
+        },
+        (_, ObjectType::Dict(dict)) => match unusable_key_error(&left) {
+            Some(error) => return error,
+            None => dict.pairs.contains_key(&left),
+        },
+        (ObjectType::String(left), ObjectType::String(right)) => right.value.contains(&*left.value),
+        (ObjectType::Integer(left), ObjectType::Range(range)) => range.contains(left.value),
+        (_, ObjectType::Range(_)) => false,
+        _ => {
+            return new_error(
+                TYPE_ERROR,
+                format!(
+                    "unknown operator: {} in {}",
+                    left.object_type(),
+                    right.object_type()
+                ),
+            )
+        }
+    };
+
+    ObjectType::Boolean(Boolean { value })
+}
+
+/**
+Returns if two objects are equal. Arrays and tuples are compared element by element, dicts by their
+pairs in any order and functions by identity: a function is only equal to itself, not to another
+function with the same code. Objects of different types are never equal
+
+# Arguments
+* `left` - the left operand of `==`
+* `right` - the right operand of `==`
+*/
+fn equals(left: &ObjectType, right: &ObjectType) -> bool {
+    let all_equal = |left: &[ObjectType], right: &[ObjectType]| {
+        left.len() == right.len() && left.iter().zip(right).all(|(l, r)| equals(l, r))
+    };
+
+    match (left, right) {
+        (ObjectType::Array(left), ObjectType::Array(right)) => {
+            all_equal(&left.elements, &right.elements)
+        }
+        (ObjectType::Tuple(left), ObjectType::Tuple(right)) => {
+            all_equal(&left.elements, &right.elements)
+        }
+        (ObjectType::Dict(left), ObjectType::Dict(right)) => {
+            left.pairs.len() == right.pairs.len()
+                && left.pairs.iter().all(|(key, value)| {
+                    right
+                        .pairs
+                        .get(key)
+                        .is_some_and(|other| equals(value, other))
+                })
+        }
+        (ObjectType::Result(left), ObjectType::Result(right)) => {
+            left.ok == right.ok && equals(&left.value, &right.value)
+        }
+        (ObjectType::Function(left), ObjectType::Function(right)) => {
+            Rc::ptr_eq(&left.body, &right.body) && left.env.ptr_eq(&right.env)
+        }
+        _ => left == right,
+    }
+}
+
+/**
+Returns the order of two objects for `<`, `>`, `<=` and `>=`. Integers are ordered by value, strings
+by their characters and arrays lexicographically, element by element, with a shorter array before a
+longer one that starts with it. Other objects, or elements that can't be ordered, return an error
+
+# Arguments
+* `left` - the left operand
+* `operator` - the comparison operator, for the error message
+* `right` - the right operand
+*/
+fn compare(
+    left: &ObjectType,
+    operator: &str,
+    right: &ObjectType,
+) -> Result<cmp::Ordering, ObjectType> {
+    match (left, right) {
+        (ObjectType::Integer(left), ObjectType::Integer(right)) => Ok(left.value.cmp(&right.value)),
+        (ObjectType::String(left), ObjectType::String(right)) => Ok(left.value.cmp(&right.value)),
+        (ObjectType::Array(left), ObjectType::Array(right)) => {
+            for (l, r) in left.elements.iter().zip(right.elements.iter()) {
+                match compare(l, operator, r)? {
+                    cmp::Ordering::Equal => continue,
+                    ordering => return Ok(ordering),
+                }
+            }
+            Ok(left.elements.len().cmp(&right.elements.len()))
+        }
+        _ => match (big_integer(left), big_integer(right)) {
+            (Some(left), Some(right)) => Ok(left.cmp(&right)),
+            _ => Err(operator_error(left, operator, right)),
+        },
+    }
+}
+
+/**
+Returns the value of an integer of any size as a `BigInt`, or None if the object is not an integer
+
+# Arguments
+* `value` - the object to convert
+*/
+fn big_integer(value: &ObjectType) -> Option<Cow<'_, BigInt>> {
+    match value {
+        ObjectType::Integer(int) => Some(Cow::Owned(BigInt::from(int.value))),
+        ObjectType::BigInteger(int) => Some(Cow::Borrowed(&int.value)),
+        _ => None,
+    }
+}
+
+/**
+Returns the error of an infix operator that doesn't support its operands: a type mismatch if they
+have different types, or an unknown operator otherwise
+
+# Arguments
+* `left` - the left operand
+* `operator` - the infix operator
+* `right` - the right operand
+*/
+fn operator_error(left: &ObjectType, operator: &str, right: &ObjectType) -> ObjectType {
+    let problem = if left.object_type() != right.object_type() {
+        "type mismatch"
+    } else {
+        "unknown operator"
+    };
+    new_error(
+        TYPE_ERROR,
+        format!(
+            "{}: {} {} {}",
+            problem,
+            left.object_type(),
+            operator,
+            right.object_type()
+        ),
+    )
 }
 
 /**
@@ -872,12 +2303,33 @@ fn is_truthy(condition: &ObjectType) -> bool {
 }
 
 /**
-Returns if an `ObjectType` is an error
+Returns if an `ObjectType` stops the evaluation of the expression that contains it.
+This happens with errors and with the return values produced by the `?` operator
 # Arguments
 * `obj` - the `ObjectType` to evaluate
 */
-fn is_error(obj: &ObjectType) -> bool {
-    obj.object_type() == ERROR
+fn is_unwinding(obj: &ObjectType) -> bool {
+    matches!(obj, ObjectType::Error(_) | ObjectType::Return(_))
+}
+
+/**
+Unwraps an ok result, or wraps an err result in an `ObjectType::Return` so that it is returned from the
+enclosing function. If the object is not a result an `ObjectType::Error` is returned
+
+# Arguments
+* `value` - the result to unwrap
+*/
+fn eval_propagation(value: ObjectType) -> ObjectType {
+    match value {
+        ObjectType::Result(result) if result.ok => *result.value,
+        ObjectType::Result(result) => ObjectType::Return(ReturnValue {
+            value: Box::new(ObjectType::Result(result)),
+        }),
+        other => new_error(
+            TYPE_ERROR,
+            format!("? operator not supported: {}", other.object_type()),
+        ),
+    }
 }
 
 /**
@@ -0,0 +1,190 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::object::{Error, PERMISSION_ERROR};
+
+/// The paths that a program can access for reading or for writing
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathAccess {
+    /// every path can be accessed
+    All,
+    /// only the listed files and the files inside the listed directories can be accessed
+    Only(Vec<PathBuf>),
+}
+
+impl PathAccess {
+    /**
+    Returns if the path can be accessed. Paths are compared once resolved, so `..` and symbolic
+    links can't be used to leave the allowed directories
+
+    # Arguments
+    * `path` - the path to check
+    */
+    pub fn allows(&self, path: &Path) -> bool {
+        match self {
+            PathAccess::All => true,
+            PathAccess::Only(allowed) => match resolve(path) {
+                Some(path) => allowed
+                    .iter()
+                    .filter_map(|allowed| fs::canonicalize(allowed).ok())
+                    .any(|allowed| path.starts_with(allowed)),
+                None => false,
+            },
+        }
+    }
+}
+
+/**
+Returns the absolute path without `..` nor symbolic links, or `None` if it can't be resolved.
+A file that doesn't exist yet is resolved through its directory, so that it can be written,
+but a symbolic link that points nowhere is rejected, since writing it would create its target
+
+# Arguments
+* `path` - the path to resolve
+*/
+fn resolve(path: &Path) -> Option<PathBuf> {
+    if let Ok(resolved) = fs::canonicalize(path) {
+        return Some(resolved);
+    }
+
+    if fs::symlink_metadata(path).is_ok() {
+        return None;
+    }
+
+    let name = path.file_name()?;
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    fs::canonicalize(directory).ok().map(|dir| dir.join(name))
+}
+
+/// The I/O that the builtins of a program are allowed to do
+#[derive(Clone, Debug, PartialEq)]
+pub struct Permissions {
+    /// the files that can be read
+    pub read: PathAccess,
+    /// the files that can be written
+    pub write: PathAccess,
+    /// if the environment variables can be read
+    pub env: bool,
+    /// if other programs can be run
+    pub exec: bool,
+}
+
+impl Permissions {
+    /**
+    Returns the permissions that allow every operation, used when no sandbox is set
+
+    # Arguments
+
+    no arguments
+
+    # Examples
+    ```
+    let permissions = Permissions::all();
+    ```
+    */
+    pub fn all() -> Self {
+        Self {
+            read: PathAccess::All,
+            write: PathAccess::All,
+            env: true,
+            exec: true,
+        }
+    }
+
+    /**
+    Returns the permissions of a sandbox, that deny every operation
+
+    # Arguments
+
+    no arguments
+
+    # Examples
+    ```
+    let mut permissions = Permissions::none();
+    permissions.read = PathAccess::Only(vec![PathBuf::from("./data")]);
+    ```
+    */
+    pub fn none() -> Self {
+        Self {
+            read: PathAccess::Only(vec![]),
+            write: PathAccess::Only(vec![]),
+            env: false,
+            exec: false,
+        }
+    }
+
+    /**
+    Returns a `PermissionError` if the file can't be read
+
+    # Arguments
+    * `path` - the path of the file
+    */
+    pub fn check_read(&self, path: &str) -> Result<(), Error> {
+        match self.read.allows(Path::new(path)) {
+            true => Ok(()),
+            false => Err(denied(format!("read access to {}", path))),
+        }
+    }
+
+    /**
+    Returns a `PermissionError` if the file can't be written
+
+    # Arguments
+    * `path` - the path of the file
+    */
+    pub fn check_write(&self, path: &str) -> Result<(), Error> {
+        match self.write.allows(Path::new(path)) {
+            true => Ok(()),
+            false => Err(denied(format!("write access to {}", path))),
+        }
+    }
+
+    /**
+    Returns a `PermissionError` if the environment variable can't be read
+
+    # Arguments
+    * `name` - the name of the variable
+    */
+    pub fn check_env(&self, name: &str) -> Result<(), Error> {
+        match self.env {
+            true => Ok(()),
+            false => Err(denied(format!("environment access to {}", name))),
+        }
+    }
+
+    /**
+    Returns a `PermissionError` if the program can't be run
+
+    # Arguments
+    * `command` - the program to run
+    */
+    pub fn check_exec(&self, command: &str) -> Result<(), Error> {
+        match self.exec {
+            true => Ok(()),
+            false => Err(denied(format!("running {}", command))),
+        }
+    }
+}
+
+impl Default for Permissions {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/**
+Returns the `PermissionError` for the denied operation
+
+# Arguments
+* `operation` - the description of the operation
+*/
+fn denied(operation: String) -> Error {
+    Error::new(
+        PERMISSION_ERROR,
+        format!("permission denied: {}", operation),
+    )
+}
@@ -1,11 +1,18 @@
 use core::panic;
+use std::cell::RefCell;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
+use std::{fs, io};
 
+use super::permissions::PathAccess;
 use super::*;
+use crate::ast::Node;
 use crate::lexer::Lexer;
+use crate::object::{ARRAY, PERMISSION_ERROR};
+use crate::optimizer;
 use crate::parser::Parser;
+use crate::resolver::Resolver;
 
 #[test]
 fn test_eval_integer_expression() {
@@ -239,6 +246,14 @@ fn test_error_handling() {
             "unusable as hash key: FUNCTION",
         ),
         ("a = 1;", "a is not in scope"),
+        ("1 / 0", "division by zero"),
+        ("let a = 1; a /= 0;", "division by zero"),
+        (r#"throw "boom";"#, "boom"),
+        (
+            r#"try { throw "a"; } catch (e) { throw e["message"] + "b"; }"#,
+            "ab",
+        ),
+        (r#"try { 1 } finally { throw "f"; }"#, "f"),
     ];
 
     for (input, expected) in tests {
@@ -353,6 +368,94 @@ fn test_closures() {
     test_integer_object(evaluated, 4)
 }
 
+#[test]
+fn test_environment_copies() {
+    let tests =
+        vec![
+        ("let x = 1; let f = fun() { x }; x = 2; f()", 1),
+        ("let x = 1; let f = fun() { x = 5; x }; f() + x", 6),
+        ("let f = fun(n) { let y = n; y++; y }; let y = 10; f(1) + y", 12),
+        (
+            "let make = fun(n) { fun() { n } }; let a = make(1); let b = make(2); a() + b() * 10",
+            21,
+        ),
+    ];
+
+    for (input, expected) in tests {
+        dbg!(&input);
+        let evaluated = test_eval(input);
+
+        test_integer_object(evaluated, expected)
+    }
+}
+
+#[test]
+fn test_resolved_variables() {
+    let tests = vec![
+        (
+            "let make = fun(a) { fun(b) { fun(c) { a + b + c } } }; make(1)(2)(3)",
+            "6",
+        ),
+        (
+            "let f = fun() { let fact = fun(n) { if (n == 0) { 1 } else { n * fact(n - 1) } }; fact(5) }; f()",
+            "120",
+        ),
+        (
+            "let even = fun(n) { if (n == 0) { true } else { odd(n - 1) } }; let odd = fun(n) { if (n == 0) { false } else { even(n - 1) } }; even(10)",
+            "true",
+        ),
+        ("let f = fun(x) { let y = x; y += 1; y++; y }; f(1)", "3"),
+        (
+            r#"let f = fun() { try { 1 + true } catch (e) { e["kind"] } }; f()"#,
+            "TypeError",
+        ),
+        (
+            "let f = fun(n) { let count = 0; while (n > 0) { n--; count += 2 }; count }; f(3)",
+            "6",
+        ),
+        (
+            "let f = fun() { y }; let g = fun() { let y = 1; f() }; g()",
+            "ERROR: NameError: identifier not found: y",
+        ),
+        (
+            "let counter = fun(step) { let count = 0; fun() { count += step; count } }; let next = counter(2); [next(), next()]",
+            "[2, 2]",
+        ),
+        (
+            "let f = fun(a) { let b = a * 2; fun() { fun() { a++; [a, b] } } }; let g = f(1)(); [g(), g()]",
+            "[[2, 2], [2, 2]]",
+        ),
+        (
+            "let f = fun(n) { let fs = []; for (let i = 0; i < n; i++) { fs = push(fs, fun() { i }); } map(fs, fun(g) { g() }) }; f(3)",
+            "[0, 1, 2]",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, test_eval(input).inspect(), "{}", input);
+    }
+}
+
+#[test]
+fn test_compiled_bodies_are_shared() {
+    let input =
+        "let make = fun(n) { fun() { n } }; let a = make(1); let b = make(2); a() + b() + a()";
+
+    let pro = parse(input);
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_backend(Backend::Vm);
+
+    test_integer_object(evaluator.eval(&pro), 4);
+    match (evaluator.resolve("a"), evaluator.resolve("b")) {
+        (ObjectType::Function(a), ObjectType::Function(b)) => match (a.chunk, b.chunk) {
+            (Some(a), Some(b)) => assert!(Rc::ptr_eq(&a, &b)),
+            other => panic!("Expected compiled functions, got {:?}", other),
+        },
+        other => panic!("Expected functions, got {:?}", other),
+    }
+}
+
 #[test]
 fn test_recursive() {
     let input =
@@ -426,14 +529,14 @@ fn test_array_index_expression() {
 #[test]
 fn test_hash_key() {
     let hello1 = StringObject {
-        value: "Hello".to_string(),
+        value: "Hello".into(),
     };
     let hello2 = StringObject {
-        value: "Hello".to_string(),
+        value: "Hello".into(),
     };
 
     let diff = StringObject {
-        value: "Hello 2".to_string(),
+        value: "Hello 2".into(),
     };
 
     let mut hasher1 = DefaultHasher::new();
@@ -465,19 +568,19 @@ fn test_hash_literals() {
 
     dictionary.insert(
         ObjectType::String(StringObject {
-            value: "one".to_string(),
+            value: "one".into(),
         }),
         1,
     );
     dictionary.insert(
         ObjectType::String(StringObject {
-            value: "two".to_string(),
+            value: "two".into(),
         }),
         2,
     );
     dictionary.insert(
         ObjectType::String(StringObject {
-            value: "three".to_string(),
+            value: "three".into(),
         }),
         3,
     );
@@ -501,6 +604,324 @@ fn test_hash_literals() {
     }
 }
 
+#[test]
+fn test_dict_order() {
+    let tests = vec![
+        (r#"{"b": 1, "a": 2, 3: true}"#, "{b: 1, a: 2, 3: true}"),
+        (r#"{"a": 1, "b": 2, "a": 3}"#, "{a: 3, b: 2}"),
+        (
+            r#"let d = {"z": 0}; let e = {"z": 0, "y": 1}; [d, e]"#,
+            "[{z: 0}, {z: 0, y: 1}]",
+        ),
+        (
+            "{1: 1, [2]: 2, {}: 3}",
+            "ERROR: TypeError: unusable as hash key: ARRAY",
+        ),
+        (
+            "{1: 1, 2: [] + 1, {}: 3}",
+            "ERROR: TypeError: type mismatch: ARRAY + INTEGER",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, test_eval(input).inspect(), "{}", input);
+    }
+}
+
+#[test]
+fn test_tuples() {
+    let tests = vec![
+        ("(1, 2 * 2, (3,))", "(1, 4, (3,))"),
+        ("()", "()"),
+        ("(1, 2)[1]", "2"),
+        ("len((1, 2, 3))", "3"),
+        ("(1, 2)[2]", "ERROR: IndexError: index: 2 out of bounds: 2"),
+        ("(1, [2]) == (1, [2])", "true"),
+        (r#"(1, "1") == (1, 1)"#, "false"),
+        ("(1, 2) != (2, 1)", "true"),
+        ("let d = {(0, 0): 1, (0, 1): 2}; d[(0, 1)]", "2"),
+        ("let d = {(0, 0): 1, (0, 0): 2}; d", "{(0, 0): 2}"),
+        ("let x = 1; {(x, x + 1): true}[(1, 2)]", "true"),
+        ("{((1, 2), 3): 4}[((1, 2), 3)]", "4"),
+        (
+            "{(1, [2]): 3}",
+            "ERROR: TypeError: unusable as hash key: TUPLE",
+        ),
+        (
+            "{}[(1, {})]",
+            "ERROR: TypeError: unusable as hash key: TUPLE",
+        ),
+        ("{[1]: 3}", "ERROR: TypeError: unusable as hash key: ARRAY"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, test_eval(input).inspect(), "{}", input);
+    }
+}
+
+#[test]
+fn test_sets() {
+    let tests = vec![
+        ("#{1, 2, 1, (1, 2)}", "#{1, 2, (1, 2)}"),
+        ("#{}", "#{}"),
+        (r#"set([3, "a", 3])"#, "#{3, a}"),
+        ("set((1, 1))", "#{1}"),
+        ("set()", "#{}"),
+        ("len(#{1, 2, 2})", "2"),
+        ("2 in #{1, 2}", "true"),
+        ("3 in #{1, 2}", "false"),
+        ("(1, 2) in #{(1, 2)}", "true"),
+        ("1 + 1 in #{2} == true", "true"),
+        (
+            "[1] in #{1}",
+            "ERROR: TypeError: unusable as hash key: ARRAY",
+        ),
+        (
+            "1 in 2",
+            "ERROR: TypeError: unknown operator: INTEGER in INTEGER",
+        ),
+        ("union(#{1, 2}, #{3, 2})", "#{1, 2, 3}"),
+        ("intersection(#{1, 2, 3}, #{3, 2})", "#{2, 3}"),
+        ("difference(#{1, 2, 3}, #{2})", "#{1, 3}"),
+        (
+            "union(#{1}, [1])",
+            "ERROR: TypeError: arguments to union must be SET, got SET and ARRAY",
+        ),
+        ("#{1, 2} == #{2, 1}", "true"),
+        ("#{1, 2} != #{1}", "true"),
+        ("map(#{3, 1, 2}, fun(x) { x * 2 })", "[6, 2, 4]"),
+        ("filter(#{1, 2, 3}, fun(x) { x > 1 })", "[2, 3]"),
+        ("#{1, [2]}", "ERROR: TypeError: unusable as hash key: ARRAY"),
+        ("set([{}])", "ERROR: TypeError: unusable as hash key: DICT"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, test_eval(input).inspect(), "{}", input);
+    }
+}
+
+#[test]
+fn test_membership() {
+    let tests = vec![
+        ("2 in [1, 2, 3]", "true"),
+        ("4 in [1, 2, 3]", "false"),
+        (r#""1" in [1, 2]"#, "false"),
+        ("[1] in [[1], [2]]", "true"),
+        ("2 not in [1, 2]", "false"),
+        ("4 not in [1, 2]", "true"),
+        ("2 in (1, 2)", "true"),
+        (r#""a" in {"a": 1}"#, "true"),
+        (r#""b" not in {"a": 1}"#, "true"),
+        (
+            "let d = {1: if (false) { 1 }}; [d[1], 1 in d]",
+            "[null, true]",
+        ),
+        ("(0, 1) in {(0, 1): 2}", "true"),
+        ("[1] in {}", "ERROR: TypeError: unusable as hash key: ARRAY"),
+        (r#""ell" in "hello""#, "true"),
+        (r#""" in "hello""#, "true"),
+        (r#""hi" not in "hello""#, "true"),
+        (
+            r#"1 in "1""#,
+            "ERROR: TypeError: unknown operator: INTEGER in STRING",
+        ),
+        ("3 in range(5)", "true"),
+        ("5 in range(5)", "false"),
+        ("4 in range(0, 10, 2)", "true"),
+        ("5 in range(0, 10, 2)", "false"),
+        ("7 in range(10, 0, -3)", "true"),
+        ("0 in range(10, 0, -3)", "false"),
+        (r#""a" in range(5)"#, "false"),
+        ("1 + 1 in [2] == true", "true"),
+        ("let f = fun(x) { x not in [1, 2] }; f(3)", "true"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, test_eval(input).inspect(), "{}", input);
+    }
+}
+
+#[test]
+fn test_ranges() {
+    let tests = vec![
+        ("range(3)", "range(0, 3)"),
+        ("range(1, 10, 3)", "range(1, 10, 3)"),
+        ("len(range(10))", "10"),
+        ("len(range(1, 10, 3))", "3"),
+        ("len(range(10, 0, -3))", "4"),
+        ("len(range(5, 1))", "0"),
+        ("range(1, 10, 3)[2]", "7"),
+        ("range(10, 0, -3)[3]", "1"),
+        (
+            "range(1, 10, 3)[3]",
+            "ERROR: IndexError: index: 3 out of bounds: range(1, 10, 3)",
+        ),
+        ("range(3) == range(0, 3, 1)", "true"),
+        (
+            "range(1, 2, 0)",
+            "ERROR: ArgumentError: the step of a range can't be 0",
+        ),
+        (
+            r#"range("a")"#,
+            "ERROR: TypeError: arguments to range must be INTEGER, got STRING",
+        ),
+        (
+            "len(range(-170141183460469231731687303715884105727, 170141183460469231731687303715884105727))",
+            "ERROR: ArgumentError: the length of range(-170141183460469231731687303715884105727, 170141183460469231731687303715884105727) is too big",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, test_eval(input).inspect(), "{}", input);
+    }
+}
+
+#[test]
+fn test_structural_equality() {
+    let tests = vec![
+        ("[1, [2, 3]] == [1, [2, 3]]", "true"),
+        ("[1, 2] == [2, 1]", "false"),
+        ("[1] != [1, 1]", "true"),
+        (r#"{"a": 1, "b": [2]} == {"b": [2], "a": 1}"#, "true"),
+        (r#"{"a": 1} == {"a": 2}"#, "false"),
+        (r#"{"a": 1} == {"a": 1, "b": 2}"#, "false"),
+        (r#""abc" == "abc""#, "true"),
+        (r#""abc" != "abd""#, "true"),
+        (r#"1 == "1""#, "false"),
+        (r#"1 != "1""#, "true"),
+        ("[1] == (1,)", "false"),
+        ("true == 1", "false"),
+        ("if (false) { 1 } == if (false) { 2 }", "true"),
+        ("if (false) { 1 } == false", "false"),
+        ("let f = fun(x) { x }; f == f", "true"),
+        ("let f = fun(x) { x }; let g = f; [f] == [g]", "true"),
+        ("fun(x) { x } == fun(x) { x }", "false"),
+        (
+            "let adder = fun(x) { fun(y) { x + y } }; adder(1) == adder(2)",
+            "false",
+        ),
+        ("let f = fun(x) { x }; f in [1, f]", "true"),
+        ("len == len", "true"),
+        ("len == push", "false"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, test_eval(input).inspect(), "{}", input);
+    }
+}
+
+#[test]
+fn test_ordering() {
+    let tests = vec![
+        (r#""abc" < "abd""#, "true"),
+        (r#""b" > "abc""#, "true"),
+        (r#""ab" < "abc""#, "true"),
+        (r#""ab" >= "ab""#, "true"),
+        ("[1] < [2]", "true"),
+        ("[1, 2] < [1, 3]", "true"),
+        ("[1, 2] < [1, 2, 0]", "true"),
+        ("[2] <= [1, 5]", "false"),
+        ("[] < [1]", "true"),
+        ("[[1, 2], 3] > [[1, 1], 4]", "true"),
+        (r#"["a", 1] <= ["a", 1]"#, "true"),
+        ("[1, true] < [2, false]", "true"),
+        (
+            "[true] < [false]",
+            "ERROR: TypeError: unknown operator: BOOLEAN < BOOLEAN",
+        ),
+        (
+            r#"[1] < ["1"]"#,
+            "ERROR: TypeError: type mismatch: INTEGER < STRING",
+        ),
+        (
+            r#"1 < "1""#,
+            "ERROR: TypeError: type mismatch: INTEGER < STRING",
+        ),
+        ("{} < {}", "ERROR: TypeError: unknown operator: DICT < DICT"),
+        (
+            r#""a" - "b""#,
+            "ERROR: TypeError: unknown operator: STRING - STRING",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, test_eval(input).inspect(), "{}", input);
+    }
+}
+
+#[test]
+fn test_big_integers() {
+    let big = "100000000000000000000000000000000000000000";
+    let tests = vec![
+        (
+            "170141183460469231731687303715884105727 + 1".to_string(),
+            "170141183460469231731687303715884105728",
+        ),
+        (
+            "-170141183460469231731687303715884105728".to_string(),
+            "-170141183460469231731687303715884105728",
+        ),
+        (
+            "let x = 170141183460469231731687303715884105727; x + 1 - 1 == x".to_string(),
+            "true",
+        ),
+        (
+            "let x = 170141183460469231731687303715884105727; x += 1; x++; x".to_string(),
+            "170141183460469231731687303715884105729",
+        ),
+        (
+            "let x = -170141183460469231731687303715884105728; -x".to_string(),
+            "170141183460469231731687303715884105728",
+        ),
+        (
+            "let fact = fun(n) { if (n < 2) { 1 } else { n * fact(n - 1) } }; fact(40)"
+                .to_string(),
+            "815915283247897734345611269596115894272000000000",
+        ),
+        (
+            "let modpow = fun(b, e, m) { let result = 1; while (e > 0) { \
+                if (e % 2 == 1) { result = result * b % m; } b = b * b % m; e = e / 2; \
+             }; result }; \
+             modpow(3, 10000000000000000000000000000000000000000, \
+                    170141183460469231731687303715884105727)"
+                .to_string(),
+            "81779306578532292258527083327619434864",
+        ),
+        (
+            "99999999999999999999999999999999999999999 / 3".to_string(),
+            "33333333333333333333333333333333333333333",
+        ),
+        (
+            format!("-{big} / 7"),
+            "-14285714285714285714285714285714285714285",
+        ),
+        (format!("-{big} % 7"), "-5"),
+        (format!("{big} - {big} == 0"), "true"),
+        (format!("{big} == {big}"), "true"),
+        (format!("{big} > 1"), "true"),
+        (format!("-{big} < 1"), "true"),
+        (format!("[{big}] > [5]"), "true"),
+        (format!("{{{big}: 1}}[{big}]"), "1"),
+        (format!("{big} in #{{{big}}}"), "true"),
+        (
+            format!("{big} / 0"),
+            "ERROR: ZeroDivisionError: division by zero",
+        ),
+        (
+            format!("[1][{big}]"),
+            "ERROR: IndexError: index: 100000000000000000000000000000000000000000 out of bounds: 1",
+        ),
+        (
+            format!("range({big})"),
+            "ERROR: ArgumentError: the argument 100000000000000000000000000000000000000000 to range is too big",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, test_eval(&input).inspect(), "{}", input);
+    }
+}
+
 #[test]
 fn test_hash_index_expression() {
     let tests = vec![
@@ -526,52 +947,1177 @@ fn test_hash_index_expression() {
     }
 }
 
-//-------------------[Test helpers]-------------------//
+#[test]
+fn test_try_catch_expressions() {
+    let tests = vec![
+        ("try { 1 } catch (e) { 2 }", 1),
+        (r#"try { throw "boom"; 1 } catch (e) { 2 }"#, 2),
+        ("try { 1 / 0 } catch (e) { 3 }", 3),
+        ("try { [1, 2][5] } catch (e) { 4 }", 4),
+        (
+            r#"let x = 0; try { throw "a"; } catch (e) { x = 1; } finally { x += 10; } x;"#,
+            11,
+        ),
+        ("let x = 0; try { x = 1 } finally { x = 2 }; x", 2),
+        ("let f = fun() { try { return 1; } finally { 2 } }; f()", 1),
+        (
+            r#"try { try { throw "a"; } catch (e) { throw e; } } catch (e) { 5 }"#,
+            5,
+        ),
+        (
+            "let x = 0; loop { try { x++; if (x == 3) { break; } } finally { x += 0 } } x;",
+            3,
+        ),
+        (
+            r#"let f = fun() { throw "inner"; }; try { f() } catch (e) { len(e["stack"]) }"#,
+            1,
+        ),
+    ];
 
-fn test_null_object(evaluated: ObjectType) {
-    match evaluated {
-        ObjectType::Null => (),
-        actual => panic!("Expected an null, got {:?}", actual),
-    };
-}
+    for (input, expected) in tests {
+        dbg!(&input);
+        dbg!(&expected);
+        let evaluated = test_eval(input);
 
-fn test_integer_object(evaluated: ObjectType, expected: i128) {
-    let eval = match evaluated {
-        ObjectType::Integer(exp) => exp,
-        actual => panic!("Expected an integer, got {:?}", actual),
-    };
-    assert_eq!(eval.value, expected)
+        test_integer_object(evaluated, expected)
+    }
 }
 
-fn test_boolean_object(evaluated: ObjectType, expected: bool) {
-    let eval = match evaluated {
-        ObjectType::Boolean(exp) => exp,
-        actual => panic!("Expected an boolean, got {:?}", actual),
-    };
-    assert_eq!(eval.value, expected)
-}
+#[test]
+fn test_caught_error_fields() {
+    let tests = vec![
+        (
+            r#"try { throw "boom"; } catch (e) { e["message"] }"#,
+            "boom",
+        ),
+        (r#"try { throw "boom"; } catch (e) { e["kind"] }"#, "Error"),
+        (r#"try { 5 + true } catch (e) { e["kind"] }"#, "TypeError"),
+        (
+            r#"try { 5 + true } catch (e) { e["message"] }"#,
+            "type mismatch: INTEGER + BOOLEAN",
+        ),
+        (r#"try { [1][3] } catch (e) { e["kind"] }"#, "IndexError"),
+        (r#"try { foo } catch (e) { e["kind"] }"#, "NameError"),
+        (
+            r#"try { len(1, 2) } catch (e) { e["kind"] }"#,
+            "ArgumentError",
+        ),
+        (
+            r#"try { 1 % 0 } catch (e) { e["kind"] }"#,
+            "ZeroDivisionError",
+        ),
+        (
+            r#"try { throw {"kind": "ValueError", "message": "bad"}; } catch (e) { e["kind"] + ": " + e["message"] }"#,
+            "ValueError: bad",
+        ),
+        (
+            r#"let inner = fun() { throw "x"; };
+            let outer = fun() { let result = inner(); result };
+            try { outer() } catch (e) { e["stack"][0]["function"] + " " + e["stack"][1]["function"] }"#,
+            "inner outer",
+        ),
+    ];
 
-fn test_string_object(evaluated: ObjectType, expected: String) {
-    let eval = match evaluated {
-        ObjectType::String(exp) => exp,
-        actual => panic!("Expected a string, got {:?}", actual),
-    };
-    assert_eq!(eval.value, expected)
-}
+    for (input, expected) in tests {
+        dbg!(&input);
+        dbg!(&expected);
+        let evaluated = test_eval(input);
 
-fn test_error_object(evaluated: ObjectType, expected: &str) {
-    let eval = match evaluated {
-        ObjectType::Error(exp) => exp,
-        actual => panic!("Expected an error, got {:?}", actual),
-    };
-    assert_eq!(eval.message, expected)
+        test_string_object(evaluated, expected.to_string())
+    }
 }
 
-fn test_eval(input: &str) -> ObjectType {
-    let mut par = Parser::new(Lexer::new(input.to_string()));
-    let pro = par.parse_program();
-
-    let mut evaluator = Evaluator::new_env(Environment::new());
-
-    evaluator.eval(pro)
+#[test]
+fn test_catch_parameter_scope() {
+    let tests = vec![
+        (
+            r#"let r = try { throw "x" } catch (e) { 1 }; e"#,
+            "ERROR: NameError: identifier not found: e",
+        ),
+        (
+            r#"let e = 5; let r = try { throw "x" } catch (e) { e["message"] }; [r, e]"#,
+            "[x, 5]",
+        ),
+        (
+            r#"let f = fun() { try { throw "x" } catch (e) { 1 }; e }; f()"#,
+            "ERROR: NameError: identifier not found: e",
+        ),
+        (
+            r#"let f = fun() { let e = 5; let r = try { throw "x" } catch (e) { e["message"] }; [r, e] }; f()"#,
+            "[x, 5]",
+        ),
+        (r#"try { throw "x" } catch (e) { let y = 2; }; y"#, "2"),
+        (
+            r#"let g = try { throw "x" } catch (e) { fun() { e["message"] } }; g()"#,
+            "x",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, test_eval(input).inspect(), "{}", input);
+    }
+}
+
+#[test]
+fn test_error_kinds() {
+    let tests = vec![
+        ("5 + true;", "TypeError"),
+        ("foobar;", "NameError"),
+        ("[1, 2, 3][3]", "IndexError"),
+        ("1 / 0", "ZeroDivisionError"),
+        (r#"throw "boom";"#, "Error"),
+        (r#"throw {"kind": "ValueError"};"#, "ValueError"),
+    ];
+
+    for (input, expected) in tests {
+        dbg!(&input);
+        dbg!(&expected);
+        let evaluated = test_eval(input);
+
+        match evaluated {
+            ObjectType::Error(error) => assert_eq!(error.kind, expected),
+            actual => panic!("Expected an error, got {:?}", actual),
+        }
+    }
+}
+
+#[test]
+fn test_result_values() {
+    let tests = vec![
+        ("ok(1)", "ok(1)"),
+        (r#"err("boom")"#, "err(boom)"),
+        ("is_ok(ok(1))", "true"),
+        ("is_err(ok(1))", "false"),
+        ("unwrap(ok(2))", "2"),
+        (r#"unwrap_err(err("boom"))"#, "boom"),
+        ("ok(1)?", "1"),
+        (r#"err("boom")?; 5"#, "err(boom)"),
+        (
+            r#"let half = fun(x) { if (x % 2 == 0) { ok(x / 2) } else { err("odd") } };
+            let quarter = fun(x) { ok(half(half(x)?)?) };
+            quarter(8)"#,
+            "ok(2)",
+        ),
+        (
+            r#"let half = fun(x) { if (x % 2 == 0) { ok(x / 2) } else { err("odd") } };
+            let quarter = fun(x) { let y = half(x)?; ok(half(y)?) };
+            quarter(6)"#,
+            "err(odd)",
+        ),
+        (
+            r#"let sum = fun(xs) { let total = 0; for (let i = 0; i < len(xs); i++) { total += xs[i]?; } ok(total) };
+            sum([ok(1), ok(2)])"#,
+            "ok(3)",
+        ),
+        (
+            r#"let sum = fun(xs) { let total = 0; for (let i = 0; i < len(xs); i++) { total += xs[i]?; } ok(total) };
+            sum([ok(1), err("bad"), ok(5)])"#,
+            "err(bad)",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        dbg!(&input);
+        dbg!(&expected);
+        let evaluated = test_eval(input);
+
+        assert_eq!(expected, evaluated.inspect())
+    }
+}
+
+#[test]
+fn test_result_errors() {
+    let tests = vec![
+        ("1?", "? operator not supported: INTEGER"),
+        ("ok(1, 2)", "wrong number of arguments. got: 2, want: 1"),
+        ("unwrap(1)", "argument to unwrap not supported, got INTEGER"),
+        (r#"unwrap(err("boom"))"#, "called unwrap on err(boom)"),
+        ("unwrap_err(ok(1))", "called unwrap_err on ok(1)"),
+        ("{ok(1): 1}", "unusable as hash key: RESULT"),
+    ];
+
+    for (input, expected) in tests {
+        dbg!(&input);
+        dbg!(&expected);
+        let evaluated = test_eval(input);
+
+        test_error_object(evaluated, expected)
+    }
+}
+
+#[test]
+fn test_stack_traces() {
+    let input = "let fail = fun(x) { x + true };
+let twice = fun(x) {
+    fail(x) + fail(x)
+};
+let apply = fun(f, x) { let result = f(x); result };
+apply(twice, 1);";
+
+    let evaluated = test_eval(input);
+
+    let error = match evaluated {
+        ObjectType::Error(error) => error,
+        actual => panic!("Expected an error, got {:?}", actual),
+    };
+
+    assert_eq!(
+        "    at fail (input.hc:3:5)
+    at twice (input.hc:5:38)
+    at apply (input.hc:6:1)
+",
+        error.trace("input.hc")
+    );
+}
+
+#[test]
+fn test_caught_stack_trace() {
+    let tests = vec![
+        (
+            r#"let f = fun() { try { throw "x"; } catch (e) { e["stack"][0]["function"] } }; f()"#,
+            "f",
+        ),
+        (
+            r#"try { fun() { throw "x"; }() } catch (e) { e["stack"][0]["function"] }"#,
+            "<anonymous>",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        dbg!(&input);
+        dbg!(&expected);
+        let evaluated = test_eval(input);
+
+        test_string_object(evaluated, expected.to_string())
+    }
+
+    let input = r#"let f = fun() { throw "x"; };
+            try { f() } catch (e) { [e["stack"][0]["line"], e["stack"][0]["column"]] }"#;
+    let evaluated = test_eval(input);
+
+    assert_eq!("[2, 19]", evaluated.inspect());
+}
+
+#[test]
+fn test_max_depth() {
+    let input = "let f = fun(x) { 1 + f(x + 1) }; f(1)";
+
+    for backend in BACKENDS {
+        let pro = parse(input);
+
+        let mut evaluator = Evaluator::new();
+        evaluator.set_backend(backend);
+        evaluator.set_max_depth(5);
+
+        let error = match evaluator.eval(&pro) {
+            ObjectType::Error(error) => error,
+            actual => panic!("Expected an error, got {:?}", actual),
+        };
+
+        assert_eq!(STACK_OVERFLOW_ERROR, error.kind);
+        assert_eq!(
+            "stack overflow: maximum call depth of 5 exceeded",
+            error.message
+        );
+        assert_eq!(
+            "    at f (input.hc:1:22)\n    ... repeated 3 more times\n    at f (input.hc:1:34)\n",
+            error.trace("input.hc")
+        );
+    }
+}
+
+#[test]
+fn test_catch_stack_overflow() {
+    let input = r#"let f = fun(x) { 1 + f(x + 1) };
+    let depth = fun(x) { try { f(x) } catch (e) { [e["kind"], len(e["stack"])] } };
+    depth(1)"#;
+
+    for backend in BACKENDS {
+        let pro = parse(input);
+
+        let mut evaluator = Evaluator::new();
+        evaluator.set_backend(backend);
+        evaluator.set_max_depth(8);
+
+        assert_eq!("[StackOverflowError, 8]", evaluator.eval(&pro).inspect());
+    }
+}
+
+#[test]
+fn test_tail_calls() {
+    let tests = vec![
+        (
+            "let sum = fun(n, acc) { if (n == 0) { acc } else { sum(n - 1, acc + n) } }; sum(1000, 0)",
+            "500500",
+        ),
+        (
+            "let count = fun(n) { if (n == 0) { return 0; } return count(n - 1); }; count(1000)",
+            "0",
+        ),
+        (
+            "let count = fun(n) { if (n > 0) { return count(n - 1); } n }; count(1000)",
+            "0",
+        ),
+        (
+            r#"let even = fun(n) { if (n == 0) { true } else { odd(n - 1) } };
+            let odd = fun(n) { if (n == 0) { false } else { even(n - 1) } };
+            [even(1000), odd(1000)]"#,
+            "[true, false]",
+        ),
+        (
+            "let count = fun(n) { if (n == 0) { len(\"done\") } else { count(n - 1) } }; count(1000)",
+            "4",
+        ),
+    ];
+
+    for backend in BACKENDS {
+        for (input, expected) in tests.clone() {
+            let pro = parse(input);
+
+            let mut evaluator = Evaluator::new();
+            evaluator.set_backend(backend);
+            evaluator.set_max_depth(10);
+
+            assert_eq!(
+                expected,
+                evaluator.eval(&pro).inspect(),
+                "{:?}: {}",
+                backend,
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn test_non_tail_calls() {
+    let tests = vec![
+        "let sum = fun(n) { if (n == 0) { 0 } else { n + sum(n - 1) } }; sum(20)",
+        "let count = fun(n) { if (n == 0) { 0 } else { let x = count(n - 1); x } }; count(20)",
+        "let count = fun(n) { if (n == 0) { 0 } else { try { count(n - 1) } finally { } } }; count(20)",
+    ];
+
+    for backend in BACKENDS {
+        for input in tests.clone() {
+            let pro = parse(input);
+
+            let mut evaluator = Evaluator::new();
+            evaluator.set_backend(backend);
+            evaluator.set_max_depth(10);
+
+            match evaluator.eval(&pro) {
+                ObjectType::Error(error) => {
+                    assert_eq!(STACK_OVERFLOW_ERROR, error.kind, "{:?}: {}", backend, input)
+                }
+                actual => panic!("Expected a stack overflow in {}, got {:?}", input, actual),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_tail_recursion_is_stopped_by_the_step_limit() {
+    let input = "let f = fun(x){ f(x+1) }; f(0)";
+
+    for backend in BACKENDS {
+        let pro = parse(input);
+
+        let mut evaluator = Evaluator::new();
+        evaluator.set_backend(backend);
+        evaluator.set_max_depth(5);
+        evaluator.set_step_limit(Some(10_000));
+
+        let error = match evaluator.eval(&pro) {
+            ObjectType::Error(error) => error,
+            actual => panic!("Expected an error, got {:?}", actual),
+        };
+
+        assert_eq!(EXECUTION_LIMIT_ERROR, error.kind, "{:?}", backend);
+        assert_eq!(
+            "    at f (input.hc:1:17)\n",
+            error.trace("input.hc"),
+            "{:?}",
+            backend
+        );
+    }
+}
+
+#[test]
+fn test_tail_call_stack_trace() {
+    let input = "let fail = fun(n) { if (n == 0) { 1 + true } else { fail(n - 1) } };
+let run = fun() { let x = fail(3); x };
+run()";
+
+    for backend in BACKENDS {
+        let pro = parse(input);
+
+        let mut evaluator = Evaluator::new();
+        evaluator.set_backend(backend);
+
+        let error = match evaluator.eval(&pro) {
+            ObjectType::Error(error) => error,
+            actual => panic!("Expected an error, got {:?}", actual),
+        };
+
+        assert_eq!(
+            "    at fail (input.hc:1:53)\n    at run (input.hc:3:1)\n",
+            error.trace("input.hc")
+        );
+    }
+}
+
+#[test]
+fn test_step_limit() {
+    let tests = vec![
+        "loop { }",
+        "let i = 0; while (true) { i++ }",
+        "let f = fun(n) { f(n + 1) }; f(0)",
+        "let f = fun() { loop { } }; try { f() } catch (e) { 1 } finally { 2 }",
+    ];
+
+    for backend in BACKENDS {
+        for input in tests.clone() {
+            let pro = parse(input);
+
+            let mut evaluator = Evaluator::new();
+            evaluator.set_backend(backend);
+            evaluator.set_step_limit(Some(1000));
+
+            match evaluator.eval(&pro) {
+                ObjectType::Error(error) => {
+                    assert_eq!(
+                        EXECUTION_LIMIT_ERROR, error.kind,
+                        "{:?}: {}",
+                        backend, input
+                    );
+                    assert_eq!(
+                        "execution limit exceeded: step limit of 1000 steps",
+                        error.message
+                    );
+                }
+                actual => panic!(
+                    "Expected an execution limit error in {}, got {:?}",
+                    input, actual
+                ),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_step_limit_per_program() {
+    for backend in BACKENDS {
+        let mut evaluator = Evaluator::new();
+        evaluator.set_backend(backend);
+        evaluator.set_step_limit(Some(1000));
+
+        for _ in 0..3 {
+            let pro = parse("let i = 0; while (i < 50) { i++ }; i");
+
+            assert_eq!("50", evaluator.eval(&pro).inspect());
+        }
+    }
+}
+
+#[test]
+fn test_time_limit() {
+    for backend in BACKENDS {
+        let pro = parse("loop { }");
+
+        let mut evaluator = Evaluator::new();
+        evaluator.set_backend(backend);
+        evaluator.set_time_limit(Some(Duration::from_millis(50)));
+
+        match evaluator.eval(&pro) {
+            ObjectType::Error(error) => {
+                assert_eq!(EXECUTION_LIMIT_ERROR, error.kind);
+                assert_eq!(
+                    "execution limit exceeded: time limit of 50 ms",
+                    error.message
+                );
+            }
+            actual => panic!("Expected an execution limit error, got {:?}", actual),
+        }
+    }
+}
+
+#[test]
+fn test_memory_limit() {
+    let tests = vec![
+        ("let a = [1]; loop { a = push(a, a) }", ARRAY),
+        (r#"let s = "hola"; loop { s = s + s }"#, STRING),
+        (r#"let s = "hola"; loop { s = [s + s, s + s][0] }"#, STRING),
+        (r#"let s = "hola"; loop { s = {s: s}[s] + s }"#, STRING),
+        (
+            r#"let s = "hola"; loop { s = {"key": s + s}; s = s["key"] }"#,
+            STRING,
+        ),
+    ];
+
+    for backend in BACKENDS {
+        for (input, object_type) in tests.clone() {
+            let pro = parse(input);
+
+            let mut evaluator = Evaluator::new();
+            evaluator.set_backend(backend);
+            evaluator.set_memory_limit(Some(4096));
+
+            match evaluator.eval(&pro) {
+                ObjectType::Error(error) => {
+                    assert_eq!(MEMORY_ERROR, error.kind, "{:?}: {}", backend, input);
+                    assert!(
+                        error
+                            .message
+                            .starts_with(&format!("memory limit exceeded: {} of ", object_type)),
+                        "{}",
+                        error.message
+                    );
+                    assert!(error.message.ends_with("over the limit of 4096 bytes"));
+                }
+                actual => panic!("Expected a memory error in {}, got {:?}", input, actual),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_memory_limit_counts_every_value() {
+    let chain = |iterations: &str| {
+        format!(
+            "let big = []; let i = 0; while (i < 20) {{ big = push(big, i); i++ }};
+            let chain = fun() {{ 0 }};
+            let j = 0;
+            while (j < {iterations}) {{
+                let previous = chain;
+                let kept = push(big, j);
+                chain = fun() {{ len(kept) + previous() }};
+                j++
+            }};
+            chain()"
+        )
+    };
+    let tests = vec![
+        (chain("10"), "210"),
+        (
+            chain("1000"),
+            "ERROR: MemoryError: memory limit exceeded: ARRAY of",
+        ),
+        (
+            r#"let words = []; loop { words = push(words, "ab" + "cd") }"#.to_string(),
+            "ERROR: MemoryError: memory limit exceeded: ",
+        ),
+    ];
+
+    for backend in BACKENDS {
+        for (input, expected) in tests.clone() {
+            let pro = parse(&input);
+
+            let mut evaluator = Evaluator::new();
+            evaluator.set_backend(backend);
+            evaluator.set_memory_limit(Some(100_000));
+
+            let result = evaluator.eval(&pro).inspect();
+            assert!(
+                result.starts_with(expected),
+                "{:?}: {}: {}",
+                backend,
+                input,
+                result
+            );
+        }
+    }
+}
+
+#[test]
+fn test_memory_limit_counts_held_values() {
+    let tests = vec![
+        (
+            r#"let x = "abcdefghij"; let i = 0; while (i < 100000) { let s = x + "klmnop"; i++ }; i"#,
+            "100000",
+        ),
+        (
+            r#"let f = fun(x) { let i = 0; while (i < 100000) { let s = x + "klmnop"; i++ }; i }; f("abcdefghij")"#,
+            "100000",
+        ),
+        (
+            "let a = [1]; loop { a = push(a, a) }",
+            "ERROR: MemoryError: memory limit exceeded: ARRAY of",
+        ),
+        (
+            "let big = []; let i = 0; while (i < 100) { big = push(big, i); i++ };
+            let f = fun(n) { if (n == 0) { 0 } else { let kept = push(big, n); f(n - 1) + len(kept) } };
+            f(500)",
+            "ERROR: MemoryError: memory limit exceeded: ARRAY of",
+        ),
+    ];
+
+    for backend in BACKENDS {
+        for (input, expected) in tests.clone() {
+            let pro = parse(input);
+
+            let mut evaluator = Evaluator::new();
+            evaluator.set_backend(backend);
+            evaluator.set_memory_limit(Some(1_000_000));
+
+            let result = evaluator.eval(&pro).inspect();
+            assert!(
+                result.starts_with(expected),
+                "{:?}: {}: {}",
+                backend,
+                input,
+                result
+            );
+        }
+    }
+}
+
+#[test]
+fn test_memory_limit_is_catchable() {
+    let input = r#"let a = [1];
+    let result = try { loop { a = push(a, a) } } catch (e) { e["kind"] };
+    let grown = len(a) > 1;
+    a = 0;
+    let outcome = [result, grown, a];
+    outcome"#;
+
+    for backend in BACKENDS {
+        let pro = parse(input);
+
+        let mut evaluator = Evaluator::new();
+        evaluator.set_backend(backend);
+        evaluator.set_memory_limit(Some(4096));
+
+        assert_eq!("[MemoryError, true, 0]", evaluator.eval(&pro).inspect());
+    }
+}
+
+#[test]
+fn test_interrupt() {
+    for backend in BACKENDS {
+        let interrupt = Arc::new(AtomicBool::new(false));
+        let mut evaluator = Evaluator::new();
+        evaluator.set_backend(backend);
+        evaluator.set_interrupt_flag(interrupt.clone());
+
+        let inputs = vec![
+            ("let x = 1; x", "1"),
+            (
+                "x = 2; loop { x = x }",
+                "ERROR: InterruptError: interrupted",
+            ),
+            (
+                "let f = fun() { loop { } }; try { f() } catch (e) { 0 }",
+                "ERROR: InterruptError: interrupted",
+            ),
+            ("x", "2"),
+        ];
+
+        for (input, expected) in inputs {
+            let pro = parse(input);
+
+            interrupt.store(true, Ordering::Relaxed);
+            let setter = interrupt.clone();
+            let interrupter = std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                setter.store(true, Ordering::Relaxed);
+            });
+
+            assert_eq!(
+                expected,
+                evaluator.eval(&pro).inspect(),
+                "{:?}: {}",
+                backend,
+                input
+            );
+            interrupter.join().unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_registered_functions() {
+    let tests = vec![
+        ("double(21)", "42"),
+        ("let apply = fun(f, x) { f(x) }; apply(double, 4)", "8"),
+        ("let twice = fun(x) { double(double(x)) }; twice(3)", "12"),
+        ("let double = fun(x) { x }; double(5)", "5"),
+        (
+            r#"try { double("x") } catch (e) { e["kind"] + ": " + e["message"] }"#,
+            "TypeError: double expects an integer",
+        ),
+        ("double", "BUILTIN"),
+        ("counter(); counter(); counter()", "3"),
+    ];
+
+    for backend in BACKENDS {
+        for (input, expected) in tests.clone() {
+            let pro = parse(input);
+
+            let mut evaluator = Evaluator::new();
+            evaluator.set_backend(backend);
+            evaluator.register_function("double", |_, args| match &args[..] {
+                [ObjectType::Integer(int)] => Ok(ObjectType::Integer(Integer {
+                    value: int.value * 2,
+                })),
+                _ => Err(Error::new(TYPE_ERROR, "double expects an integer")),
+            });
+
+            let count = Rc::new(std::cell::Cell::new(0));
+            evaluator.register_function("counter", move |_, _| {
+                count.set(count.get() + 1);
+                Ok(ObjectType::Integer(Integer { value: count.get() }))
+            });
+
+            assert_eq!(
+                expected,
+                evaluator.eval(&pro).inspect(),
+                "{:?}: {}",
+                backend,
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn test_higher_order_builtins() {
+    let tests = vec![
+        ("map([1, 2, 3], fun(x) { x * 2 })", "[2, 4, 6]"),
+        ("map([], fun(x) { x })", "[]"),
+        ("map([[1], [1, 2]], len)", "[1, 2]"),
+        ("filter([1, 2, 3, 4], fun(x) { x % 2 == 0 })", "[2, 4]"),
+        ("reduce([1, 2, 3], fun(acc, x) { acc + x })", "6"),
+        ("reduce([1, 2, 3], fun(acc, x) { acc + x }, 10)", "16"),
+        ("reduce([], fun(acc, x) { acc + x }, 0)", "0"),
+        (
+            r#"sort_by(["ccc", "a", "bb"], fun(s) { len(s) })"#,
+            "[a, bb, ccc]",
+        ),
+        (
+            r#"sort_by([["b", 1], ["a", 2], ["b", 0]], fun(p) { p[0] })"#,
+            "[[a, 2], [b, 1], [b, 0]]",
+        ),
+        ("any([1, 2, 3], fun(x) { x > 2 })", "true"),
+        ("any([], fun(x) { true })", "false"),
+        ("all([1, 2, 3], fun(x) { x > 0 })", "true"),
+        ("all([1, 2, 3], fun(x) { x > 1 })", "false"),
+        ("find([1, 2, 3], fun(x) { x > 1 })", "2"),
+        ("find([1, 2, 3], fun(x) { x > 5 })", "null"),
+        ("any([3, 2, 0], fun(x) { 6 / x > 2 })", "true"),
+        ("all([2, 1, 0], fun(x) { 2 / x > 1 })", "false"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, test_eval(input).inspect(), "{}", input);
+    }
+}
+
+#[test]
+fn test_shared_values() {
+    let tests = vec![
+        (
+            "let a = [1, 2]; let b = push(a, 3); [a, b]",
+            "[[1, 2], [1, 2, 3]]",
+        ),
+        (
+            "let a = [1]; let b = a; a = push(a, 2); let c = [a, b]; c",
+            "[[1, 2], [1]]",
+        ),
+        (
+            "let a = [[1]]; let b = push(a[0], 2); [a, b]",
+            "[[[1]], [1, 2]]",
+        ),
+        (
+            "let f = fun(a) { push(a, 0) }; let a = [1]; [f(a), a]",
+            "[[1, 0], [1]]",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, test_eval(input).inspect(), "{}", input);
+    }
+}
+
+#[test]
+fn test_higher_order_builtin_errors() {
+    let tests = vec![
+        (
+            "map([1, 2], fun(x) { x + true })",
+            TYPE_ERROR,
+            "type mismatch: INTEGER + BOOLEAN",
+        ),
+        (
+            "map(1, fun(x) { x })",
+            TYPE_ERROR,
+            "argument to map not supported, got INTEGER",
+        ),
+        (
+            "filter([1], 2)",
+            TYPE_ERROR,
+            "second argument to filter must be a function, got INTEGER",
+        ),
+        (
+            "map([1], fun(a, b) { a })",
+            ARGUMENT_ERROR,
+            "wrong number of arguments. got: 1, want: 2",
+        ),
+        (
+            "reduce([], fun(acc, x) { acc + x })",
+            ARGUMENT_ERROR,
+            "reduce of an empty array with no initial value",
+        ),
+        (
+            r#"sort_by([1, "a"], fun(x) { x })"#,
+            TYPE_ERROR,
+            "sort_by keys must have the same type, got INTEGER and STRING",
+        ),
+        (
+            "sort_by([true], fun(x) { x })",
+            TYPE_ERROR,
+            "sort_by keys must be INTEGER or STRING, got BOOLEAN",
+        ),
+        (
+            r#"find([1], fun(x) { throw "boom"; })"#,
+            GENERIC_ERROR,
+            "boom",
+        ),
+    ];
+
+    for (input, kind, message) in tests {
+        match test_eval(input) {
+            ObjectType::Error(error) => {
+                assert_eq!(kind, error.kind, "{}", input);
+                assert_eq!(message, error.message, "{}", input);
+            }
+            actual => panic!("Expected an error in {}, got {:?}", input, actual),
+        }
+    }
+
+    let input = r#"try { all([1], fun(x) { throw "x"; }) } catch (e) { e["message"] }"#;
+    assert_eq!("x", test_eval(input).inspect());
+}
+
+#[test]
+fn test_higher_order_builtin_stack_trace() {
+    let input = "let fail = fun(x) { x + true };
+let double = fun(xs) {
+    map(xs, fail)
+};
+double([1]);";
+
+    let error = match test_eval(input) {
+        ObjectType::Error(error) => error,
+        actual => panic!("Expected an error, got {:?}", actual),
+    };
+
+    assert_eq!(
+        "    at fail (input.hc:3:5)
+    at double (input.hc:5:1)
+",
+        error.trace("input.hc")
+    );
+}
+
+#[test]
+fn test_output() {
+    let tests = vec![
+        (r#"print("hola")"#, "hola"),
+        (r#"println("hola")"#, "hola\n"),
+        (
+            r#"print("hola", "mundo", 1, [1, "a"])"#,
+            "hola mundo 1 [1, a]",
+        ),
+        (
+            r#"println(); print("a"); println("b", true)"#,
+            "\nab true\n",
+        ),
+        (
+            "let f = fun(x) { println(x); x }; print(f(1) + f(2))",
+            "1\n2\n3",
+        ),
+    ];
+
+    for backend in BACKENDS {
+        for (input, expected) in tests.clone() {
+            let pro = parse(input);
+
+            let output = SharedOutput::default();
+            let mut evaluator = Evaluator::new();
+            evaluator.set_backend(backend);
+            evaluator.set_output(output.clone());
+
+            test_null_object(evaluator.eval(&pro));
+            assert_eq!(expected, output.text(), "{:?}: {}", backend, input);
+        }
+    }
+}
+
+#[test]
+fn test_io_builtins() {
+    let dir = std::env::temp_dir().join(format!("halcon_io_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("notes.txt").display().to_string();
+
+    let tests = vec![
+        (format!(r#"write_file("{}", "hola")"#, file), "null"),
+        (format!(r#"read_file("{}")"#, file), "hola"),
+        (
+            format!(
+                r#"try {{ read_file("{}.missing") }} catch (e) {{ e["kind"] }}"#,
+                file
+            ),
+            IO_ERROR,
+        ),
+        (r#"len(env("PATH")) > 0"#.to_string(), "true"),
+        (
+            r#"env("HALCON_TEST_UNDEFINED_VARIABLE")"#.to_string(),
+            "null",
+        ),
+        (
+            r#"exec("echo", ["hola", "mundo"])["stdout"]"#.to_string(),
+            "hola mundo\n",
+        ),
+        (r#"exec("sh", ["-c", "exit 3"])["status"]"#.to_string(), "3"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, test_eval(&input).inspect(), "{}", input);
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_permissions() {
+    let dir = std::env::temp_dir().join(format!("halcon_permissions_{}", std::process::id()));
+    let data = dir.join("data");
+    fs::create_dir_all(&data).unwrap();
+    fs::write(data.join("allowed.txt"), "allowed").unwrap();
+    fs::write(dir.join("secret.txt"), "secret").unwrap();
+    let data_path = data.display().to_string();
+    let dir_path = dir.display().to_string();
+
+    let tests = vec![
+        (
+            format!(r#"read_file("{}/allowed.txt")"#, data_path),
+            "allowed".to_string(),
+        ),
+        (
+            format!(
+                r#"write_file("{}/new.txt", "x"); read_file("{}/new.txt")"#,
+                data_path, data_path
+            ),
+            "x".to_string(),
+        ),
+        (
+            format!(r#"read_file("{}/secret.txt")"#, dir_path),
+            format!(
+                "ERROR: PermissionError: permission denied: read access to {}/secret.txt",
+                dir_path
+            ),
+        ),
+        (
+            format!(r#"read_file("{}/../secret.txt")"#, data_path),
+            format!(
+                "ERROR: PermissionError: permission denied: read access to {}/../secret.txt",
+                data_path
+            ),
+        ),
+        (
+            format!(r#"write_file("{}/secret.txt", "x")"#, dir_path),
+            format!(
+                "ERROR: PermissionError: permission denied: write access to {}/secret.txt",
+                dir_path
+            ),
+        ),
+        (
+            r#"env("PATH")"#.to_string(),
+            "ERROR: PermissionError: permission denied: environment access to PATH".to_string(),
+        ),
+        (
+            r#"exec("echo")"#.to_string(),
+            "ERROR: PermissionError: permission denied: running echo".to_string(),
+        ),
+        (
+            r#"try { exec("echo") } catch (e) { e["kind"] }"#.to_string(),
+            PERMISSION_ERROR.to_string(),
+        ),
+    ];
+
+    for backend in BACKENDS {
+        for (input, expected) in tests.clone() {
+            let pro = parse(&input);
+
+            let mut permissions = Permissions::none();
+            permissions.read = PathAccess::Only(vec![data.clone()]);
+            permissions.write = PathAccess::Only(vec![data.clone()]);
+            let mut evaluator = Evaluator::new();
+            evaluator.set_backend(backend);
+            evaluator.set_permissions(permissions);
+
+            assert_eq!(
+                expected,
+                evaluator.eval(&pro).inspect(),
+                "{:?}: {}",
+                backend,
+                input
+            );
+        }
+    }
+
+    assert_eq!(
+        "secret",
+        fs::read_to_string(dir.join("secret.txt")).unwrap()
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn test_permissions_reject_dangling_symlinks() {
+    let dir = std::env::temp_dir().join(format!("halcon_symlinks_{}", std::process::id()));
+    let data = dir.join("data");
+    fs::create_dir_all(&data).unwrap();
+    std::os::unix::fs::symlink(dir.join("outside.txt"), data.join("link.txt")).unwrap();
+    let link_path = data.join("link.txt").display().to_string();
+
+    let tests = vec![
+        (
+            format!(r#"write_file("{}", "x")"#, link_path),
+            format!(
+                "ERROR: PermissionError: permission denied: write access to {}",
+                link_path
+            ),
+        ),
+        (
+            format!(r#"read_file("{}")"#, link_path),
+            format!(
+                "ERROR: PermissionError: permission denied: read access to {}",
+                link_path
+            ),
+        ),
+    ];
+
+    for backend in BACKENDS {
+        for (input, expected) in tests.clone() {
+            let pro = parse(&input);
+
+            let mut permissions = Permissions::none();
+            permissions.read = PathAccess::Only(vec![data.clone()]);
+            permissions.write = PathAccess::Only(vec![data.clone()]);
+            let mut evaluator = Evaluator::new();
+            evaluator.set_backend(backend);
+            evaluator.set_permissions(permissions);
+
+            assert_eq!(
+                expected,
+                evaluator.eval(&pro).inspect(),
+                "{:?}: {}",
+                backend,
+                input
+            );
+        }
+    }
+
+    assert!(!dir.join("outside.txt").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+//-------------------[Test helpers]-------------------//
+
+/// An output that can still be read after it is given to the evaluator
+#[derive(Clone, Default)]
+struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+impl SharedOutput {
+    fn text(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+impl io::Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn test_null_object(evaluated: ObjectType) {
+    match evaluated {
+        ObjectType::Null => (),
+        actual => panic!("Expected an null, got {:?}", actual),
+    };
+}
+
+fn test_integer_object(evaluated: ObjectType, expected: i128) {
+    let eval = match evaluated {
+        ObjectType::Integer(exp) => exp,
+        actual => panic!("Expected an integer, got {:?}", actual),
+    };
+    assert_eq!(eval.value, expected)
+}
+
+fn test_boolean_object(evaluated: ObjectType, expected: bool) {
+    let eval = match evaluated {
+        ObjectType::Boolean(exp) => exp,
+        actual => panic!("Expected an boolean, got {:?}", actual),
+    };
+    assert_eq!(eval.value, expected)
+}
+
+fn test_string_object(evaluated: ObjectType, expected: String) {
+    let eval = match evaluated {
+        ObjectType::String(exp) => exp,
+        actual => panic!("Expected a string, got {:?}", actual),
+    };
+    assert_eq!(&*eval.value, expected)
+}
+
+fn test_error_object(evaluated: ObjectType, expected: &str) {
+    let eval = match evaluated {
+        ObjectType::Error(exp) => exp,
+        actual => panic!("Expected an error, got {:?}", actual),
+    };
+    assert_eq!(eval.message, expected)
+}
+
+/// The backends every test runs on
+const BACKENDS: [Backend; 2] = [Backend::Ast, Backend::Vm];
+
+/// Parses and resolves a program, leaving the undefined variables to fail when the program runs
+fn parse(input: &str) -> AstNode {
+    let mut par = Parser::new(Lexer::new(input.to_string()));
+    let mut pro = par.parse_program();
+    if let AstNode::Program(program) = &mut pro {
+        Resolver::new(|_| true).resolve(program);
+    }
+    pro
+}
+
+/// Evaluates a program with every backend, with and without optimizing it, and checks that the results are the same
+fn test_eval(input: &str) -> ObjectType {
+    let pro = parse(input);
+    let mut optimized = pro.clone();
+    if let AstNode::Program(program) = &mut optimized {
+        optimizer::optimize(program);
+    }
+
+    let runs = BACKENDS
+        .iter()
+        .flat_map(|backend| [(*backend, &pro), (*backend, &optimized)]);
+    let mut results = runs.clone().map(|(backend, pro)| {
+        let mut evaluator = Evaluator::new_env(Environment::new());
+        evaluator.set_backend(backend);
+        evaluator.eval(pro)
+    });
+
+    let expected = results.next().unwrap();
+    for (result, (backend, pro)) in results.zip(runs.skip(1)) {
+        assert_eq!(expected, result, "{:?}: {}", backend, pro.string());
+    }
+    expected
 }
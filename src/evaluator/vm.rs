@@ -0,0 +1,845 @@
+use crate::ast::statements::Operation;
+use crate::ast::Program;
+use crate::compiler::code::{Chunk, Instruction, Operator, Place};
+use crate::compiler::Compiler;
+
+use super::*;
+
+/// A place to continue at when the code inside it unwinds
+struct Handler {
+    /// the position of the instruction to continue at
+    target: usize,
+    /// the height of the stack when the handler was installed
+    height: usize,
+    /// the number of chunks running when the handler was installed, it handles the unwinding of the last one
+    depth: usize,
+}
+
+/// The global variables of the caller of a function, restored when the call returns with the
+/// environment of the caller, kept in `Evaluator::callers`
+struct Caller {
+    globals: Option<Environment>,
+}
+
+/// A chunk being run by the virtual machine, the program or a call to a compiled function
+struct CallFrame {
+    chunk: Rc<Chunk>,
+    /// the position of the next instruction, kept while the chunk calls another function
+    pc: usize,
+    /// the position of the first local slot of the chunk in `Evaluator::locals`
+    base: usize,
+    /// the height of the stack when the chunk started
+    height: usize,
+    /// the environment to restore when a function call finishes, the program has none
+    caller: Option<Caller>,
+}
+
+/// The state of a run of the virtual machine. Calls between compiled functions run in the same machine,
+/// so they don't nest calls on the native stack
+#[derive(Default)]
+pub(super) struct Machine {
+    stack: Vec<ObjectType>,
+    handlers: Vec<Handler>,
+    calls: Vec<CallFrame>,
+    /// the values of the global variables shadowed by the parameters of the running catch blocks
+    shadowed: Vec<Option<ObjectType>>,
+}
+
+/// What the machine does after an instruction
+enum Flow {
+    /// runs the next instruction
+    Next,
+    /// unwinds with the value to the innermost handler of the running chunk, or finishes the chunk with it
+    Unwind(ObjectType),
+    /// finishes the running chunk with the value
+    Finish(ObjectType),
+}
+
+impl Evaluator {
+    /**
+    Compiles a program to bytecode and runs it, returning the same result as `eval_program`
+
+    # Arguments
+    * `program` - the program to run
+    */
+    pub(super) fn run_program(&mut self, program: &Program) -> ObjectType {
+        self.start_program();
+
+        let mut machine = Machine::default();
+        machine.calls.push(CallFrame {
+            chunk: Rc::new(Compiler::compile_program(program)),
+            pc: 0,
+            base: 0,
+            height: 0,
+            caller: None,
+        });
+        unwrap_return_value(self.run(&mut machine))
+    }
+
+    /**
+    Calls a function created by the virtual machine and returns the result, like `apply_function`.
+    The call runs in a machine of its own, which is kept to run the next call once it finishes
+
+    # Arguments
+    * `function` - the function to call, with its bytecode
+    * `args` - the arguments to pass to the function
+    * `span` - the position of the call in the source code
+    */
+    pub(super) fn run_function(
+        &mut self,
+        function: Function,
+        args: Vec<ObjectType>,
+        span: Span,
+    ) -> ObjectType {
+        let mut machine = self.machines.pop().unwrap_or_default();
+        let argc = args.len();
+        machine.stack.push(ObjectType::Function(function));
+        machine.stack.extend(args);
+
+        let result = match self.call(&mut machine, argc, span) {
+            Some(result) => result,
+            None => self.run(&mut machine),
+        };
+        self.machines.push(machine);
+        result
+    }
+
+    /**
+    Starts a call to the compiled function below the arguments on top of the stack, mirroring
+    `apply_function`, and removes them from the stack. Returns the result if the call fails before
+    running, otherwise the chunk of the function becomes the running chunk
+
+    # Arguments
+    * `machine` - the machine that runs the call
+    * `argc` - the number of arguments
+    * `span` - the position of the call in the source code
+    */
+    fn call(&mut self, machine: &mut Machine, argc: usize, span: Span) -> Option<ObjectType> {
+        let at = machine.stack.len() - argc - 1;
+        let function = match std::mem::replace(&mut machine.stack[at], ObjectType::Null) {
+            ObjectType::Function(function) => function,
+            other => panic!("not a compiled function: {:?}", other),
+        };
+
+        if self.frames.len() >= self.max_depth {
+            machine.stack.truncate(at);
+            return Some(self.attach_trace(new_error(
+                STACK_OVERFLOW_ERROR,
+                format!(
+                    "stack overflow: maximum call depth of {} exceeded",
+                    self.max_depth
+                ),
+            )));
+        }
+        self.frames.push(Frame {
+            function: function
+                .name
+                .clone()
+                .unwrap_or(ANONYMOUS_FUNCTION.to_string()),
+            span,
+        });
+        if argc != function.parameters.len() {
+            machine.stack.truncate(at);
+            let error = self.attach_trace(wrong_arguments(argc, function.parameters.len()));
+            self.frames.pop();
+            return Some(error);
+        }
+
+        let caller = Caller {
+            globals: self.globals.clone(),
+        };
+        let env = self.env.clone();
+        if self.frames.len() == 1 {
+            self.globals = Some(env.clone());
+        }
+        let base = self.locals.len();
+        let chunk = self.bind_arguments(machine, function, at, &env);
+        self.callers.push(env);
+        machine.calls.push(CallFrame {
+            chunk,
+            pc: 0,
+            base,
+            height: at,
+            caller: Some(caller),
+        });
+        None
+    }
+
+    /**
+    Moves the arguments on top of the stack, from the position after the function, to the locals
+    of a call, and sets the active environment to the one of the function. Functions that were not
+    resolved get their arguments by name instead. Returns the chunk of the function
+
+    # Arguments
+    * `machine` - the machine that runs the call
+    * `function` - the called function
+    * `at` - the position of the function in the stack, the stack is truncated to it
+    * `caller` - the environment of the caller
+    */
+    fn bind_arguments(
+        &mut self,
+        machine: &mut Machine,
+        mut function: Function,
+        at: usize,
+        caller: &Environment,
+    ) -> Rc<Chunk> {
+        let chunk = function
+            .chunk
+            .clone()
+            .expect("the function has no bytecode");
+        let base = self.locals.len();
+
+        match function.scope {
+            Some(scope) => {
+                self.locals.extend(machine.stack.drain(at + 1..).map(Some));
+                self.locals.resize(base + chunk.locals.len(), None);
+                if let Some(slot) = scope.recursive {
+                    self.locals[base + slot] = Some(ObjectType::Function(function.clone()));
+                }
+                self.env = function.env;
+            }
+            None => {
+                let args = machine.stack.split_off(at + 1);
+                self.locals.resize(base + chunk.locals.len(), None);
+                function.env.update(caller);
+                self.env = self.extended_function_env(&function, args);
+            }
+        }
+        machine.stack.truncate(at);
+        chunk
+    }
+
+    /**
+    Finishes the running chunk with a value and returns its result. The result of a function call is
+    the value unwrapped from a return value, with the stack trace of the call if it is an error
+
+    # Arguments
+    * `machine` - the machine that runs the chunk
+    * `value` - the value the chunk finishes with
+    */
+    fn finish_call(&mut self, machine: &mut Machine, value: ObjectType) -> ObjectType {
+        let frame = machine
+            .calls
+            .pop()
+            .expect("the virtual machine is not running a chunk");
+        self.locals.truncate(frame.base);
+        machine.stack.truncate(frame.height);
+        while machine
+            .handlers
+            .last()
+            .is_some_and(|handler| handler.depth > machine.calls.len())
+        {
+            machine.handlers.pop();
+        }
+
+        match frame.caller {
+            Some(caller) => {
+                self.env = self.callers.pop().expect("the caller is missing");
+                self.globals = caller.globals;
+                let result = self.attach_trace(unwrap_return_value(value));
+                self.frames.pop();
+                result
+            }
+            None => value,
+        }
+    }
+
+    /**
+    Runs the chunks of a machine until the first one finishes and returns the value it finishes with.
+    Values that unwind go to the innermost handler of their chunk, or finish the chunk if there is none,
+    like errors and return values leave the nodes of the tree walker. Once the program is stopped the
+    handlers are skipped, so the error can't be caught
+
+    # Arguments
+    * `machine` - the machine to run
+    */
+    fn run(&mut self, machine: &mut Machine) -> ObjectType {
+        let frame = machine
+            .calls
+            .last()
+            .expect("the virtual machine is not running a chunk");
+        let mut chunk = frame.chunk.clone();
+        let mut pc = frame.pc;
+        let mut base = frame.base;
+
+        loop {
+            let instruction = chunk.instructions[pc];
+            pc += 1;
+
+            let mut flow = match instruction {
+                Instruction::Constant(index) => {
+                    machine.stack.push(chunk.constants[index as usize].clone());
+                    continue;
+                }
+                Instruction::Null => {
+                    machine.stack.push(ObjectType::Null);
+                    continue;
+                }
+                Instruction::Break => {
+                    machine.stack.push(ObjectType::Break);
+                    continue;
+                }
+                Instruction::Pop => {
+                    pop(&mut machine.stack);
+                    continue;
+                }
+                Instruction::Step => match self.step() {
+                    Some(error) => Flow::Unwind(error),
+                    None => continue,
+                },
+                Instruction::GetVariable(Place::Local(slot)) => {
+                    match &self.locals[base + slot as usize] {
+                        Some(value) => {
+                            machine.stack.push(value.clone());
+                            continue;
+                        }
+                        None => Flow::Unwind(not_found(&chunk.locals[slot as usize])),
+                    }
+                }
+                Instruction::GetVariable(place) => {
+                    let value = match place {
+                        Place::Global(index) => self.resolve(chunk.name(index)),
+                        place => match self.read(base, place) {
+                            Some(value) => value,
+                            None => not_found(chunk.variable(place)),
+                        },
+                    };
+                    push(&mut machine.stack, value)
+                }
+                Instruction::Let(place) => {
+                    let value = name_function(pop(&mut machine.stack), chunk.variable(place));
+                    self.write(base, &chunk, place, value.clone());
+                    machine.stack.push(value);
+                    continue;
+                }
+                Instruction::Assign(place, operation) => {
+                    let value = pop(&mut machine.stack);
+                    let updated = match (&value, arithmetic_operator(operation)) {
+                        (ObjectType::Integer(right), Some(operator)) => {
+                            self.update_integer(base, &chunk, place, operator, right.value)
+                        }
+                        _ => None,
+                    };
+                    match updated {
+                        Some(value) => {
+                            machine.stack.push(value);
+                            continue;
+                        }
+                        None => {
+                            let current = self.current(base, &chunk, place);
+                            let value = self.assigned_value(
+                                chunk.variable(place),
+                                current,
+                                operation,
+                                value,
+                            );
+                            if !is_unwinding(&value) {
+                                self.write(base, &chunk, place, value.clone());
+                            }
+                            push(&mut machine.stack, value)
+                        }
+                    }
+                }
+                Instruction::Increment(place) | Instruction::Decrement(place) => {
+                    let delta = match instruction {
+                        Instruction::Increment(_) => 1,
+                        _ => -1,
+                    };
+                    match self.update_integer(base, &chunk, place, Operator::Add, delta) {
+                        Some(value) => {
+                            machine.stack.push(value);
+                            continue;
+                        }
+                        None => {
+                            let current = self.current(base, &chunk, place);
+                            let value =
+                                self.post_operation_value(chunk.variable(place), current, delta);
+                            if !is_unwinding(&value) {
+                                self.write(base, &chunk, place, value.clone());
+                            }
+                            push(&mut machine.stack, value)
+                        }
+                    }
+                }
+                Instruction::Prefix(operator) => {
+                    let right = pop(&mut machine.stack);
+                    let value = self.eval_prefix_expression(chunk.name(operator), right);
+                    push(&mut machine.stack, value)
+                }
+                Instruction::Infix(operator) => {
+                    let right = pop(&mut machine.stack);
+                    let top = machine
+                        .stack
+                        .last_mut()
+                        .expect("the stack of the virtual machine is empty");
+                    let result = match (&*top, &right) {
+                        (ObjectType::Integer(left), ObjectType::Integer(right)) => {
+                            integer_operation(left.value, operator, right.value)
+                        }
+                        _ => None,
+                    };
+                    match result {
+                        Some(result) => {
+                            *top = result;
+                            continue;
+                        }
+                        None => {
+                            let left = pop(&mut machine.stack);
+                            let value =
+                                self.eval_infix_expression(left, chunk.operator(operator), right);
+                            push(&mut machine.stack, value)
+                        }
+                    }
+                }
+                Instruction::Array(len) => {
+                    let elements = machine.stack.split_off(machine.stack.len() - len as usize);
+                    let value = self.allocate(ObjectType::Array(Array {
+                        elements: Rc::new(elements),
+                    }));
+                    push(&mut machine.stack, value)
+                }
+                Instruction::Tuple(len) => {
+                    let elements = machine.stack.split_off(machine.stack.len() - len as usize);
+                    let value = self.allocate(ObjectType::Tuple(Tuple {
+                        elements: elements.into(),
+                    }));
+                    push(&mut machine.stack, value)
+                }
+                Instruction::Set(len) => {
+                    let elements = machine.stack.split_off(machine.stack.len() - len as usize);
+                    let value = self.allocate(ObjectType::Set(Set {
+                        elements: Rc::new(elements.into_iter().collect()),
+                    }));
+                    push(&mut machine.stack, value)
+                }
+                Instruction::Dict(len) => {
+                    let mut elements = machine
+                        .stack
+                        .split_off(machine.stack.len() - 2 * len as usize)
+                        .into_iter();
+                    let mut pairs = IndexMap::new();
+                    while let (Some(key), Some(value)) = (elements.next(), elements.next()) {
+                        pairs.insert(key, value);
+                    }
+                    let value = self.allocate(ObjectType::Dict(Dict {
+                        pairs: Rc::new(pairs),
+                    }));
+                    push(&mut machine.stack, value)
+                }
+                Instruction::CheckKey => match unusable_key_error(top(&machine.stack)) {
+                    Some(error) => Flow::Unwind(error),
+                    None => continue,
+                },
+                Instruction::Index => {
+                    let index = pop(&mut machine.stack);
+                    let left = pop(&mut machine.stack);
+                    let value = self.eval_index_expression(left, index);
+                    push(&mut machine.stack, value)
+                }
+                Instruction::Propagate => {
+                    let value = eval_propagation(pop(&mut machine.stack));
+                    push(&mut machine.stack, value)
+                }
+                Instruction::Throw => Flow::Unwind(thrown_error(pop(&mut machine.stack))),
+                Instruction::Return => {
+                    let value = pop(&mut machine.stack);
+                    // the return value only has to be wrapped if a handler of the chunk can see it
+                    if has_handler(machine) {
+                        Flow::Unwind(ObjectType::Return(ReturnValue {
+                            value: Box::new(value),
+                        }))
+                    } else {
+                        Flow::Finish(value)
+                    }
+                }
+                Instruction::Closure(index) => {
+                    let prototype = &chunk.functions[index as usize];
+                    let env = if prototype.chunk.upvalues.is_empty() {
+                        self.env.clone()
+                    } else {
+                        let captured = prototype
+                            .chunk
+                            .upvalues
+                            .iter()
+                            .map(|upvalue| self.read(base, upvalue.source))
+                            .collect();
+                        self.env.with_slots(captured)
+                    };
+                    let literal = &prototype.literal;
+                    let value = self.allocate(ObjectType::Function(Function {
+                        name: None,
+                        parameters: literal.parameters.clone(),
+                        body: literal.body.clone(),
+                        scope: literal.scope,
+                        env,
+                        chunk: Some(prototype.chunk.clone()),
+                    }));
+                    push(&mut machine.stack, value)
+                }
+                Instruction::Call { argc, span } => {
+                    let span = chunk.spans[span as usize];
+                    let at = machine.stack.len() - argc as usize - 1;
+                    if is_compiled(&machine.stack[at]) {
+                        if let Some(frame) = machine.calls.last_mut() {
+                            frame.pc = pc;
+                        }
+                        match self.call(machine, argc as usize, span) {
+                            Some(result) => push(&mut machine.stack, result),
+                            None => {
+                                let frame = machine.calls.last().expect("the call is missing");
+                                chunk = frame.chunk.clone();
+                                pc = 0;
+                                base = frame.base;
+                                continue;
+                            }
+                        }
+                    } else {
+                        let args = machine.stack.split_off(at + 1);
+                        let function = pop(&mut machine.stack);
+                        let result = self.apply_function(function, args, span);
+                        push(&mut machine.stack, result)
+                    }
+                }
+                Instruction::TailCall { argc, span } => {
+                    let span = chunk.spans[span as usize];
+                    let at = machine.stack.len() - argc as usize - 1;
+                    match &machine.stack[at] {
+                        ObjectType::Function(next) if next.chunk.is_some() => {
+                            if let Some(frame) = self.frames.last_mut() {
+                                frame.function =
+                                    next.name.clone().unwrap_or(ANONYMOUS_FUNCTION.to_string());
+                                frame.span = span;
+                            }
+                            if argc as usize != next.parameters.len() {
+                                Flow::Finish(wrong_arguments(argc as usize, next.parameters.len()))
+                            } else {
+                                let next = match pop_at(&mut machine.stack, at) {
+                                    ObjectType::Function(next) => next,
+                                    _ => unreachable!(),
+                                };
+                                let frame = machine.calls.pop().expect("the call is missing");
+                                self.locals.truncate(frame.base);
+                                let caller = match (&frame.caller, self.callers.last()) {
+                                    (Some(_), Some(caller)) => caller.clone(),
+                                    _ => self.env.clone(),
+                                };
+                                chunk = self.bind_arguments(machine, next, at, &caller);
+                                machine.stack.truncate(frame.height);
+                                machine.calls.push(CallFrame {
+                                    chunk: chunk.clone(),
+                                    ..frame
+                                });
+                                pc = 0;
+                                continue;
+                            }
+                        }
+                        _ => {
+                            let args = machine.stack.split_off(at + 1);
+                            let function = pop(&mut machine.stack);
+                            Flow::Finish(self.apply_function(function, args, span))
+                        }
+                    }
+                }
+                Instruction::Jump(target) => {
+                    pc = target as usize;
+                    continue;
+                }
+                Instruction::JumpIfFalse(target) => {
+                    if !is_truthy(&pop(&mut machine.stack)) {
+                        pc = target as usize;
+                    }
+                    continue;
+                }
+                Instruction::JumpIfTrue(target) => {
+                    if is_truthy(&pop(&mut machine.stack)) {
+                        pc = target as usize;
+                    }
+                    continue;
+                }
+                Instruction::Unwind if is_unwinding(top(&machine.stack)) => {
+                    Flow::Unwind(pop(&mut machine.stack))
+                }
+                Instruction::UnwindStatement
+                    if matches!(
+                        top(&machine.stack),
+                        ObjectType::Break | ObjectType::Return(_) | ObjectType::Error(_)
+                    ) =>
+                {
+                    Flow::Unwind(pop(&mut machine.stack))
+                }
+                Instruction::Unwind | Instruction::UnwindStatement => continue,
+                Instruction::PushHandler(target) => {
+                    machine.handlers.push(Handler {
+                        target: target as usize,
+                        height: machine.stack.len(),
+                        depth: machine.calls.len(),
+                    });
+                    continue;
+                }
+                Instruction::PopHandler => {
+                    machine.handlers.pop();
+                    continue;
+                }
+                Instruction::BreakLoop(target) => match pop(&mut machine.stack) {
+                    ObjectType::Break => {
+                        machine.stack.push(ObjectType::Null);
+                        pc = target as usize;
+                        continue;
+                    }
+                    other => Flow::Unwind(other),
+                },
+                Instruction::Catch { variable, end } => {
+                    if let ObjectType::Error(_) = top(&machine.stack) {
+                        if let ObjectType::Error(error) = self.attach_trace(pop(&mut machine.stack))
+                        {
+                            if let Place::Global(index) = variable {
+                                machine.shadowed.push(self.env.remove(chunk.name(index)));
+                            }
+                            self.write(base, &chunk, variable, error_object(&error));
+                        }
+                    } else {
+                        pc = end as usize;
+                    }
+                    continue;
+                }
+                Instruction::EndCatch(place) => {
+                    if let Place::Global(index) = place {
+                        let name = chunk.name(index);
+                        self.env.remove(name);
+                        if let Some(Some(shadowed)) = machine.shadowed.pop() {
+                            self.env.set(name, shadowed);
+                        }
+                    }
+                    continue;
+                }
+                Instruction::Finish => Flow::Finish(pop(&mut machine.stack)),
+            };
+
+            loop {
+                match flow {
+                    Flow::Next => break,
+                    Flow::Unwind(value) => {
+                        if self.stopped.is_none() && has_handler(machine) {
+                            let handler = machine.handlers.pop().expect("the handler is missing");
+                            machine.stack.truncate(handler.height);
+                            machine.stack.push(value);
+                            pc = handler.target;
+                            break;
+                        }
+                        flow = Flow::Finish(value);
+                    }
+                    Flow::Finish(value) => {
+                        let result = self.finish_call(machine, value);
+                        let Some(frame) = machine.calls.last() else {
+                            return result;
+                        };
+                        chunk = frame.chunk.clone();
+                        pc = frame.pc;
+                        base = frame.base;
+                        flow = push(&mut machine.stack, result);
+                    }
+                }
+            }
+        }
+    }
+
+    /**
+    Applies an arithmetic operator in place to an integer variable of the running chunk, a local or
+    a variable kept by name in the active `Environment`, and returns its new value. Returns `None` if
+    the variable is not one of those or if the result needs the evaluator, see `arithmetic`
+
+    # Arguments
+    * `base` - the position of the first local slot of the chunk
+    * `chunk` - the running chunk
+    * `place` - the place of the variable
+    * `operator` - the operator to apply
+    * `right` - the integer on the right of the operator
+    */
+    fn update_integer(
+        &mut self,
+        base: usize,
+        chunk: &Chunk,
+        place: Place,
+        operator: Operator,
+        right: i128,
+    ) -> Option<ObjectType> {
+        let variable = match place {
+            Place::Local(slot) => self.locals[base + slot as usize].as_mut(),
+            Place::Global(index) => self.env.get_mut(chunk.name(index)),
+            Place::Upvalue(_) => None,
+        };
+        let Some(ObjectType::Integer(integer)) = variable else {
+            return None;
+        };
+        integer.value = arithmetic(integer.value, operator, right)?;
+        Some(ObjectType::Integer(Integer {
+            value: integer.value,
+        }))
+    }
+
+    /**
+    Returns the value of a local or an upvalue of the running chunk, if its variable is bound
+
+    # Arguments
+    * `base` - the position of the first local slot of the chunk
+    * `place` - the place of the variable
+    */
+    fn read(&self, base: usize, place: Place) -> Option<ObjectType> {
+        match place {
+            Place::Local(slot) => self.locals[base + slot as usize].clone(),
+            Place::Upvalue(index) => self.env.get_slot(0, index as usize).cloned(),
+            Place::Global(_) => None,
+        }
+    }
+
+    /**
+    Returns the value of a variable of the running chunk to be updated, like `current_value`
+
+    # Arguments
+    * `base` - the position of the first local slot of the chunk
+    * `chunk` - the running chunk
+    * `place` - the place of the variable
+    */
+    fn current(&self, base: usize, chunk: &Chunk, place: Place) -> Option<ObjectType> {
+        match place {
+            Place::Global(index) => self.variable(chunk.name(index)).cloned(),
+            place => self.read(base, place),
+        }
+    }
+
+    /**
+    Stores the value of a variable of the running chunk. Upvalues are copied on write, so the
+    closure and the other calls to it keep the captured value
+
+    # Arguments
+    * `base` - the position of the first local slot of the chunk
+    * `chunk` - the running chunk
+    * `place` - the place of the variable
+    * `value` - the value to store
+    */
+    fn write(&mut self, base: usize, chunk: &Chunk, place: Place, value: ObjectType) {
+        match place {
+            Place::Global(index) => self.env.set(chunk.name(index), value),
+            Place::Local(slot) => self.locals[base + slot as usize] = Some(value),
+            Place::Upvalue(index) => self.env.set_slot(0, index as usize, value),
+        }
+    }
+}
+
+/**
+Pushes a value, or starts unwinding with it if it is an error or a return value
+
+# Arguments
+* `stack` - the stack of the machine
+* `value` - the value to push
+*/
+fn push(stack: &mut Vec<ObjectType>, value: ObjectType) -> Flow {
+    if is_unwinding(&value) {
+        Flow::Unwind(value)
+    } else {
+        stack.push(value);
+        Flow::Next
+    }
+}
+
+/**
+Returns the result of an arithmetic operator on two integers, or `None` if it has to be evaluated by
+`Evaluator::eval_infix_expression`: when the result doesn't fit in an integer, on a division by zero
+or for the other operators
+
+# Arguments
+* `left` - the integer on the left
+* `operator` - the operator
+* `right` - the integer on the right
+*/
+fn arithmetic(left: i128, operator: Operator, right: i128) -> Option<i128> {
+    match operator {
+        Operator::Add => left.checked_add(right),
+        Operator::Subtract => left.checked_sub(right),
+        Operator::Multiply => left.checked_mul(right),
+        Operator::Divide => left.checked_div(right),
+        Operator::Modulo => left.checked_rem(right),
+        _ => None,
+    }
+}
+
+/**
+Returns the result of an infix operator on two integers, or `None` if it has to be evaluated by
+`Evaluator::eval_infix_expression`, see `arithmetic`
+
+# Arguments
+* `left` - the integer on the left
+* `operator` - the operator
+* `right` - the integer on the right
+*/
+fn integer_operation(left: i128, operator: Operator, right: i128) -> Option<ObjectType> {
+    let value = match operator {
+        Operator::Equal => left == right,
+        Operator::NotEqual => left != right,
+        Operator::Less => left < right,
+        Operator::Greater => left > right,
+        Operator::LessEqual => left <= right,
+        Operator::GreaterEqual => left >= right,
+        operator => {
+            return arithmetic(left, operator, right)
+                .map(|value| ObjectType::Integer(Integer { value }))
+        }
+    };
+
+    Some(ObjectType::Boolean(Boolean { value }))
+}
+
+/**
+Returns the arithmetic operator of a compound assignment, if it is one
+
+# Arguments
+* `operation` - the kind of assignment
+*/
+fn arithmetic_operator(operation: Operation) -> Option<Operator> {
+    match operation {
+        Operation::Sum => Some(Operator::Add),
+        Operation::Minus => Some(Operator::Subtract),
+        Operation::Mult => Some(Operator::Multiply),
+        Operation::Divide => Some(Operator::Divide),
+        Operation::Assig => None,
+    }
+}
+
+/**
+Returns if the innermost handler belongs to the running chunk
+
+# Arguments
+* `machine` - the machine that runs the chunk
+*/
+fn has_handler(machine: &Machine) -> bool {
+    machine
+        .handlers
+        .last()
+        .is_some_and(|handler| handler.depth == machine.calls.len())
+}
+
+/**
+Returns if a value is a function created by the virtual machine, which runs its calls itself
+
+# Arguments
+* `value` - the value to check
+*/
+fn is_compiled(value: &ObjectType) -> bool {
+    matches!(value, ObjectType::Function(function) if function.chunk.is_some())
+}
+
+fn pop(stack: &mut Vec<ObjectType>) -> ObjectType {
+    stack
+        .pop()
+        .expect("the stack of the virtual machine is empty")
+}
+
+fn pop_at(stack: &mut [ObjectType], at: usize) -> ObjectType {
+    std::mem::replace(&mut stack[at], ObjectType::Null)
+}
+
+fn top(stack: &[ObjectType]) -> &ObjectType {
+    stack
+        .last()
+        .expect("the stack of the virtual machine is empty")
+}
@@ -1,14 +1,17 @@
-use crate::token::Token;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::token::{Span, Token};
 #[cfg(test)]
 mod test;
 
 trait Letter {
-    fn is_letter(self) -> bool;
+    fn is_letter(&self) -> bool;
 }
 
 impl Letter for u8 {
-    fn is_letter(self) -> bool {
-        self.is_ascii_alphabetic() || self == b'_'
+    fn is_letter(&self) -> bool {
+        self.is_ascii_alphabetic() || *self == b'_'
     }
 }
 
@@ -23,6 +26,14 @@ pub struct Lexer {
     read_position: usize,
     /// The actual character
     ch: u8,
+    /// The line of the actual character
+    line: usize,
+    /// The column of the actual character
+    column: usize,
+    /// The position of the first character of the last token read
+    token_span: Span,
+    /// The identifiers and strings read, so that the tokens with the same text share it
+    interned: HashSet<Rc<str>>,
 }
 
 impl Lexer {
@@ -45,6 +56,10 @@ impl Lexer {
             position: 0,
             read_position: 0,
             ch: 0,
+            line: 1,
+            column: 0,
+            token_span: Span::default(),
+            interned: HashSet::new(),
         };
 
         lexer.read_char();
@@ -61,6 +76,13 @@ impl Lexer {
 
     */
     fn read_char(&mut self) {
+        if self.ch == b'\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
         if self.read_position >= self.input.len() {
             self.ch = 0;
         } else {
@@ -92,6 +114,11 @@ impl Lexer {
             self.jump_comment();
         }
 
+        self.token_span = Span {
+            line: self.line,
+            column: self.column,
+        };
+
         let token: Token = match self.ch {
             b'+' => {
                 if self.peek_char() == b'=' {
@@ -169,13 +196,25 @@ impl Lexer {
                 }
             }
 
-            b'"' => Token::ConstStr(self.read_string()),
+            b'"' => {
+                let string = self.read_string();
+                Token::ConstStr(self.intern(string))
+            }
             b',' => Token::Coma,
             b';' => Token::Semicolon,
             b':' => Token::Colon,
+            b'?' => Token::Question,
             b'(' => Token::Opar,
             b')' => Token::Cpar,
             b'{' => Token::Okey,
+            b'#' => {
+                if self.peek_char() == b'{' {
+                    self.read_char();
+                    Token::Oset
+                } else {
+                    Token::Invalid("#".to_string())
+                }
+            }
             b'}' => Token::Ckey,
             b'[' => Token::Obrac,
             b']' => Token::Cbrac,
@@ -199,14 +238,25 @@ impl Lexer {
                         "arr" => Token::Arr,
                         "and" => Token::And,
                         "or" => Token::Or,
-                        "not" => Token::Not,
+                        "not" => {
+                            if self.next_word_is("in") {
+                                Token::NotIn
+                            } else {
+                                Token::Not
+                            }
+                        }
+                        "in" => Token::In,
                         "for" => Token::For,
                         "while" => Token::While,
                         "loop" => Token::Loop,
-                        _ => Token::Id(id),
+                        "try" => Token::Try,
+                        "catch" => Token::Catch,
+                        "finally" => Token::Finally,
+                        "throw" => Token::Throw,
+                        _ => Token::Id(self.intern(id)),
                     };
                 } else if ch.is_ascii_digit() {
-                    return Token::ConstInt(self.read_number());
+                    return self.read_number();
                 } else {
                     Token::Invalid((ch as char).to_string())
                 }
@@ -217,6 +267,38 @@ impl Lexer {
         token
     }
 
+    /**
+    Returns the position of the first character of the last `Token` returned by `next_token`
+
+    # Arguments
+
+    no arguments
+
+    # Examples
+    ```
+    let new_token = lexer.next_token();
+    let span = lexer.token_span();
+    ```
+    */
+    pub fn token_span(&self) -> Span {
+        self.token_span
+    }
+
+    /**
+    Returns the shared copy of a text, adding it if it was not read before
+
+    # Arguments
+    * `text` - the identifier or string read
+    */
+    fn intern(&mut self, text: String) -> Rc<str> {
+        if let Some(interned) = self.interned.get(text.as_str()) {
+            return interned.clone();
+        }
+        let interned: Rc<str> = text.into();
+        self.interned.insert(interned.clone());
+        interned
+    }
+
     /**
     When a word is detected the function will read the word and return a `String` with the content
 
@@ -240,7 +322,8 @@ impl Lexer {
     }
 
     /**
-    When a digit is detected the function will read the number and return a `i128` with the content
+    When a digit is detected the function will read the number and return a `Token::ConstInt` with
+    the content, or a `Token::ConstBigInt` if it doesn't fit in an `i128`
 
     # Arguments
 
@@ -251,17 +334,22 @@ impl Lexer {
     }
     ```
     */
-    fn read_number(&mut self) -> i128 {
+    fn read_number(&mut self) -> Token {
         let position = self.position;
 
         while self.ch.is_ascii_digit() {
             self.read_char();
         }
 
-        String::from_utf8_lossy(&self.input[position..self.position])
-            .to_string()
-            .parse::<i128>()
-            .expect("Not a  valid number, numbers must be i128")
+        let digits = String::from_utf8_lossy(&self.input[position..self.position]).to_string();
+        match digits.parse::<i128>() {
+            Ok(value) => Token::ConstInt(value),
+            Err(_) => Token::ConstBigInt(Rc::new(
+                digits
+                    .parse()
+                    .expect("a sequence of digits is a valid number"),
+            )),
+        }
     }
 
     /**
@@ -276,7 +364,6 @@ impl Lexer {
     }
     ```
     */
-
     fn read_string(&mut self) -> String {
         self.read_char();
         let position = self.position;
@@ -301,6 +388,39 @@ impl Lexer {
         }
     }
 
+    /**
+    Returns if the next word after the white space is the one given. If it is, the pointer
+    moves to the character after the word, so `not in` is read as a single token
+
+    # Arguments
+
+    * `word` - the word expected
+    ```
+    if self.next_word_is("in") {
+        Token::NotIn
+    }
+    ```
+    */
+    fn next_word_is(&mut self, word: &str) -> bool {
+        let rest = self.input.get(self.position..).unwrap_or_default();
+        let start = match rest.iter().position(|ch| !ch.is_ascii_whitespace()) {
+            Some(start) => start,
+            None => return false,
+        };
+
+        let found = rest[start..].starts_with(word.as_bytes())
+            && !rest
+                .get(start + word.len())
+                .is_some_and(|ch| ch.is_letter() || ch.is_ascii_digit());
+        if found {
+            self.skip_whitespace();
+            for _ in 0..word.len() {
+                self.read_char();
+            }
+        }
+        found
+    }
+
     /**
     Returns the character pointed by `self.read_position` in u8 form
     If the 'self.read_position' is out of bounds, the function returns the null character
@@ -43,28 +43,28 @@ fn test_source_code_first_subset() {
 
     let expected = vec![
         Token::Let,
-        Token::Id("five".to_string()),
+        Token::Id("five".into()),
         Token::Colon,
         Token::Int,
         Token::Assig,
         Token::ConstInt(5),
         Token::Semicolon,
         Token::Let,
-        Token::Id("ten".to_string()),
+        Token::Id("ten".into()),
         Token::Colon,
         Token::Int,
         Token::Assig,
         Token::ConstInt(10),
         Token::Semicolon,
         Token::Let,
-        Token::Id("hello".to_string()),
+        Token::Id("hello".into()),
         Token::Colon,
         Token::Str,
         Token::Assig,
-        Token::ConstStr("hello world".to_string()),
+        Token::ConstStr("hello world".into()),
         Token::Semicolon,
         Token::Let,
-        Token::Id("array".to_string()),
+        Token::Id("array".into()),
         Token::Colon,
         Token::Arr,
         Token::Assig,
@@ -75,37 +75,37 @@ fn test_source_code_first_subset() {
         Token::Cbrac,
         Token::Semicolon,
         Token::Let,
-        Token::Id("my_bool".to_string()),
+        Token::Id("my_bool".into()),
         Token::Colon,
         Token::Bool,
         Token::Assig,
         Token::ConstBool(true),
         Token::Semicolon,
         Token::Let,
-        Token::Id("add".to_string()),
+        Token::Id("add".into()),
         Token::Assig,
         Token::Fun,
         Token::Opar,
-        Token::Id("x".to_string()),
+        Token::Id("x".into()),
         Token::Coma,
-        Token::Id("y".to_string()),
+        Token::Id("y".into()),
         Token::Cpar,
         Token::Arrow,
         Token::Int,
         Token::Okey,
-        Token::Id("x".to_string()),
+        Token::Id("x".into()),
         Token::Plus,
-        Token::Id("y".to_string()),
+        Token::Id("y".into()),
         Token::Semicolon,
         Token::Ckey,
         Token::Let,
-        Token::Id("result".to_string()),
+        Token::Id("result".into()),
         Token::Assig,
-        Token::Id("add".to_string()),
+        Token::Id("add".into()),
         Token::Opar,
-        Token::Id("five".to_string()),
+        Token::Id("five".into()),
         Token::Coma,
-        Token::Id("ten".to_string()),
+        Token::Id("ten".into()),
         Token::Cpar,
         Token::Semicolon,
         Token::Eof,
@@ -122,7 +122,7 @@ fn test_source_code_first_subset() {
 
 #[test]
 fn test_one_char_operands() {
-    let input = "-/*5:;
+    let input = "-/*5:;?
 5 < 10 > 5;
 ";
 
@@ -133,6 +133,7 @@ fn test_one_char_operands() {
         Token::ConstInt(5),
         Token::Colon,
         Token::Semicolon,
+        Token::Question,
         Token::ConstInt(5),
         Token::Lt,
         Token::ConstInt(10),
@@ -240,3 +241,147 @@ break
         assert_eq!(*token, new_token);
     }
 }
+
+#[test]
+fn test_exception_keywords() {
+    let input = "try { throw \"boom\"; } catch (e) { e } finally { 1 }";
+
+    let expected = vec![
+        Token::Try,
+        Token::Okey,
+        Token::Throw,
+        Token::ConstStr("boom".into()),
+        Token::Semicolon,
+        Token::Ckey,
+        Token::Catch,
+        Token::Opar,
+        Token::Id("e".into()),
+        Token::Cpar,
+        Token::Okey,
+        Token::Id("e".into()),
+        Token::Ckey,
+        Token::Finally,
+        Token::Okey,
+        Token::ConstInt(1),
+        Token::Ckey,
+        Token::Eof,
+    ];
+
+    let mut lexer = Lexer::new(input.to_string());
+
+    for (i, token) in expected.iter().enumerate() {
+        let new_token = lexer.next_token();
+        println!("Test {i} expected: {token}, got: {new_token}");
+        assert_eq!(*token, new_token);
+    }
+}
+
+#[test]
+fn test_membership_tokens() {
+    let input = "1 in #{1, inside} # { not in not\n in not inside not";
+
+    let expected = vec![
+        Token::ConstInt(1),
+        Token::In,
+        Token::Oset,
+        Token::ConstInt(1),
+        Token::Coma,
+        Token::Id("inside".into()),
+        Token::Ckey,
+        Token::Invalid("#".to_string()),
+        Token::Okey,
+        Token::NotIn,
+        Token::NotIn,
+        Token::Not,
+        Token::Id("inside".into()),
+        Token::Not,
+        Token::Eof,
+    ];
+
+    let mut lexer = Lexer::new(input.to_string());
+
+    for (i, token) in expected.iter().enumerate() {
+        let new_token = lexer.next_token();
+        println!("Test {i} expected: {token}, got: {new_token}");
+        assert_eq!(*token, new_token);
+    }
+}
+
+#[test]
+fn test_big_integer_literals() {
+    let input = "170141183460469231731687303715884105727 170141183460469231731687303715884105728";
+
+    let expected = vec![
+        Token::ConstInt(i128::MAX),
+        Token::ConstBigInt(Rc::new(
+            "170141183460469231731687303715884105728".parse().unwrap(),
+        )),
+        Token::Eof,
+    ];
+
+    let mut lexer = Lexer::new(input.to_string());
+
+    for token in expected {
+        assert_eq!(token, lexer.next_token());
+    }
+}
+
+#[test]
+fn test_token_spans() {
+    let input = "let x = 5;
+    add(x,
+  y)";
+
+    let expected = vec![
+        (Token::Let, 1, 1),
+        (Token::Id("x".into()), 1, 5),
+        (Token::Assig, 1, 7),
+        (Token::ConstInt(5), 1, 9),
+        (Token::Semicolon, 1, 10),
+        (Token::Id("add".into()), 2, 5),
+        (Token::Opar, 2, 8),
+        (Token::Id("x".into()), 2, 9),
+        (Token::Coma, 2, 10),
+        (Token::Id("y".into()), 3, 3),
+        (Token::Cpar, 3, 4),
+        (Token::Eof, 3, 5),
+    ];
+
+    let mut lexer = Lexer::new(input.to_string());
+
+    for (i, (token, line, column)) in expected.iter().enumerate() {
+        let new_token = lexer.next_token();
+        let span = lexer.token_span();
+        println!("Test {i} expected: {token} at {line}:{column}, got: {new_token} at {span}");
+        assert_eq!(*token, new_token);
+        assert_eq!(
+            Span {
+                line: *line,
+                column: *column
+            },
+            span
+        );
+    }
+}
+
+#[test]
+fn test_interned_names_and_strings() {
+    let input = r#"let name = "halcon"; name + "halcon" + name"#;
+
+    let mut lexer = Lexer::new(input.to_string());
+    let mut names = vec![];
+    let mut strings = vec![];
+    loop {
+        match lexer.next_token() {
+            Token::Id(name) => names.push(name),
+            Token::ConstStr(string) => strings.push(string),
+            Token::Eof => break,
+            _ => {}
+        }
+    }
+
+    assert_eq!(3, names.len());
+    assert!(names.iter().all(|name| Rc::ptr_eq(name, &names[0])));
+    assert_eq!(2, strings.len());
+    assert!(Rc::ptr_eq(&strings[0], &strings[1]));
+}
@@ -0,0 +1,14 @@
+pub mod ast;
+pub mod bigint;
+pub mod compiler;
+pub mod engine;
+pub mod evaluator;
+pub mod lexer;
+pub mod object;
+pub mod optimizer;
+pub mod parser;
+pub mod repl;
+pub mod resolver;
+pub mod token;
+
+pub use engine::{Engine, HalconError, Value};
@@ -1,31 +1,49 @@
-mod ast;
-mod evaluator;
-mod lexer;
-mod object;
-mod parser;
-mod repl;
-mod token;
-
-use std::{fs, io};
+use std::path::PathBuf;
+use std::{io, process, time::Duration};
 
 use docopt::Docopt;
-use evaluator::Evaluator;
-use lexer::Lexer;
-use parser::Parser;
+use halcon::evaluator::permissions::{PathAccess, Permissions};
+use halcon::evaluator::{Backend, Evaluator};
+use halcon::object::Object;
+use halcon::{repl, Engine, HalconError};
 use serde::Deserialize;
 
-static USAGE: &'static str = "
-Usage: halcon [<input>]
+static USAGE: &str = "
+Usage: halcon [options] [<input>]
 
     default: runns the language REPL
     Option:
         <input>: an input file can be specified from whitch to get the code
+
+Options:
+    --backend=<name>      how to run the code: ast walks the syntax tree, vm compiles it to bytecode [default: ast]
+    --no-optimize         run the code as written, without folding constant expressions and pruning branches
+    --max-depth=<calls>   the maximum depth of nested function calls, tail calls don't count [default: 1000]
+    --max-steps=<steps>   the maximum number of steps a program can take
+    --time-limit=<ms>     the maximum time in milliseconds a program can run for
+    --max-memory=<bytes>  the maximum number of bytes the program may hold
+    --sandbox             deny the file, environment and process access that is not allowed below
+    --allow-read=<paths>  comma separated files and directories that can be read, implies --sandbox
+    --allow-write=<paths>  comma separated files and directories that can be written, implies --sandbox
+    --allow-env           allow reading environment variables, implies --sandbox
+    --allow-exec          allow running other programs, implies --sandbox
 ";
 
 #[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 pub struct Args {
     arg_input: Option<String>,
+    flag_backend: String,
+    flag_no_optimize: bool,
+    flag_max_depth: usize,
+    flag_max_steps: Option<u64>,
+    flag_time_limit: Option<u64>,
+    flag_max_memory: Option<usize>,
+    flag_sandbox: bool,
+    flag_allow_read: Option<String>,
+    flag_allow_write: Option<String>,
+    flag_allow_env: bool,
+    flag_allow_exec: bool,
 }
 
 pub fn get_args() -> Args {
@@ -37,32 +55,96 @@ pub fn get_args() -> Args {
 fn main() {
     let args: Args = get_args();
 
+    let mut evaluator = Evaluator::new();
+    evaluator.set_backend(backend(&args.flag_backend));
+    evaluator.set_max_depth(args.flag_max_depth);
+    evaluator.set_step_limit(args.flag_max_steps);
+    evaluator.set_time_limit(args.flag_time_limit.map(Duration::from_millis));
+    evaluator.set_memory_limit(args.flag_max_memory);
+    evaluator.set_permissions(permissions(&args));
+    let mut engine = Engine::with_evaluator(evaluator);
+    engine.set_optimization(!args.flag_no_optimize);
+
     match args.arg_input {
         Some(input_file_name) => {
             if !input_file_name.ends_with(".hc") {
                 panic!("not a valid extension")
             }
-            execute_file(input_file_name);
+            execute_file(input_file_name, engine);
         }
         None => {
             println!("Welcome to halcon");
-            repl::start(io::stdin(), io::stdout());
+            repl::start(io::stdin(), io::stdout(), engine);
         }
     }
 }
 
-fn execute_file(file_name: String) {
-    let contents = fs::read_to_string(file_name).unwrap();
-    let lex = Lexer::new(contents);
-    let mut pars = Parser::new(lex);
+/**
+Returns the backend with the name given to `--backend`, or exits if there is none
 
-    let program = pars.parse_program();
+# Arguments
+* `name` - the name of the backend
+*/
+fn backend(name: &str) -> Backend {
+    match name {
+        "ast" => Backend::Ast,
+        "vm" => Backend::Vm,
+        other => {
+            eprintln!("unknown backend: {}, expected ast or vm", other);
+            process::exit(1)
+        }
+    }
+}
 
-    if pars.errors().len() != 0 {
-        println!("Errors have been found: \n{:?}", pars.errors());
-        return;
+/**
+Returns the permissions set by the sandbox options. Without them every operation is allowed
+
+# Arguments
+* `args` - the command line arguments
+*/
+fn permissions(args: &Args) -> Permissions {
+    let sandboxed = args.flag_sandbox
+        || args.flag_allow_read.is_some()
+        || args.flag_allow_write.is_some()
+        || args.flag_allow_env
+        || args.flag_allow_exec;
+    if !sandboxed {
+        return Permissions::all();
     }
 
-    let mut evaluator = Evaluator::new();
-    let _ = evaluator.eval(program);
+    let paths = |list: &Option<String>| {
+        PathAccess::Only(
+            list.iter()
+                .flat_map(|list| list.split(','))
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+                .collect(),
+        )
+    };
+
+    Permissions {
+        read: paths(&args.flag_allow_read),
+        write: paths(&args.flag_allow_write),
+        env: args.flag_allow_env,
+        exec: args.flag_allow_exec,
+    }
+}
+
+fn execute_file(file_name: String, mut engine: Engine) {
+    match engine.eval_file(&file_name) {
+        Ok(_) => (),
+        Err(HalconError::Parse(errors) | HalconError::Resolve(errors)) => {
+            println!("Errors have been found: \n{:?}", errors);
+            process::exit(1);
+        }
+        Err(HalconError::Runtime(error)) => {
+            eprintln!("Uncaught {}", error.inspect());
+            eprint!("{}", error.trace(&file_name));
+            process::exit(1);
+        }
+        Err(error) => {
+            eprintln!("{}: {}", file_name, error);
+            process::exit(1);
+        }
+    }
 }
@@ -0,0 +1,391 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::rc::Rc;
+
+use indexmap::{IndexMap, IndexSet};
+
+use crate::bigint::BigInt;
+
+use super::{
+    Array, BigInteger, Boolean, Dict, Error, Integer, NativeFunction, Object, ObjectType, Set,
+    StringObject, Tuple, ARGUMENT_ERROR, ARRAY, BOOLEAN, DICT, INTEGER, SET, STRING, TYPE_ERROR,
+};
+
+/// Conversion of a Rust value into a Halcon object
+pub trait IntoHalcon {
+    fn into_halcon(self) -> ObjectType;
+}
+
+/// Conversion of a Halcon object into a Rust value. Objects of the wrong type produce a `TypeError`
+pub trait FromHalcon: Sized {
+    fn from_halcon(value: ObjectType) -> Result<Self, Error>;
+}
+
+/**
+Returns the `TypeError` of a failed conversion
+
+# Arguments
+* `expected` - the description of the expected object
+* `value` - the object that was found instead
+*/
+fn type_error(expected: &str, value: &ObjectType) -> Error {
+    Error::new(
+        TYPE_ERROR,
+        format!("expected {}, got {}", expected, value.object_type()),
+    )
+}
+
+/**
+Returns the converted key, or the `TypeError` of a dict literal if it can't be used as a dict key
+
+# Arguments
+* `key` - the key to convert
+*/
+fn into_key<K: IntoHalcon>(key: K) -> Result<ObjectType, Error> {
+    let key = key.into_halcon();
+    if key.is_hashable() {
+        Ok(key)
+    } else {
+        Err(Error::new(
+            TYPE_ERROR,
+            format!("unusable as hash key: {}", key.object_type()),
+        ))
+    }
+}
+
+impl IntoHalcon for ObjectType {
+    fn into_halcon(self) -> ObjectType {
+        self
+    }
+}
+
+impl FromHalcon for ObjectType {
+    fn from_halcon(value: ObjectType) -> Result<Self, Error> {
+        Ok(value)
+    }
+}
+
+impl IntoHalcon for () {
+    fn into_halcon(self) -> ObjectType {
+        ObjectType::Null
+    }
+}
+
+macro_rules! integer_conversions {
+    ($($int:ty),*) => {$(
+        impl IntoHalcon for $int {
+            fn into_halcon(self) -> ObjectType {
+                ObjectType::Integer(Integer {
+                    value: self as i128,
+                })
+            }
+        }
+
+        impl FromHalcon for $int {
+            fn from_halcon(value: ObjectType) -> Result<Self, Error> {
+                match value {
+                    ObjectType::Integer(int) => <$int>::try_from(int.value).map_err(|_| {
+                        Error::new(
+                            TYPE_ERROR,
+                            format!("integer {} does not fit in {}", int.value, stringify!($int)),
+                        )
+                    }),
+                    ObjectType::BigInteger(int) => Err(Error::new(
+                        TYPE_ERROR,
+                        format!("integer {} does not fit in {}", int.value, stringify!($int)),
+                    )),
+                    other => Err(type_error(INTEGER, &other)),
+                }
+            }
+        }
+    )*};
+}
+
+integer_conversions!(i32, i64, i128, u32, u64, usize);
+
+impl IntoHalcon for BigInt {
+    fn into_halcon(self) -> ObjectType {
+        BigInteger::object(self)
+    }
+}
+
+impl FromHalcon for BigInt {
+    fn from_halcon(value: ObjectType) -> Result<Self, Error> {
+        match value {
+            ObjectType::Integer(int) => Ok(BigInt::from(int.value)),
+            ObjectType::BigInteger(int) => Ok(Rc::unwrap_or_clone(int.value)),
+            other => Err(type_error(INTEGER, &other)),
+        }
+    }
+}
+
+impl IntoHalcon for bool {
+    fn into_halcon(self) -> ObjectType {
+        ObjectType::Boolean(Boolean { value: self })
+    }
+}
+
+impl FromHalcon for bool {
+    fn from_halcon(value: ObjectType) -> Result<Self, Error> {
+        match value {
+            ObjectType::Boolean(boolean) => Ok(boolean.value),
+            other => Err(type_error(BOOLEAN, &other)),
+        }
+    }
+}
+
+impl IntoHalcon for String {
+    fn into_halcon(self) -> ObjectType {
+        ObjectType::String(StringObject { value: self.into() })
+    }
+}
+
+impl IntoHalcon for &str {
+    fn into_halcon(self) -> ObjectType {
+        self.to_string().into_halcon()
+    }
+}
+
+impl FromHalcon for String {
+    fn from_halcon(value: ObjectType) -> Result<Self, Error> {
+        match value {
+            ObjectType::String(string) => Ok(string.value.to_string()),
+            other => Err(type_error(STRING, &other)),
+        }
+    }
+}
+
+impl<T: IntoHalcon> IntoHalcon for Vec<T> {
+    fn into_halcon(self) -> ObjectType {
+        ObjectType::Array(Array {
+            elements: Rc::new(self.into_iter().map(IntoHalcon::into_halcon).collect()),
+        })
+    }
+}
+
+impl<T: FromHalcon> FromHalcon for Vec<T> {
+    fn from_halcon(value: ObjectType) -> Result<Self, Error> {
+        match value {
+            ObjectType::Array(array) => Rc::unwrap_or_clone(array.elements)
+                .into_iter()
+                .enumerate()
+                .map(|(i, element)| {
+                    T::from_halcon(element).map_err(|error| Error {
+                        message: format!("element {}: {}", i, error.message),
+                        ..error
+                    })
+                })
+                .collect(),
+            other => Err(type_error(ARRAY, &other)),
+        }
+    }
+}
+
+macro_rules! map_conversions {
+    ($map:ident) => {
+        /// A key that can't be used as a dict key, like an array, gives a `TypeError` object
+        impl<K: IntoHalcon, V: IntoHalcon> IntoHalcon for $map<K, V> {
+            fn into_halcon(self) -> ObjectType {
+                self.into_iter()
+                    .map(|(key, value)| Ok((into_key(key)?, value.into_halcon())))
+                    .collect::<Result<_, Error>>()
+                    .map(|pairs| {
+                        ObjectType::Dict(Dict {
+                            pairs: Rc::new(pairs),
+                        })
+                    })
+                    .into_halcon()
+            }
+        }
+
+        impl<K: FromHalcon + Eq + Hash, V: FromHalcon> FromHalcon for $map<K, V> {
+            fn from_halcon(value: ObjectType) -> Result<Self, Error> {
+                match value {
+                    ObjectType::Dict(dict) => Rc::unwrap_or_clone(dict.pairs)
+                        .into_iter()
+                        .map(|(key, value)| {
+                            let description = key.inspect();
+                            let in_entry = |error: Error| Error {
+                                message: format!("entry {}: {}", description, error.message),
+                                ..error
+                            };
+
+                            Ok((
+                                K::from_halcon(key).map_err(in_entry)?,
+                                V::from_halcon(value).map_err(in_entry)?,
+                            ))
+                        })
+                        .collect(),
+                    other => Err(type_error(DICT, &other)),
+                }
+            }
+        }
+    };
+}
+
+map_conversions!(HashMap);
+// keeps the order of the pairs
+map_conversions!(IndexMap);
+
+macro_rules! set_conversions {
+    ($set:ident) => {
+        /// An element that can't be used as a dict key, like an array, gives a `TypeError` object
+        impl<T: IntoHalcon> IntoHalcon for $set<T> {
+            fn into_halcon(self) -> ObjectType {
+                self.into_iter()
+                    .map(into_key)
+                    .collect::<Result<_, Error>>()
+                    .map(|elements| {
+                        ObjectType::Set(Set {
+                            elements: Rc::new(elements),
+                        })
+                    })
+                    .into_halcon()
+            }
+        }
+
+        impl<T: FromHalcon + Eq + Hash> FromHalcon for $set<T> {
+            fn from_halcon(value: ObjectType) -> Result<Self, Error> {
+                match value {
+                    ObjectType::Set(set) => Rc::unwrap_or_clone(set.elements)
+                        .into_iter()
+                        .map(|element| {
+                            let description = element.inspect();
+                            T::from_halcon(element).map_err(|error| Error {
+                                message: format!("element {}: {}", description, error.message),
+                                ..error
+                            })
+                        })
+                        .collect(),
+                    other => Err(type_error(SET, &other)),
+                }
+            }
+        }
+    };
+}
+
+set_conversions!(HashSet);
+// keeps the order of the elements
+set_conversions!(IndexSet);
+
+impl<T: IntoHalcon> IntoHalcon for Option<T> {
+    fn into_halcon(self) -> ObjectType {
+        match self {
+            Some(value) => value.into_halcon(),
+            None => ObjectType::Null,
+        }
+    }
+}
+
+impl<T: FromHalcon> FromHalcon for Option<T> {
+    fn from_halcon(value: ObjectType) -> Result<Self, Error> {
+        match value {
+            ObjectType::Null => Ok(None),
+            other => T::from_halcon(other).map(Some),
+        }
+    }
+}
+
+/// An `Err` becomes an `ObjectType::Error`, so a registered function that returns it raises the error
+impl<T: IntoHalcon> IntoHalcon for Result<T, Error> {
+    fn into_halcon(self) -> ObjectType {
+        match self {
+            Ok(value) => value.into_halcon(),
+            Err(error) => ObjectType::Error(error),
+        }
+    }
+}
+
+macro_rules! tuple_conversions {
+    ($len:literal; $($name:ident),+) => {
+        impl<$($name: IntoHalcon),+> IntoHalcon for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn into_halcon(self) -> ObjectType {
+                let ($($name,)+) = self;
+                ObjectType::Tuple(Tuple {
+                    elements: Rc::new([$($name.into_halcon()),+]),
+                })
+            }
+        }
+
+        /// Arrays with one element per field are converted too
+        impl<$($name: FromHalcon),+> FromHalcon for ($($name,)+) {
+            fn from_halcon(value: ObjectType) -> Result<Self, Error> {
+                let elements = match value {
+                    ObjectType::Tuple(tuple) if tuple.elements.len() == $len => tuple.elements.to_vec(),
+                    ObjectType::Array(array) if array.elements.len() == $len => {
+                        Rc::unwrap_or_clone(array.elements)
+                    }
+                    other => {
+                        return Err(type_error(
+                            concat!("a TUPLE or ARRAY of ", $len, " elements"),
+                            &other,
+                        ))
+                    }
+                };
+
+                let mut elements = elements.into_iter();
+                Ok(($($name::from_halcon(elements.next().unwrap())?,)+))
+            }
+        }
+    };
+}
+
+tuple_conversions!(1; A);
+tuple_conversions!(2; A, B);
+tuple_conversions!(3; A, B, C);
+tuple_conversions!(4; A, B, C, D);
+
+/// A Rust function with typed arguments that can be registered as a Halcon function.
+/// `Args` is the tuple of argument types, so that functions of different arities can be told apart
+pub trait TypedFunction<Args> {
+    /**
+    Returns the function as a `NativeFunction` that converts the arguments before calling it
+
+    # Arguments
+    * `name` - the name the function is registered with, used in the error messages
+    */
+    fn into_native(self, name: &str) -> NativeFunction;
+}
+
+macro_rules! typed_functions {
+    ($len:literal; $($name:ident),*) => {
+        impl<Fun, Ret, $($name),*> TypedFunction<($($name,)*)> for Fun
+        where
+            Fun: Fn($($name),*) -> Ret + 'static,
+            Ret: IntoHalcon,
+            $($name: FromHalcon),*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn into_native(self, name: &str) -> NativeFunction {
+                let name = name.to_string();
+
+                Rc::new(move |_, args| {
+                    if args.len() != $len {
+                        return Err(Error::new(
+                            ARGUMENT_ERROR,
+                            format!("wrong number of arguments. got: {}, want: {}", args.len(), $len),
+                        ));
+                    }
+
+                    let mut args = args.into_iter().enumerate();
+                    $(
+                        let (i, arg) = args.next().unwrap();
+                        let $name = $name::from_halcon(arg).map_err(|error| Error {
+                            message: format!("argument {} of {}: {}", i + 1, name, error.message),
+                            ..error
+                        })?;
+                    )*
+
+                    Ok(self($($name),*).into_halcon())
+                })
+            }
+        }
+    };
+}
+
+typed_functions!(0;);
+typed_functions!(1; A);
+typed_functions!(2; A, B);
+typed_functions!(3; A, B, C);
+typed_functions!(4; A, B, C, D);
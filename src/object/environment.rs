@@ -1,33 +1,70 @@
 use std::collections::HashMap;
+use std::rc::Rc;
 
-use super::ObjectType;
+use super::{HeldValues, ObjectType};
 
+/// The variables in scope. Scopes are shared between clones and copied on write, so cloning an
+/// environment is cheap and changes to a clone are never seen by the others.
+/// Variables resolved to slots live in the call frame of their function, the rest are kept by name
 #[derive(Clone, Debug)]
 pub struct Environment {
-    store: HashMap<String, ObjectType>,
-    outer: Option<Box<Environment>>,
+    store: Rc<HashMap<String, ObjectType>>,
+    /// the slots of a call frame, empty until their variable is bound
+    slots: Rc<Vec<Option<ObjectType>>>,
+    outer: Option<Rc<Environment>>,
 }
 
 impl Environment {
     pub fn new() -> Self {
         Self {
-            store: HashMap::new(),
+            store: Rc::new(HashMap::new()),
+            slots: Rc::new(vec![]),
             outer: None,
         }
     }
 
     pub fn new_enclosed_environment(outer: &Self) -> Self {
         let mut env = Self::new();
-        env.outer = Some(Box::new(outer.clone()));
+        env.outer = Some(Rc::new(outer.clone()));
         env
     }
 
     pub fn update(&mut self, other: &Self) {
-        self.outer = Some(Box::new(other.clone()));
+        self.outer = Some(Rc::new(other.clone()));
     }
 
-    pub fn get(&self, name: String) -> Option<&ObjectType> {
-        match self.store.get(&name) {
+    /**
+    Returns the environment of a call to a function with resolved variables
+
+    # Arguments
+    * `outer` - the environment of the function
+    * `slots` - the slots of the call frame, starting with the arguments
+    */
+    pub fn new_frame(outer: &Self, slots: Vec<Option<ObjectType>>) -> Self {
+        Self {
+            store: Rc::new(HashMap::new()),
+            slots: Rc::new(slots),
+            outer: Some(Rc::new(outer.clone())),
+        }
+    }
+
+    /**
+    Returns the environment with the same variables and other slots, the values captured by a closure
+    created by the virtual machine
+
+    # Arguments
+    * `slots` - the slots of the new environment
+    */
+    pub fn with_slots(&self, slots: Vec<Option<ObjectType>>) -> Self {
+        Self {
+            store: self.store.clone(),
+            slots: Rc::new(slots),
+            outer: self.outer.clone(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ObjectType> {
+        match self.store.get(name) {
             Some(obj) => Some(obj),
             None => match &self.outer {
                 Some(outer) => outer.get(name),
@@ -37,6 +74,128 @@ impl Environment {
     }
 
     pub fn set(&mut self, name: &str, obj: ObjectType) {
-        self.store.insert(name.to_string(), obj);
+        let store = Rc::make_mut(&mut self.store);
+        match store.get_mut(name) {
+            Some(slot) => *slot = obj,
+            None => {
+                store.insert(name.to_string(), obj);
+            }
+        }
+    }
+
+    /**
+    Unbinds a variable kept by name in the innermost scope and returns its value, if it was bound there
+
+    # Arguments
+    * `name` - the name of the variable
+    */
+    pub fn remove(&mut self, name: &str) -> Option<ObjectType> {
+        if !self.store.contains_key(name) {
+            return None;
+        }
+        Rc::make_mut(&mut self.store).remove(name)
+    }
+
+    /**
+    Returns the value of a variable kept by name in the innermost scope, to be updated in place.
+    The scope is copied if it is shared, like `set` does
+
+    # Arguments
+    * `name` - the name of the variable
+    */
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut ObjectType> {
+        Rc::make_mut(&mut self.store).get_mut(name)
+    }
+
+    /**
+    Returns the value in a slot of the call frame `depth` frames out, if its variable is bound
+
+    # Arguments
+    * `depth` - the number of frames to go out
+    * `slot` - the slot in that frame
+    */
+    pub fn get_slot(&self, depth: usize, slot: usize) -> Option<&ObjectType> {
+        let mut env = self;
+        for _ in 0..depth {
+            env = env.outer.as_deref()?;
+        }
+        env.slots.get(slot)?.as_ref()
+    }
+
+    /**
+    Binds a value to a slot of the call frame `depth` frames out. The frames are copied if they are
+    shared, so the functions that captured them keep the old value
+
+    # Arguments
+    * `depth` - the number of frames to go out
+    * `slot` - the slot in that frame
+    * `obj` - the value to bind
+    */
+    pub fn set_slot(&mut self, depth: usize, slot: usize, obj: ObjectType) {
+        if depth == 0 {
+            Rc::make_mut(&mut self.slots)[slot] = Some(obj);
+        } else if let Some(outer) = &mut self.outer {
+            Rc::make_mut(outer).set_slot(depth - 1, slot, obj);
+        }
+    }
+
+    /**
+    Returns the approximate number of bytes held by the innermost scope, the one a write copies
+    when it is shared. The values of the variables are not included
+
+    # Arguments
+
+    no arguments
+    */
+    pub fn size(&self) -> usize {
+        self.store.len() * (std::mem::size_of::<String>() + std::mem::size_of::<ObjectType>())
+            + self.slots.len() * std::mem::size_of::<Option<ObjectType>>()
+    }
+
+    /**
+    Adds the variables of the environment and of its outer scopes to the values held by a program.
+    The scopes shared with environments already added are skipped
+
+    # Arguments
+    * `held` - the values held by the program
+    */
+    pub fn hold<'a>(&'a self, held: &mut HeldValues<'a>) {
+        let entry = std::mem::size_of::<String>() + std::mem::size_of::<ObjectType>();
+        let slot = std::mem::size_of::<Option<ObjectType>>();
+        let mut env = self;
+        loop {
+            if held.reach(Rc::as_ptr(&env.store).cast(), env.store.len() * entry) {
+                held.pending.extend(env.store.values());
+            }
+            if held.reach(Rc::as_ptr(&env.slots).cast(), env.slots.len() * slot) {
+                held.pending.extend(env.slots.iter().flatten());
+            }
+            match &env.outer {
+                Some(outer) if held.reach(Rc::as_ptr(outer).cast(), 0) => env = outer,
+                _ => break,
+            }
+        }
+    }
+
+    /**
+    Returns if two environments are the same one, sharing all their scopes, rather than two
+    environments with equal variables
+
+    # Arguments
+    * `other` - the environment to compare with
+    */
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        let outer = match (&self.outer, &other.outer) {
+            (Some(outer), Some(other)) => Rc::ptr_eq(outer, other),
+            (None, None) => true,
+            _ => false,
+        };
+        outer && Rc::ptr_eq(&self.store, &other.store) && Rc::ptr_eq(&self.slots, &other.slots)
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
     }
 }
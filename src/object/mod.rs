@@ -1,9 +1,21 @@
 use self::environment::Environment;
-use crate::ast::{expressions::Identifier, statements::BlockStatement, Node};
+use crate::ast::expressions::{FunctionScope, Identifier};
+use crate::ast::{statements::BlockStatement, Node};
+use crate::bigint::BigInt;
+use crate::compiler::code::Chunk;
+use crate::evaluator::Evaluator;
+use crate::token::Span;
+use std::collections::HashSet;
+use std::fmt::Debug;
 use std::hash::Hash;
-use std::{collections::HashMap, fmt::Debug, i128};
+use std::rc::Rc;
 
+use indexmap::{IndexMap, IndexSet};
+
+pub mod convert;
 pub mod environment;
+#[cfg(test)]
+mod test;
 
 pub const INTEGER: &str = "INTEGER";
 pub const BOOLEAN: &str = "BOOLEAN";
@@ -15,7 +27,27 @@ pub const FUNCTION: &str = "FUNCTION";
 pub const STRING: &str = "STRING";
 pub const BUILTIN: &str = "BUILTIN";
 pub const ARRAY: &str = "ARRAY";
+pub const TUPLE: &str = "TUPLE";
 pub const DICT: &str = "DICT";
+pub const SET: &str = "SET";
+pub const RANGE: &str = "RANGE";
+pub const RESULT: &str = "RESULT";
+pub const TAIL_CALL: &str = "TAIL_CALL";
+
+pub const ANONYMOUS_FUNCTION: &str = "<anonymous>";
+
+pub const GENERIC_ERROR: &str = "Error";
+pub const TYPE_ERROR: &str = "TypeError";
+pub const NAME_ERROR: &str = "NameError";
+pub const INDEX_ERROR: &str = "IndexError";
+pub const ARGUMENT_ERROR: &str = "ArgumentError";
+pub const ZERO_DIVISION_ERROR: &str = "ZeroDivisionError";
+pub const STACK_OVERFLOW_ERROR: &str = "StackOverflowError";
+pub const EXECUTION_LIMIT_ERROR: &str = "ExecutionLimitError";
+pub const MEMORY_ERROR: &str = "MemoryError";
+pub const INTERRUPT_ERROR: &str = "InterruptError";
+pub const IO_ERROR: &str = "IOError";
+pub const PERMISSION_ERROR: &str = "PermissionError";
 
 pub trait Object: Debug {
     fn object_type(&self) -> String;
@@ -25,6 +57,7 @@ pub trait Object: Debug {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ObjectType {
     Integer(Integer),
+    BigInteger(BigInteger),
     Boolean(Boolean),
     Null,
     Return(ReturnValue),
@@ -33,14 +66,69 @@ pub enum ObjectType {
     Function(Function),
     String(StringObject),
     Builtin(Builtin),
+    Native(Native),
     Array(Array),
+    Tuple(Tuple),
     Dict(Dict),
+    Set(Set),
+    Range(Range),
+    Result(ResultValue),
+    TailCall(TailCall),
+}
+
+impl ObjectType {
+    /**
+    Returns the approximate number of bytes held by the object itself: the characters of a string,
+    the limbs of a big integer, the slots of the elements of an array, tuple, dict or set and the
+    variables of the scope captured by a function. The values inside are not included, they are
+    counted on their own when they are created
+
+    # Arguments
+
+    no arguments
+    */
+    pub fn size(&self) -> usize {
+        let slot = std::mem::size_of::<ObjectType>();
+        let contents = match self {
+            ObjectType::String(string) => string.value.len(),
+            ObjectType::BigInteger(int) => int.value.size(),
+            ObjectType::Array(array) => array.elements.len() * slot,
+            ObjectType::Tuple(tuple) => tuple.elements.len() * slot,
+            ObjectType::Dict(dict) => dict.pairs.len() * 2 * slot,
+            ObjectType::Set(set) => set.elements.len() * slot,
+            ObjectType::Function(function) => function.env.size(),
+            _ => 0,
+        };
+
+        slot + contents
+    }
+
+    /**
+    Returns if the object can be used as a dict key. Integers, booleans, strings and null can,
+    and tuples can if all their elements can
+
+    # Arguments
+
+    no arguments
+    */
+    pub fn is_hashable(&self) -> bool {
+        match self {
+            ObjectType::Integer(_)
+            | ObjectType::BigInteger(_)
+            | ObjectType::Boolean(_)
+            | ObjectType::String(_)
+            | ObjectType::Null => true,
+            ObjectType::Tuple(tuple) => tuple.elements.iter().all(ObjectType::is_hashable),
+            _ => false,
+        }
+    }
 }
 
 impl Object for ObjectType {
     fn object_type(&self) -> String {
         match self {
             ObjectType::Integer(ty) => ty.object_type(),
+            ObjectType::BigInteger(ty) => ty.object_type(),
             ObjectType::Boolean(ty) => ty.object_type(),
             ObjectType::Null => NULL.to_string(),
             ObjectType::Break => BREAK.to_string(),
@@ -49,14 +137,21 @@ impl Object for ObjectType {
             ObjectType::Function(ty) => ty.object_type(),
             ObjectType::String(ty) => ty.object_type(),
             ObjectType::Builtin(ty) => ty.object_type(),
+            ObjectType::Native(ty) => ty.object_type(),
             ObjectType::Array(ty) => ty.object_type(),
+            ObjectType::Tuple(ty) => ty.object_type(),
             ObjectType::Dict(ty) => ty.object_type(),
+            ObjectType::Set(ty) => ty.object_type(),
+            ObjectType::Range(ty) => ty.object_type(),
+            ObjectType::Result(ty) => ty.object_type(),
+            ObjectType::TailCall(ty) => ty.object_type(),
         }
     }
 
     fn inspect(&self) -> String {
         match self {
             ObjectType::Integer(ty) => ty.inspect(),
+            ObjectType::BigInteger(ty) => ty.inspect(),
             ObjectType::Boolean(ty) => ty.inspect(),
             ObjectType::Null => "null".to_string(),
             ObjectType::Break => "break".to_string(),
@@ -65,9 +160,105 @@ impl Object for ObjectType {
             ObjectType::Function(ty) => ty.inspect(),
             ObjectType::String(ty) => ty.inspect(),
             ObjectType::Builtin(ty) => ty.inspect(),
+            ObjectType::Native(ty) => ty.inspect(),
             ObjectType::Array(ty) => ty.inspect(),
+            ObjectType::Tuple(ty) => ty.inspect(),
             ObjectType::Dict(ty) => ty.inspect(),
+            ObjectType::Set(ty) => ty.inspect(),
+            ObjectType::Range(ty) => ty.inspect(),
+            ObjectType::Result(ty) => ty.inspect(),
+            ObjectType::TailCall(ty) => ty.inspect(),
+        }
+    }
+}
+
+/// The values held by a program, measured to compare with its memory limit. Copies of a value share
+/// its contents, so the contents reached from several values are only counted once
+#[derive(Default)]
+pub struct HeldValues<'a> {
+    /// the addresses of the contents already counted
+    seen: HashSet<*const ()>,
+    /// the values reached whose contents are not counted yet
+    pending: Vec<&'a ObjectType>,
+    bytes: usize,
+}
+
+impl<'a> HeldValues<'a> {
+    /**
+    Adds a value held by the program, with everything it refers to
+
+    # Arguments
+    * `obj` - the held value
+    */
+    pub fn add(&mut self, obj: &'a ObjectType) {
+        self.bytes += std::mem::size_of::<ObjectType>();
+        self.pending.push(obj);
+    }
+
+    /**
+    Returns the approximate number of bytes held by the added values, sized like `ObjectType::size`
+
+    # Arguments
+
+    no arguments
+    */
+    pub fn bytes(mut self) -> usize {
+        let slot = std::mem::size_of::<ObjectType>();
+        while let Some(obj) = self.pending.pop() {
+            match obj {
+                ObjectType::String(string) => {
+                    self.reach(Rc::as_ptr(&string.value).cast(), string.value.len());
+                }
+                ObjectType::BigInteger(int) => {
+                    self.reach(Rc::as_ptr(&int.value).cast(), int.value.size());
+                }
+                ObjectType::Array(array) => {
+                    let elements = &array.elements;
+                    if self.reach(Rc::as_ptr(elements).cast(), elements.len() * slot) {
+                        self.pending.extend(elements.iter());
+                    }
+                }
+                ObjectType::Tuple(tuple) => {
+                    let elements = &tuple.elements;
+                    if self.reach(Rc::as_ptr(elements).cast(), elements.len() * slot) {
+                        self.pending.extend(elements.iter());
+                    }
+                }
+                ObjectType::Dict(dict) => {
+                    let pairs = &dict.pairs;
+                    if self.reach(Rc::as_ptr(pairs).cast(), pairs.len() * 2 * slot) {
+                        self.pending.extend(pairs.keys().chain(pairs.values()));
+                    }
+                }
+                ObjectType::Set(set) => {
+                    let elements = &set.elements;
+                    if self.reach(Rc::as_ptr(elements).cast(), elements.len() * slot) {
+                        self.pending.extend(elements.iter());
+                    }
+                }
+                ObjectType::Function(function) => function.env.hold(&mut self),
+                ObjectType::Return(ReturnValue { value })
+                | ObjectType::Result(ResultValue { value, .. }) => self.pending.push(value),
+                _ => {}
+            }
+        }
+
+        self.bytes
+    }
+
+    /**
+    Counts the contents at an address the first time they are reached, and returns if they were
+
+    # Arguments
+    * `contents` - the address of the contents
+    * `bytes` - the size of the contents
+    */
+    fn reach(&mut self, contents: *const (), bytes: usize) -> bool {
+        let first = self.seen.insert(contents);
+        if first {
+            self.bytes += bytes;
         }
+        first
     }
 }
 
@@ -93,6 +284,48 @@ impl Hash for Integer {
     }
 }
 
+/// An integer that doesn't fit in an `i128`. Integers are only kept as big integers while they are
+/// out of the range of `i128`, so the fast path of `Integer` is used whenever it can be
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct BigInteger {
+    pub value: Rc<BigInt>,
+}
+
+impl BigInteger {
+    /**
+    Returns an `ObjectType::Integer` if the value fits in an `i128`, or an `ObjectType::BigInteger`
+    otherwise
+
+    # Arguments
+    * `value` - the integer
+    */
+    pub fn object(value: BigInt) -> ObjectType {
+        match value.to_i128() {
+            Some(value) => ObjectType::Integer(Integer { value }),
+            None => ObjectType::BigInteger(BigInteger {
+                value: Rc::new(value),
+            }),
+        }
+    }
+}
+
+impl Object for BigInteger {
+    fn object_type(&self) -> String {
+        INTEGER.to_string()
+    }
+
+    fn inspect(&self) -> String {
+        self.value.to_string()
+    }
+}
+
+impl Hash for BigInteger {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+        self.object_type().hash(state);
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Boolean {
     pub value: bool,
@@ -115,6 +348,7 @@ impl Hash for Boolean {
     }
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Null {}
 
@@ -134,9 +368,10 @@ impl Hash for Null {
     }
 }
 
+/// Strings are immutable and shared between copies, so copying a string value is cheap
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct StringObject {
-    pub value: String,
+    pub value: Rc<str>,
 }
 
 impl Object for StringObject {
@@ -177,9 +412,68 @@ impl Hash for ReturnValue {
     }
 }
 
+/// A call in tail position that has been evaluated up to the point of applying the function.
+/// It is returned to the caller's `apply_function`, which runs it without nesting a new call
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct TailCall {
+    pub function: Box<ObjectType>,
+    pub args: Vec<ObjectType>,
+    /// the position of the call in the source code
+    pub span: Span,
+}
+
+impl Object for TailCall {
+    fn object_type(&self) -> String {
+        TAIL_CALL.to_string()
+    }
+
+    fn inspect(&self) -> String {
+        format!("tail call to {}", self.function.inspect())
+    }
+}
+
+impl Hash for TailCall {
+    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {
+        panic!("not able to hash tail calls")
+    }
+}
+
+/// The value returned by the `ok` and `err` builtins. The `?` operator unwraps ok results and
+/// returns err results from the enclosing function
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ResultValue {
+    pub ok: bool,
+    pub value: Box<ObjectType>,
+}
+
+impl Object for ResultValue {
+    fn object_type(&self) -> String {
+        RESULT.to_string()
+    }
+
+    fn inspect(&self) -> String {
+        if self.ok {
+            format!("ok({})", self.value.inspect())
+        } else {
+            format!("err({})", self.value.inspect())
+        }
+    }
+}
+
+impl Hash for ResultValue {
+    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {
+        panic!("not able to hash results")
+    }
+}
+
+/// A runtime error raised by the evaluator or by a `throw` statement
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Error {
     pub message: String,
+    /// the category of the error, e.g. `TypeError`
+    pub kind: String,
+    /// the function calls that were active when the error was raised, innermost first
+    pub stack: Vec<Frame>,
 }
 
 impl Object for Error {
@@ -188,7 +482,59 @@ impl Object for Error {
     }
 
     fn inspect(&self) -> String {
-        format!("ERROR: {}", self.message)
+        format!("ERROR: {}: {}", self.kind, self.message)
+    }
+}
+
+impl Error {
+    /**
+    Returns an error of the specified kind with the specified message and no stack trace.
+    The trace is attached by the evaluator when the error leaves a function call
+
+    # Arguments
+    * `kind` - the kind of error, e.g. `TYPE_ERROR`
+    * `message` - the error message
+
+    # Examples
+    ```
+    let error = Error::new(TYPE_ERROR, "expected a string");
+    ```
+    */
+    pub fn new(kind: &str, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: kind.to_string(),
+            stack: vec![],
+        }
+    }
+
+    /**
+    Returns the stack trace of the error, with one `at <function> (<source>:<line>:<column>)` line per call.
+    Consecutive repetitions of the same call are collapsed into a single line
+
+    # Arguments
+    * `source` - the name of the source the program was read from
+    */
+    pub fn trace(&self, source: &str) -> String {
+        let mut trace = String::new();
+        let mut i = 0;
+
+        while i < self.stack.len() {
+            let frame = &self.stack[i];
+            let repeated = self.stack[i + 1..]
+                .iter()
+                .take_while(|other| *other == frame)
+                .count();
+
+            trace += &format!("    at {} ({}:{})\n", frame.function, source, frame.span);
+            if repeated > 0 {
+                trace += &format!("    ... repeated {} more times\n", repeated);
+            }
+
+            i += repeated + 1;
+        }
+
+        trace
     }
 }
 
@@ -198,11 +544,26 @@ impl Hash for Error {
     }
 }
 
+/// A function call that is being evaluated
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Frame {
+    /// the name the called function was bound to with a let statement
+    pub function: String,
+    /// the position of the call in the source code
+    pub span: Span,
+}
+
 #[derive(Debug, Clone)]
 pub struct Function {
-    pub parameters: Vec<Identifier>,
-    pub body: BlockStatement,
+    /// the name of the let statement the function was bound to, if any
+    pub name: Option<String>,
+    pub parameters: Rc<Vec<Identifier>>,
+    pub body: Rc<BlockStatement>,
+    /// the call frames laid out by the resolver, if the function was resolved
+    pub scope: Option<FunctionScope>,
     pub env: Environment,
+    /// the bytecode of the body, for the functions created by the virtual machine
+    pub chunk: Option<Rc<Chunk>>,
 }
 
 impl Object for Function {
@@ -241,8 +602,10 @@ impl Hash for Function {
     }
 }
 
-pub type BuiltinFunction = fn(args: Vec<ObjectType>) -> ObjectType;
+/// A builtin receives the evaluator that calls it, so it can call back the functions it is given
+pub type BuiltinFunction = fn(evaluator: &mut Evaluator, args: Vec<ObjectType>) -> ObjectType;
 
+#[allow(unpredictable_function_pointer_comparisons)]
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Builtin {
     pub function: BuiltinFunction,
@@ -264,14 +627,58 @@ impl Hash for Builtin {
     }
 }
 
+/// A Rust closure registered by the host application as a Halcon function
+pub type NativeFunction = Rc<dyn Fn(&mut Evaluator, Vec<ObjectType>) -> Result<ObjectType, Error>>;
+
+/// A function registered with `Evaluator::register_function`. It behaves like a builtin, but it can
+/// capture state and use the evaluator that calls it
+#[derive(Clone)]
+pub struct Native {
+    /// the name the function was registered with
+    pub name: String,
+    pub function: NativeFunction,
+}
+
+impl Object for Native {
+    fn object_type(&self) -> String {
+        BUILTIN.to_string()
+    }
+
+    fn inspect(&self) -> String {
+        BUILTIN.to_string()
+    }
+}
+
+impl Debug for Native {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Native({})", self.name)
+    }
+}
+
+impl PartialEq for Native {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.function, &other.function)
+    }
+}
+
+impl Eq for Native {}
+
+impl Hash for Native {
+    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {
+        panic!("not able to hash builtins")
+    }
+}
+
+/// The elements are shared between copies of the array and copied when one of them is changed,
+/// with `Rc::make_mut`
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Array {
-    pub elements: Vec<ObjectType>,
+    pub elements: Rc<Vec<ObjectType>>,
 }
 
 impl Object for Array {
     fn object_type(&self) -> String {
-        return ARRAY.to_string();
+        ARRAY.to_string()
     }
 
     fn inspect(&self) -> String {
@@ -297,14 +704,47 @@ impl Hash for Array {
     }
 }
 
+/// An immutable sequence of values. Tuples are equal if their elements are equal, and they are
+/// hashed with their elements, so a tuple of hashable values can be used as a dict key
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Tuple {
+    pub elements: Rc<[ObjectType]>,
+}
+
+impl Object for Tuple {
+    fn object_type(&self) -> String {
+        TUPLE.to_string()
+    }
+
+    fn inspect(&self) -> String {
+        match &self.elements[..] {
+            [element] => format!("({},)", element.inspect()),
+            elements => {
+                let elements: Vec<String> = elements.iter().map(ObjectType::inspect).collect();
+                format!("({})", elements.join(", "))
+            }
+        }
+    }
+}
+
+impl Hash for Tuple {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.elements.hash(state);
+        self.object_type().hash(state);
+    }
+}
+
+/// The pairs are shared between copies of the dict and copied when one of them is changed,
+/// with `Rc::make_mut`. They keep the order in which the keys were first inserted, which is the
+/// order they are inspected and iterated in. Two dicts with the same pairs are equal in any order
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Dict {
-    pub pairs: HashMap<ObjectType, ObjectType>,
+    pub pairs: Rc<IndexMap<ObjectType, ObjectType>>,
 }
 
 impl Object for Dict {
     fn object_type(&self) -> String {
-        return DICT.to_string();
+        DICT.to_string()
     }
 
     fn inspect(&self) -> String {
@@ -329,3 +769,126 @@ impl Hash for Dict {
         panic!("not able to hash dictionarys")
     }
 }
+
+/// The elements follow the same rules as the keys of a dict: they must be hashable, they keep the
+/// order in which they were first inserted and two sets with the same elements are equal in any order.
+/// Like the pairs of a dict, they are shared between copies of the set
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Set {
+    pub elements: Rc<IndexSet<ObjectType>>,
+}
+
+impl Object for Set {
+    fn object_type(&self) -> String {
+        SET.to_string()
+    }
+
+    fn inspect(&self) -> String {
+        let elements: Vec<String> = self.elements.iter().map(ObjectType::inspect).collect();
+        format!("#{{{}}}", elements.join(", "))
+    }
+}
+
+impl Hash for Set {
+    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {
+        panic!("not able to hash sets")
+    }
+}
+
+/// The integers from `start` up to `end`, not included, separated by `step`, like a Python range.
+/// The step is never zero, and when it is negative the range goes down to `end`.
+/// The integers are not stored, so a range of any length uses the same memory
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Range {
+    pub start: i128,
+    pub end: i128,
+    pub step: i128,
+}
+
+impl Range {
+    /**
+    Returns the number of integers in the range, or None if it doesn't fit in an `i128`
+
+    # Arguments
+
+    no arguments
+    */
+    pub fn len(&self) -> Option<i128> {
+        let (low, high) = if self.step > 0 {
+            (self.start, self.end)
+        } else {
+            (self.end, self.start)
+        };
+        if high <= low {
+            return Some(0);
+        }
+
+        let len = (high.abs_diff(low) - 1) / self.step.unsigned_abs() + 1;
+        len.try_into().ok()
+    }
+
+    /**
+    Returns if there are no integers in the range
+
+    # Arguments
+
+    no arguments
+    */
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+
+    /**
+    Returns the integer at a position of the range, or None if the position is out of the range
+
+    # Arguments
+
+    * `index` - the position of the integer, starting at 0
+    */
+    pub fn get(&self, index: i128) -> Option<i128> {
+        let value = index
+            .checked_mul(self.step)
+            .and_then(|offset| self.start.checked_add(offset))?;
+        (index >= 0 && self.contains(value)).then_some(value)
+    }
+
+    /**
+    Returns if an integer is in the range
+
+    # Arguments
+
+    * `value` - the integer to check
+    */
+    pub fn contains(&self, value: i128) -> bool {
+        let inside = if self.step > 0 {
+            self.start <= value && value < self.end
+        } else {
+            self.end < value && value <= self.start
+        };
+
+        inside
+            && value
+                .abs_diff(self.start)
+                .is_multiple_of(self.step.unsigned_abs())
+    }
+}
+
+impl Object for Range {
+    fn object_type(&self) -> String {
+        RANGE.to_string()
+    }
+
+    fn inspect(&self) -> String {
+        if self.step == 1 {
+            format!("range({}, {})", self.start, self.end)
+        } else {
+            format!("range({}, {}, {})", self.start, self.end, self.step)
+        }
+    }
+}
+
+impl Hash for Range {
+    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {
+        panic!("not able to hash ranges")
+    }
+}
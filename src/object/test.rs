@@ -0,0 +1,210 @@
+use std::collections::{HashMap, HashSet};
+
+use super::convert::{FromHalcon, IntoHalcon};
+use super::*;
+
+#[test]
+fn test_into_halcon() {
+    let mut dict = HashMap::new();
+    dict.insert("key".to_string(), vec![true, false]);
+
+    let tests = vec![
+        (5i64.into_halcon(), "5"),
+        (
+            i128::MAX.into_halcon(),
+            "170141183460469231731687303715884105727",
+        ),
+        (3usize.into_halcon(), "3"),
+        (true.into_halcon(), "true"),
+        ("hola".into_halcon(), "hola"),
+        ("mundo".to_string().into_halcon(), "mundo"),
+        (vec![1, 2, 3].into_halcon(), "[1, 2, 3]"),
+        (dict.into_halcon(), "{key: [true, false]}"),
+        (Some(1).into_halcon(), "1"),
+        (None::<i64>.into_halcon(), "null"),
+        ((1, "a", false).into_halcon(), "(1, a, false)"),
+        (IndexSet::from([3, 1, 3]).into_halcon(), "#{3, 1}"),
+        (BigInt::from(7).into_halcon(), "7"),
+        (
+            (&BigInt::from(i128::MAX) + &BigInt::from(1)).into_halcon(),
+            "170141183460469231731687303715884105728",
+        ),
+        (().into_halcon(), "null"),
+    ];
+
+    for (obj, expected) in tests {
+        assert_eq!(expected, obj.inspect());
+    }
+}
+
+#[test]
+fn test_into_halcon_unhashable_keys() {
+    let tests = vec![
+        (
+            HashSet::from([vec![1, 2]]).into_halcon(),
+            "ERROR: TypeError: unusable as hash key: ARRAY",
+        ),
+        (
+            HashMap::from([(vec![1], 1)]).into_halcon(),
+            "ERROR: TypeError: unusable as hash key: ARRAY",
+        ),
+        (
+            IndexMap::from([((1, vec![true]), 1)]).into_halcon(),
+            "ERROR: TypeError: unusable as hash key: TUPLE",
+        ),
+        (IndexSet::from([(1, "a")]).into_halcon(), "#{(1, a)}"),
+    ];
+
+    for (obj, expected) in tests {
+        assert_eq!(expected, obj.inspect());
+    }
+}
+
+#[test]
+fn test_from_halcon() {
+    let int = |value| ObjectType::Integer(Integer { value });
+    let string = |value: &str| {
+        ObjectType::String(StringObject {
+            value: value.into(),
+        })
+    };
+    let array = |elements| {
+        ObjectType::Array(Array {
+            elements: Rc::new(elements),
+        })
+    };
+
+    assert_eq!(Ok(5), i64::from_halcon(int(5)));
+    assert_eq!(Ok(5), usize::from_halcon(int(5)));
+    assert_eq!(Ok(true), bool::from_halcon(true.into_halcon()));
+    assert_eq!(Ok("hola".to_string()), String::from_halcon(string("hola")));
+    assert_eq!(
+        Ok(vec![1, 2]),
+        Vec::<i32>::from_halcon(array(vec![int(1), int(2)]))
+    );
+    assert_eq!(Ok(None), Option::<i64>::from_halcon(ObjectType::Null));
+    assert_eq!(Ok(Some(3)), Option::<i64>::from_halcon(int(3)));
+    assert_eq!(
+        Ok((1, "a".to_string())),
+        <(i64, String)>::from_halcon(array(vec![int(1), string("a")]))
+    );
+    assert_eq!(
+        Ok((1, "a".to_string())),
+        <(i64, String)>::from_halcon((1, "a").into_halcon())
+    );
+    assert_eq!(
+        Ok(HashSet::from([1, 2])),
+        HashSet::<i64>::from_halcon(IndexSet::from([2, 1]).into_halcon())
+    );
+    assert_eq!(Ok(BigInt::from(5)), BigInt::from_halcon(int(5)));
+
+    let mut pairs = IndexMap::new();
+    pairs.insert(string("a"), int(1));
+    let mut expected = HashMap::new();
+    expected.insert("a".to_string(), 1);
+    assert_eq!(
+        Ok(expected),
+        HashMap::<String, i64>::from_halcon(ObjectType::Dict(Dict {
+            pairs: Rc::new(pairs)
+        }))
+    );
+}
+
+#[test]
+fn test_from_halcon_errors() {
+    let int = |value| ObjectType::Integer(Integer { value });
+
+    let tests = vec![
+        (
+            i64::from_halcon(true.into_halcon()).err(),
+            "expected INTEGER, got BOOLEAN",
+        ),
+        (
+            i64::from_halcon(int(i128::MAX)).err(),
+            "integer 170141183460469231731687303715884105727 does not fit in i64",
+        ),
+        (
+            i128::from_halcon((&BigInt::from(i128::MIN) - &BigInt::from(1)).into_halcon()).err(),
+            "integer -170141183460469231731687303715884105729 does not fit in i128",
+        ),
+        (
+            usize::from_halcon(int(-1)).err(),
+            "integer -1 does not fit in usize",
+        ),
+        (
+            String::from_halcon(int(1)).err(),
+            "expected STRING, got INTEGER",
+        ),
+        (
+            Vec::<i64>::from_halcon(vec![int(1), "a".into_halcon()].into_halcon()).err(),
+            "element 1: expected INTEGER, got STRING",
+        ),
+        (
+            <(i64, i64)>::from_halcon(vec![1, 2, 3].into_halcon()).err(),
+            "expected a TUPLE or ARRAY of 2 elements, got ARRAY",
+        ),
+        (
+            Option::<bool>::from_halcon(int(1)).err(),
+            "expected BOOLEAN, got INTEGER",
+        ),
+    ];
+
+    for (error, expected) in tests {
+        let error = error.expect("the conversion should fail");
+        assert_eq!(TYPE_ERROR, error.kind);
+        assert_eq!(expected, error.message);
+    }
+}
+
+#[test]
+fn test_copies_share_storage() {
+    let original = ObjectType::Array(Array {
+        elements: Rc::new(vec![ObjectType::Integer(Integer { value: 1 })]),
+    });
+    let mut copy = original.clone();
+
+    match (&original, &mut copy) {
+        (ObjectType::Array(original), ObjectType::Array(copy)) => {
+            assert!(Rc::ptr_eq(&original.elements, &copy.elements));
+
+            Rc::make_mut(&mut copy.elements).push(ObjectType::Null);
+            assert!(!Rc::ptr_eq(&original.elements, &copy.elements));
+            assert_eq!(1, original.elements.len());
+            assert_eq!(2, copy.elements.len());
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_tuple_hash() {
+    let tuple = |elements: Vec<ObjectType>| {
+        ObjectType::Tuple(Tuple {
+            elements: elements.into(),
+        })
+    };
+    let int = |value| ObjectType::Integer(Integer { value });
+    let string = |value: &str| {
+        ObjectType::String(StringObject {
+            value: value.into(),
+        })
+    };
+
+    let mut pairs = IndexMap::new();
+    pairs.insert(tuple(vec![int(1), int(2)]), string("a"));
+    pairs.insert(tuple(vec![int(1), string("2")]), string("b"));
+    pairs.insert(tuple(vec![int(1), int(2)]), string("c"));
+
+    assert_eq!(2, pairs.len());
+    assert_eq!(Some(&string("c")), pairs.get(&tuple(vec![int(1), int(2)])));
+    assert_eq!(None, pairs.get(&tuple(vec![int(2), int(1)])));
+
+    assert!(tuple(vec![int(1), tuple(vec![string("a")])]).is_hashable());
+    assert!(!tuple(vec![
+        int(1),
+        ObjectType::Array(Array {
+            elements: Rc::new(vec![]),
+        })
+    ])
+    .is_hashable());
+}
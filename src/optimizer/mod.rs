@@ -0,0 +1,322 @@
+use std::rc::Rc;
+
+use crate::ast::expressions::{Boolean, IfExpression, IntegerLiteral, StringLiteral};
+use crate::ast::statements::BlockStatement;
+use crate::ast::{AstNode, Program};
+use crate::object::{self, Integer, ObjectType, StringObject};
+use crate::token::Token;
+
+#[cfg(test)]
+mod test;
+
+/**
+Optimizes a program before it runs. Prefix and infix expressions whose operands are literals are replaced
+with their value, and the branches of if expressions whose conditions are literals are pruned.
+Expressions that would raise an error, like a division by zero or an integer overflow, are kept so that they
+raise it when the program runs
+
+# Arguments
+* `program` - the program to optimize
+
+# Examples
+```
+optimizer::optimize(&mut program);
+```
+*/
+pub fn optimize(program: &mut Program) {
+    statements(&mut program.statements);
+}
+
+fn statements(statements: &mut [AstNode]) {
+    for statement in statements {
+        node(statement);
+    }
+}
+
+fn block(block: &mut BlockStatement) {
+    statements(&mut block.statements);
+}
+
+/**
+Optimizes a node and the nodes it contains
+
+# Arguments
+* `node` - the node to optimize
+*/
+fn node(node: &mut AstNode) {
+    match node {
+        AstNode::Program(program) => statements(&mut program.statements),
+        AstNode::PrefixExpression(prefix_expression) => {
+            self::node(&mut prefix_expression.right);
+            let folded = literal(&prefix_expression.right)
+                .and_then(|right| fold_prefix(&prefix_expression.operator, right));
+            if let Some(folded) = folded {
+                *node = folded;
+            }
+        }
+        AstNode::InfixExpression(infix_expression) => {
+            self::node(&mut infix_expression.left);
+            self::node(&mut infix_expression.right);
+            let folded = literal(&infix_expression.left)
+                .zip(literal(&infix_expression.right))
+                .and_then(|(left, right)| fold_infix(left, &infix_expression.operator, right));
+            if let Some(folded) = folded {
+                *node = folded;
+            }
+        }
+        AstNode::IfExpression(if_expression) => {
+            self::node(&mut if_expression.condition);
+            block(&mut if_expression.consequence);
+            for (condition, consequence) in &mut if_expression.elifs {
+                self::node(condition);
+                block(consequence);
+            }
+            if let Some(alternative) = &mut if_expression.alternative {
+                block(alternative);
+            }
+
+            if let AstNode::IfExpression(if_expression) = std::mem::replace(node, AstNode::Break) {
+                *node = prune(if_expression);
+            }
+        }
+        AstNode::TryExpression(try_expression) => {
+            block(&mut try_expression.block);
+            if let Some((_, handler)) = &mut try_expression.catch {
+                block(handler);
+            }
+            if let Some(finally) = &mut try_expression.finally {
+                block(finally);
+            }
+        }
+        AstNode::FunctionLiteral(function_literal) => {
+            block(Rc::make_mut(&mut function_literal.body))
+        }
+        AstNode::ArrayLiteral(array_literal) => statements(&mut array_literal.elements),
+        AstNode::TupleLiteral(tuple_literal) => statements(&mut tuple_literal.elements),
+        AstNode::SetLiteral(set_literal) => statements(&mut set_literal.elements),
+        AstNode::DictLiteral(dict) => {
+            for (key, value) in &mut dict.pairs {
+                self::node(key);
+                self::node(value);
+            }
+        }
+        AstNode::IndexExpression(index_expression) => {
+            self::node(&mut index_expression.left);
+            self::node(&mut index_expression.index);
+        }
+        AstNode::PropagationExpression(propagation) => self::node(&mut propagation.value),
+        AstNode::CallExpression(call) => {
+            self::node(&mut call.function);
+            statements(&mut call.arguments);
+        }
+        AstNode::LetStatement(let_statement) => self::node(&mut let_statement.value),
+        AstNode::ReturnStatement(return_statement) => {
+            self::node(&mut return_statement.return_value)
+        }
+        AstNode::ThrowStatement(throw_statement) => self::node(&mut throw_statement.value),
+        AstNode::BlockStatement(block) => self::block(block),
+        AstNode::Assignation(assig) => self::node(&mut assig.value),
+        AstNode::ForLoop(for_loop) => {
+            self::node(&mut for_loop.initialization.value);
+            self::node(&mut for_loop.condition);
+            block(&mut for_loop.statements);
+            self::node(&mut for_loop.step);
+        }
+        AstNode::WhileLoop(while_loop) => {
+            self::node(&mut while_loop.condition);
+            block(&mut while_loop.statements);
+        }
+        AstNode::Loop(loop_exp) => block(&mut loop_exp.statements),
+        AstNode::Identifier(_)
+        | AstNode::IntegerLiteral(_)
+        | AstNode::Boolean(_)
+        | AstNode::StringLiteral(_)
+        | AstNode::PostIncrement(_)
+        | AstNode::PostDecrement(_)
+        | AstNode::Break => {}
+    }
+}
+
+/**
+Removes the branches of an if expression whose conditions are false literals, and the ones after
+a condition that is a true literal, which becomes the else branch. If no condition is left the
+expression is replaced with the block that runs, or with an empty block that evaluates to null
+
+# Arguments
+* `if_expression` - the if expression to prune
+*/
+fn prune(if_expression: IfExpression) -> AstNode {
+    let IfExpression {
+        token,
+        condition,
+        consequence,
+        elifs,
+        mut alternative,
+    } = if_expression;
+
+    let empty = BlockStatement {
+        token: consequence.token.clone(),
+        statements: vec![],
+    };
+
+    let mut branches = vec![];
+    for (condition, consequence) in std::iter::once((*condition, consequence)).chain(elifs) {
+        match literal(&condition) {
+            Some(value) if is_truthy(&value) => {
+                alternative = Some(consequence);
+                break;
+            }
+            Some(_) => {}
+            None => branches.push((condition, consequence)),
+        }
+    }
+
+    if branches.is_empty() {
+        return AstNode::BlockStatement(alternative.unwrap_or(empty));
+    }
+
+    let (condition, consequence) = branches.remove(0);
+    AstNode::IfExpression(IfExpression {
+        token,
+        condition: Box::new(condition),
+        consequence,
+        elifs: branches,
+        alternative,
+    })
+}
+
+/**
+Returns the value of a literal node, or None if the node is not a literal
+
+# Arguments
+* `node` - the node to check
+*/
+fn literal(node: &AstNode) -> Option<ObjectType> {
+    match node {
+        AstNode::IntegerLiteral(IntegerLiteral {
+            token: Token::ConstInt(value),
+        }) => Some(ObjectType::Integer(Integer { value: *value })),
+        AstNode::Boolean(Boolean {
+            token: Token::ConstBool(value),
+        }) => Some(ObjectType::Boolean(object::Boolean { value: *value })),
+        AstNode::StringLiteral(StringLiteral {
+            token: Token::ConstStr(value),
+        }) => Some(ObjectType::String(StringObject {
+            value: value.clone(),
+        })),
+        _ => None,
+    }
+}
+
+/**
+Returns the literal node of a value
+
+# Arguments
+* `value` - an integer, boolean or string
+*/
+fn to_literal(value: ObjectType) -> AstNode {
+    match value {
+        ObjectType::Integer(int) => AstNode::IntegerLiteral(IntegerLiteral {
+            token: Token::ConstInt(int.value),
+        }),
+        ObjectType::Boolean(boolean) => AstNode::Boolean(Boolean {
+            token: Token::ConstBool(boolean.value),
+        }),
+        ObjectType::String(string) => AstNode::StringLiteral(StringLiteral {
+            token: Token::ConstStr(string.value),
+        }),
+        other => panic!("not a literal value: {:?}", other),
+    }
+}
+
+/**
+Returns the literal with the value of a prefix expression, or None if it raises an error.
+Mirrors `Evaluator::eval_prefix_expression`
+
+# Arguments
+* `operator` - the prefix operator
+* `right` - the value of the operand
+*/
+fn fold_prefix(operator: &str, right: ObjectType) -> Option<AstNode> {
+    let value = match (operator, right) {
+        ("not", right) => ObjectType::Boolean(object::Boolean {
+            value: !is_truthy(&right),
+        }),
+        ("-", ObjectType::Integer(int)) => ObjectType::Integer(Integer {
+            value: int.value.checked_neg()?,
+        }),
+        _ => return None,
+    };
+
+    Some(to_literal(value))
+}
+
+/**
+Returns the literal with the value of an infix expression, or None if it raises an error.
+Mirrors `Evaluator::eval_infix_expression`
+
+# Arguments
+* `left` - the value of the left operand
+* `operator` - the infix operator
+* `right` - the value of the right operand
+*/
+fn fold_infix(left: ObjectType, operator: &str, right: ObjectType) -> Option<AstNode> {
+    let int = |value: Option<i128>| value.map(|value| ObjectType::Integer(Integer { value }));
+    let boolean = |value| Some(ObjectType::Boolean(object::Boolean { value }));
+
+    let value = match (left, right) {
+        (ObjectType::Integer(left), ObjectType::Integer(right)) => {
+            let (left, right) = (left.value, right.value);
+            match operator {
+                "+" => int(left.checked_add(right)),
+                "-" => int(left.checked_sub(right)),
+                "*" => int(left.checked_mul(right)),
+                "/" => int(left.checked_div(right)),
+                "%" => int(left.checked_rem(right)),
+                "<" => boolean(left < right),
+                ">" => boolean(left > right),
+                "<=" => boolean(left <= right),
+                ">=" => boolean(left >= right),
+                "==" => boolean(left == right),
+                "!=" => boolean(left != right),
+                _ => None,
+            }
+        }
+        (ObjectType::String(left), ObjectType::String(right)) => {
+            let (left, right) = (left.value, right.value);
+            match operator {
+                "+" => Some(ObjectType::String(StringObject {
+                    value: format!("{}{}", left, right).into(),
+                })),
+                "<" => boolean(left < right),
+                ">" => boolean(left > right),
+                "<=" => boolean(left <= right),
+                ">=" => boolean(left >= right),
+                "==" => boolean(left == right),
+                "!=" => boolean(left != right),
+                _ => None,
+            }
+        }
+        (ObjectType::Boolean(left), ObjectType::Boolean(right)) => match operator {
+            "==" => boolean(left.value == right.value),
+            "!=" => boolean(left.value != right.value),
+            _ => None,
+        },
+        _ => None,
+    }?;
+
+    Some(to_literal(value))
+}
+
+/**
+Returns if a literal value is truthy, like `is_truthy` in the evaluator
+
+# Arguments
+* `value` - the value to check
+*/
+fn is_truthy(value: &ObjectType) -> bool {
+    !matches!(
+        value,
+        ObjectType::Boolean(object::Boolean { value: false }) | ObjectType::Null
+    )
+}
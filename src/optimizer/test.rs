@@ -0,0 +1,84 @@
+use super::*;
+use crate::ast::Node;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+
+#[test]
+fn test_fold_constants() {
+    let tests = vec![
+        ("60 * 60 * 24", "86400"),
+        ("-5 + 2", "-3"),
+        ("10 / 3 - 10 % 3", "2"),
+        (r#""hola" + " " + "mundo""#, "hola mundo"),
+        ("1 < 2 == true", "true"),
+        ("not (1 > 2)", "true"),
+        ("not 0", "false"),
+        ("true != false", "true"),
+        (r#""a" == "a""#, "true"),
+        (r#""abc" < "abd""#, "true"),
+        ("x + 2 * 3", "(x + 6)"),
+        ("2 * 3 + x", "(6 + x)"),
+        ("[1 + 1, fun() { 2 * 2 }]", "[2, fun() {4}]"),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, optimized(input), "{}", input);
+    }
+}
+
+#[test]
+fn test_errors_are_not_folded() {
+    let tests = vec![
+        ("1 / 0", "(1 / 0)"),
+        ("5 % (2 - 2)", "(5 % 0)"),
+        ("1 + true", "(1 + true)"),
+        ("-true", "(-true)"),
+        (r#""a" - "b""#, "(a - b)"),
+        ("true < false", "(true < false)"),
+        (
+            "170141183460469231731687303715884105727 + 1",
+            "(170141183460469231731687303715884105727 + 1)",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, optimized(input), "{}", input);
+    }
+}
+
+#[test]
+fn test_prune_branches() {
+    let tests = vec![
+        ("if (true) { 1 } else { 2 }", "1"),
+        ("if (1 > 2) { 1 } else { 2 }", "2"),
+        ("if (false) { 1 }", ""),
+        ("if (0) { 1 }", "1"),
+        ("if (x) { 1 } else { 2 }", "ifx 1 else 2"),
+        ("if (false) { 1 } elif (x) { 2 } else { 3 }", "ifx 2 else 3"),
+        (
+            "if (x) { 1 } elif (false) { 2 } elif (1 == 1) { 3 } else { 4 }",
+            "ifx 1 else 3",
+        ),
+        ("if (x) { 1 } elif (y) { 2 }", "ifx 1 elif y 2"),
+        (
+            "let f = fun() { if (false) { 1 } else { 60 * 60 } };",
+            "let f = fun() {3600};",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(expected, optimized(input), "{}", input);
+    }
+}
+
+//-------------------[Test helpers]-------------------//
+
+fn optimized(input: &str) -> String {
+    match Parser::new(Lexer::new(input.to_string())).parse_program() {
+        AstNode::Program(mut program) => {
+            optimize(&mut program);
+            program.string()
+        }
+        other => panic!("Expected a program, got {:?}", other),
+    }
+}
@@ -1,14 +1,14 @@
-use std::collections::HashMap;
+use std::rc::Rc;
 
 use crate::ast::expressions::*;
-use crate::ast::statements::{Assignation, BlockStatement, Operation};
+use crate::ast::statements::{Assignation, BlockStatement, Operation, ThrowStatement};
 use crate::ast::{
     expressions::Identifier, statements::LetStatement, statements::ReturnStatement, Program,
 };
 use crate::ast::{AstNode, Node};
 use crate::lexer::Lexer;
 use crate::parser::precedence::Precedence;
-use crate::token::Token;
+use crate::token::{Span, Token};
 
 #[cfg(test)]
 mod test;
@@ -23,6 +23,10 @@ pub struct Parser {
     current_token: Token,
     peek_token: Token,
 
+    /// The positions of `current_token` and `peek_token` in the source code
+    current_span: Span,
+    peek_span: Span,
+
     /// Stores a list of the errors encountered
     errors: Vec<String>,
 }
@@ -46,6 +50,8 @@ impl Parser {
             lex,
             current_token: Token::Invalid(b'0'.to_string()),
             peek_token: Token::Invalid(b'0'.to_string()),
+            current_span: Span::default(),
+            peek_span: Span::default(),
             errors: vec![],
         };
 
@@ -86,7 +92,9 @@ impl Parser {
     */
     fn next_token(&mut self) {
         self.current_token = self.peek_token.clone();
+        self.current_span = self.peek_span;
         self.peek_token = self.lex.next_token();
+        self.peek_span = self.lex.token_span();
     }
 
     /**
@@ -108,9 +116,8 @@ impl Parser {
         let mut program = Program { statements: vec![] };
 
         while self.current_token != Token::Eof {
-            match self.parse_statement() {
-                Ok(statement) => program.statements.push(statement),
-                Err(_) => (),
+            if let Ok(statement) = self.parse_statement() {
+                program.statements.push(statement)
             };
             self.next_token();
         }
@@ -120,7 +127,7 @@ impl Parser {
 
     /**
     Parses a statement and returns an `AstNode` containing it
-    Valid statements are the Let statement and the Return statement
+    Valid statements are the Let statement, the Return statement and the Throw statement
 
     # Arguments
 
@@ -139,6 +146,7 @@ impl Parser {
         match self.current_token {
             Token::Let => Ok(AstNode::LetStatement(self.parse_let_statement()?)),
             Token::Return => Ok(AstNode::ReturnStatement(self.parse_return_statement()?)),
+            Token::Throw => Ok(AstNode::ThrowStatement(self.parse_throw_statement()?)),
             Token::Break => {
                 self.next_token();
                 Ok(AstNode::Break)
@@ -166,12 +174,13 @@ impl Parser {
     fn parse_let_statement(&mut self) -> Result<LetStatement, MyParseError> {
         let tok = &self.current_token.clone();
 
-        if !self.expect_peek(Token::Id("".to_string())) {
+        if !self.expect_peek(Token::Id("".into())) {
             return Err(MyParseError);
         }
 
         let name = Identifier {
             token: self.current_token.clone(),
+            resolution: None,
         };
 
         self.expect_peek(Token::Assig);
@@ -218,6 +227,33 @@ impl Parser {
         })
     }
 
+    /**
+    Parses the throw statement and returns a `ThrowStatement` struct containing the information
+    the throw statement is parsed as the following
+    'throw x;'
+
+    # Arguments
+
+    no arguments
+
+    */
+    fn parse_throw_statement(&mut self) -> Result<ThrowStatement, MyParseError> {
+        let tok = &self.current_token.clone();
+
+        self.next_token();
+
+        let expression = self.parse_expression(Precedence::Lowest)?;
+
+        if self.peek_token_is(Token::Semicolon) {
+            self.next_token();
+        }
+
+        Ok(ThrowStatement {
+            token: tok.clone(),
+            value: Box::new(expression),
+        })
+    }
+
     /**
     Parses an expression and returns an `AstNode` containing it. Anything that is not a statement is an expression
 
@@ -226,11 +262,12 @@ impl Parser {
     * `precedence` - the active precedence
     */
     fn parse_expression(&mut self, precedence: Precedence) -> Result<AstNode, MyParseError> {
+        let start = self.current_span;
         let mut left_expression = self.execute_prefix_parse_function()?;
 
         while !self.peek_token_is(Token::Semicolon) && precedence < self.peek_precedence() {
             self.next_token();
-            left_expression = self.execute_infix_parse_function(left_expression)?;
+            left_expression = self.execute_infix_parse_function(left_expression, start)?;
         }
 
         Ok(left_expression)
@@ -242,6 +279,7 @@ impl Parser {
     * Numbers
     * Booleans
     * If else statement
+    * Try catch finally statement
     * Grouped expressions
     * function literals
     * Strings
@@ -254,19 +292,23 @@ impl Parser {
     */
     fn execute_prefix_parse_function(&mut self) -> Result<AstNode, MyParseError> {
         match &self.current_token {
-            Token::Id(id) => Ok(self.parse_identifier(id.to_string())?),
-            Token::ConstInt(num) => Ok(self.parse_integer_literal(*num)?),
+            Token::Id(id) => Ok(self.parse_identifier(id.clone())?),
+            Token::ConstInt(_) | Token::ConstBigInt(_) => {
+                Ok(self.parse_integer_literal(self.current_token.clone())?)
+            }
             Token::Not | Token::Minus => Ok(self.parse_prefix_expression()?),
             Token::ConstBool(b) => Ok(self.parse_boolean(*b)?),
             Token::Opar => Ok(self.parse_grouped_expression()?),
             Token::If => Ok(self.parse_if_expression()?),
+            Token::Try => Ok(self.parse_try_expression()?),
             Token::For => Ok(self.parse_for_expression()?),
             Token::While => Ok(self.parse_while_expression()?),
             Token::Loop => Ok(self.parse_loop_expression()?),
             Token::Fun => Ok(self.parse_function_literal()?),
-            Token::ConstStr(s) => Ok(self.parse_string_literal(s.to_string())?),
+            Token::ConstStr(s) => Ok(self.parse_string_literal(s.clone())?),
             Token::Obrac => Ok(self.parse_array_literal()?),
             Token::Okey => Ok(self.parse_dict_literal()?),
+            Token::Oset => Ok(self.parse_set_literal()?),
             _ => {
                 self.no_prefix_function_error(self.current_token.clone());
                 Err(MyParseError)
@@ -299,24 +341,27 @@ impl Parser {
     Parses an identifier and returns an `AstNode::Identifier`
 
     # Arguments
-    * `id` - The `String` containing the id to parse
+    * `id` - The id to parse
 
     */
-    fn parse_identifier(&mut self, id: String) -> Result<AstNode, MyParseError> {
+    fn parse_identifier(&mut self, id: Rc<str>) -> Result<AstNode, MyParseError> {
         if self.peek_token_is(Token::Inc) {
             self.next_token();
 
             Ok(AstNode::PostIncrement(PostIncrement {
-                token: Token::Id(id.to_string()),
+                token: Token::Id(id.clone()),
+                resolution: None,
             }))
         } else if self.peek_token_is(Token::Dec) {
             self.next_token();
             Ok(AstNode::PostDecrement(PostDecrement {
-                token: Token::Id(id.to_string()),
+                token: Token::Id(id.clone()),
+                resolution: None,
             }))
         } else {
             Ok(AstNode::Identifier(Identifier {
-                token: Token::Id(id.to_string()),
+                token: Token::Id(id.clone()),
+                resolution: None,
             }))
         }
     }
@@ -325,13 +370,11 @@ impl Parser {
     Parses an integer and returns an `AstNode::IntegerLiteral`
 
     # Arguments
-    * `num` - The `i128` containing the integer to parse
+    * `token` - The `Token::ConstInt` or `Token::ConstBigInt` containing the integer to parse
 
     */
-    fn parse_integer_literal(&self, num: i128) -> Result<AstNode, MyParseError> {
-        Ok(AstNode::IntegerLiteral(IntegerLiteral {
-            token: Token::ConstInt(num),
-        }))
+    fn parse_integer_literal(&self, token: Token) -> Result<AstNode, MyParseError> {
+        Ok(AstNode::IntegerLiteral(IntegerLiteral { token }))
     }
 
     /**
@@ -351,10 +394,10 @@ impl Parser {
     Parses a string literal and returns an `AstNode::StringLiteral`
 
     # Arguments
-    * `s` - The `String` containing the string to parse
+    * `s` - The string to parse
 
     */
-    fn parse_string_literal(&self, s: String) -> Result<AstNode, MyParseError> {
+    fn parse_string_literal(&self, s: Rc<str>) -> Result<AstNode, MyParseError> {
         Ok(AstNode::StringLiteral(StringLiteral {
             token: Token::ConstStr(s),
         }))
@@ -363,19 +406,50 @@ impl Parser {
     /**
     Parses a grouped expression and returns the corresponding `AstNode` containing the information
     A grouped expression is an expression surrounded by ()
+    If the parentheses are empty or the expression is followed by a comma a `AstNode::TupleLiteral`
+    is returned instead, parsed as
+    (<exp>, <exp>...)
 
     # Arguments
     no arguments
 
     */
     fn parse_grouped_expression(&mut self) -> Result<AstNode, MyParseError> {
+        let tok_tuple = self.current_token.clone();
+
+        if self.peek_token_is(Token::Cpar) {
+            self.next_token();
+            return Ok(AstNode::TupleLiteral(TupleLiteral {
+                token: tok_tuple,
+                elements: vec![],
+            }));
+        }
+
         self.next_token();
 
         let exp = self.parse_expression(Precedence::Lowest)?;
 
+        if !self.peek_token_is(Token::Coma) {
+            self.expect_peek(Token::Cpar);
+            return Ok(exp);
+        }
+
+        let mut elements = vec![exp];
+        while self.peek_token_is(Token::Coma) {
+            self.next_token();
+            if self.peek_token_is(Token::Cpar) {
+                break;
+            }
+            self.next_token();
+            elements.push(self.parse_expression(Precedence::Lowest)?);
+        }
+
         self.expect_peek(Token::Cpar);
 
-        Ok(exp)
+        Ok(AstNode::TupleLiteral(TupleLiteral {
+            token: tok_tuple,
+            elements,
+        }))
     }
 
     /**
@@ -438,6 +512,64 @@ impl Parser {
         }
     }
 
+    /**
+    Parses a try expressin and returns an `AstNode::TryExpression`
+    A try expression is parsed as
+    try { <exp> } catch (<id>) { <exp> } finally { <exp> }
+    where at least one of the catch or finally blocks must be present
+
+    # Arguments
+    no arguments
+    */
+    fn parse_try_expression(&mut self) -> Result<AstNode, MyParseError> {
+        let try_token = self.current_token.clone();
+
+        self.expect_peek(Token::Okey);
+
+        let block = self.parse_block_statement()?;
+
+        let catch = if self.peek_token_is(Token::Catch) {
+            self.next_token();
+            self.expect_peek(Token::Opar);
+            if !self.expect_peek(Token::Id("".into())) {
+                return Err(MyParseError);
+            }
+            let parameter = Identifier {
+                token: self.current_token.clone(),
+                resolution: None,
+            };
+            self.expect_peek(Token::Cpar);
+
+            self.expect_peek(Token::Okey);
+
+            Some((parameter, self.parse_block_statement()?))
+        } else {
+            None
+        };
+
+        let finally = if self.peek_token_is(Token::Finally) {
+            self.next_token();
+            self.expect_peek(Token::Okey);
+
+            Some(self.parse_block_statement()?)
+        } else {
+            None
+        };
+
+        if catch.is_none() && finally.is_none() {
+            self.errors
+                .push("expected catch or finally after try block".to_string());
+            return Err(MyParseError);
+        }
+
+        Ok(AstNode::TryExpression(TryExpression {
+            token: try_token,
+            block,
+            catch,
+            finally,
+        }))
+    }
+
     /**
     Parses a for loop expression and returns an `AstNode::ForLoop`
     A for loop expression is parsed as
@@ -540,8 +672,9 @@ impl Parser {
 
         Ok(AstNode::FunctionLiteral(FunctionLiteral {
             token: func_tok,
-            parameters,
-            body: block,
+            parameters: Rc::new(parameters),
+            body: Rc::new(block),
+            scope: None,
         }))
     }
 
@@ -564,6 +697,7 @@ impl Parser {
 
         let ident = Identifier {
             token: self.current_token.clone(),
+            resolution: None,
         };
 
         identifiers.push(ident);
@@ -573,6 +707,7 @@ impl Parser {
             self.next_token();
             let ident = Identifier {
                 token: self.current_token.clone(),
+                resolution: None,
             };
             identifiers.push(ident);
         }
@@ -627,6 +762,25 @@ impl Parser {
         }))
     }
 
+    /**
+    Parses a set literal and returns a `AstNode::SetLiteral`
+    A set expression is parsed as
+    #{<exp>, <exp>...}
+
+    # Arguments
+    no arguments
+    */
+    fn parse_set_literal(&mut self) -> Result<AstNode, MyParseError> {
+        let tok_set = self.current_token.clone();
+
+        let elements = self.parse_expression_list(Token::Ckey)?;
+
+        Ok(AstNode::SetLiteral(SetLiteral {
+            token: tok_set,
+            elements,
+        }))
+    }
+
     /**
     Parses an dict literal and returns a `AstNode::DictLiteral`
     An dict expression is parsed as
@@ -638,7 +792,7 @@ impl Parser {
     fn parse_dict_literal(&mut self) -> Result<AstNode, MyParseError> {
         let dict_tock = self.current_token.clone();
 
-        let mut pairs: HashMap<AstNode, AstNode> = HashMap::new();
+        let mut pairs = vec![];
 
         while !self.peek_token_is(Token::Ckey) {
             self.next_token();
@@ -649,7 +803,7 @@ impl Parser {
             self.next_token();
             let value = self.parse_expression(Precedence::Lowest)?;
 
-            pairs.insert(key, value);
+            pairs.push((key, value));
 
             if !self.peek_token_is(Token::Ckey) && !self.expect_peek(Token::Coma) {
                 return Err(MyParseError);
@@ -670,12 +824,18 @@ impl Parser {
     * Math operators
     * call expressios
     * index expressions
+    * propagation expressions
 
     # Arguments
 
     * `left` - the `AstNode` to the left of the operator
+    * `start` - the position where the `left` expression starts
     */
-    fn execute_infix_parse_function(&mut self, left: AstNode) -> Result<AstNode, MyParseError> {
+    fn execute_infix_parse_function(
+        &mut self,
+        left: AstNode,
+        start: Span,
+    ) -> Result<AstNode, MyParseError> {
         match &self.current_token {
             Token::Eq
             | Token::Neq
@@ -683,6 +843,8 @@ impl Parser {
             | Token::Gt
             | Token::Ge
             | Token::Le
+            | Token::In
+            | Token::NotIn
             | Token::Plus
             | Token::Minus
             | Token::Div
@@ -691,8 +853,9 @@ impl Parser {
             Token::Assig | Token::DivAsig | Token::SumAsig | Token::MinAsig | Token::MulAsig => {
                 Ok(self.parse_assignation_expression(left)?)
             }
-            Token::Opar => Ok(self.parse_call_expression(left)?),
+            Token::Opar => Ok(self.parse_call_expression(left, start)?),
             Token::Obrac => Ok(self.parse_index_expression(left)?),
+            Token::Question => Ok(self.parse_propagation_expression(left)),
             _ => {
                 self.no_infix_fn_error(self.current_token.clone());
                 Err(MyParseError)
@@ -748,10 +911,8 @@ impl Parser {
             Token::DivAsig => Operation::Divide,
 
             other => {
-                self.errors.push(format!(
-                    "{} is not a valid assignation type",
-                    other.to_string()
-                ));
+                self.errors
+                    .push(format!("{} is not a valid assignation type", other));
                 return Err(MyParseError);
             }
         };
@@ -795,18 +956,38 @@ impl Parser {
         }))
     }
 
+    /**
+    Parses a propagation expression and returns the corresponding `AstNode`
+    Propagation expressions unwrap an ok result or return an err result from the enclosing function: result?
+
+    # Arguments
+    * `left` - the expression to be unwrapped
+    */
+    fn parse_propagation_expression(&self, left: AstNode) -> AstNode {
+        AstNode::PropagationExpression(PropagationExpression {
+            token: self.current_token.clone(),
+            value: Box::new(left),
+        })
+    }
+
     /**
     Parses a call expression and returns the corresponding `AstNode`
     Call expressions are expressions that call a function: function(a);
 
     # Arguments
     * `function` - the function `AstNode` to call
+    * `span` - the position where the `function` expression starts
     */
-    fn parse_call_expression(&mut self, function: AstNode) -> Result<AstNode, MyParseError> {
+    fn parse_call_expression(
+        &mut self,
+        function: AstNode,
+        span: Span,
+    ) -> Result<AstNode, MyParseError> {
         Ok(AstNode::CallExpression(CallExpression {
             token: self.current_token.clone(),
             function: Box::new(function),
             arguments: self.parse_expression_list(Token::Cpar)?,
+            span,
         }))
     }
 
@@ -855,7 +1036,7 @@ impl Parser {
 
         self.peek_error(tok);
 
-        return false;
+        false
     }
 
     /**
@@ -948,12 +1129,15 @@ impl Parser {
             Token::Gt => Precedence::LessGreater,
             Token::Le => Precedence::LessGreater,
             Token::Ge => Precedence::LessGreater,
+            Token::In => Precedence::LessGreater,
+            Token::NotIn => Precedence::LessGreater,
             Token::Plus => Precedence::Sum,
             Token::Minus => Precedence::Sum,
             Token::Div => Precedence::Product,
             Token::Mult => Precedence::Product,
             Token::Mod => Precedence::Product,
             Token::Opar => Precedence::Call,
+            Token::Question => Precedence::Call,
             Token::Obrac => Precedence::Index,
             Token::Assig => Precedence::Assig,
             Token::SumAsig => Precedence::Assig,
@@ -57,16 +57,19 @@ fn test_string() {
             AstNode::LetStatement(LetStatement {
                 token: Token::Let,
                 name: Identifier {
-                    token: Token::Id("myVar".to_string()),
+                    token: Token::Id("myVar".into()),
+                    resolution: None,
                 },
                 value: Box::new(AstNode::Identifier(Identifier {
-                    token: Token::Id("otherVar".to_string()),
+                    token: Token::Id("otherVar".into()),
+                    resolution: None,
                 })),
             }),
             AstNode::ReturnStatement(ReturnStatement {
                 token: Token::Return,
                 return_value: Box::new(AstNode::Identifier(Identifier {
-                    token: Token::Id("myVar".to_string()),
+                    token: Token::Id("myVar".into()),
+                    resolution: None,
                 })),
             }),
         ],
@@ -106,7 +109,7 @@ fn test_integer_literal_expression() {
 
     let exp = &program.statements[0];
 
-    test_int_literal(&exp, "5");
+    test_int_literal(exp, "5");
 }
 
 #[test]
@@ -125,7 +128,7 @@ fn test_boolean_literal_expression() {
 
         let exp = &program.statements[0];
 
-        test_boolean(&exp, expected);
+        test_boolean(exp, expected);
     }
 }
 
@@ -148,7 +151,7 @@ fn test_parse_prefix_expressions() {
 
         let exp = &program.statements[0];
 
-        test_prefix_expression(&exp, operator, right_expected);
+        test_prefix_expression(exp, operator, right_expected);
     }
 }
 
@@ -183,7 +186,7 @@ fn test_parse_infix_expressions() {
 
         let exp = &program.statements[0];
 
-        test_infix_expression(&exp, left_expected, operator, right_expected);
+        test_infix_expression(exp, left_expected, operator, right_expected);
     }
 }
 
@@ -209,6 +212,8 @@ fn test_operator_precedence_parsing() {
         ("5 < 4 != true", "((5 < 4) != true)"),
         ("5 < 4 == false", "((5 < 4) == false)"),
         ("a + (b + c) + d", "((a + (b + c)) + d)"),
+        ("a + 1 in b == true", "(((a + 1) in b) == true)"),
+        ("a not in b != c < d", "((a not in b) != (c < d))"),
         ("(4 + 4) * 3", "((4 + 4) * 3)"),
         ("2 / (3 + 4)", "(2 / (3 + 4))"),
         ("-(5 + 5)", "(-(5 + 5))"),
@@ -344,7 +349,7 @@ fn test_if_expression() {
 
     let consequence = &if_expression.consequence.statements[0];
 
-    test_identifier(&consequence, "x")
+    test_identifier(consequence, "x")
 }
 
 #[test]
@@ -372,7 +377,7 @@ fn test_if_else_expression() {
 
     let consequence = &if_expression.consequence.statements[0];
 
-    test_identifier(&consequence, "x");
+    test_identifier(consequence, "x");
 
     let alternative_block = match &if_expression.alternative {
         Some(alt) => alt,
@@ -383,7 +388,7 @@ fn test_if_else_expression() {
 
     let alternative = &alternative_block.statements[0];
 
-    test_identifier(&alternative, "y")
+    test_identifier(alternative, "y")
 }
 
 #[test]
@@ -411,7 +416,7 @@ fn test_if_elif_else_expression() {
 
     let consequence = &if_expression.consequence.statements[0];
 
-    test_identifier(&consequence, "x");
+    test_identifier(consequence, "x");
 
     let elifs = &if_expression.elifs;
 
@@ -425,7 +430,7 @@ fn test_if_elif_else_expression() {
 
     let consequence = &elif_cons.statements[0];
 
-    test_identifier(&consequence, "x");
+    test_identifier(consequence, "x");
 
     let alternative_block = match &if_expression.alternative {
         Some(alt) => alt,
@@ -436,7 +441,7 @@ fn test_if_elif_else_expression() {
 
     let alternative = &alternative_block.statements[0];
 
-    test_identifier(&alternative, "y")
+    test_identifier(alternative, "y")
 }
 
 #[test]
@@ -467,7 +472,7 @@ fn test_function_literal_parsing() {
 
     let exp = &function.body.statements[0];
 
-    test_infix_expression(&exp, "x", "+", "y");
+    test_infix_expression(exp, "x", "+", "y");
 }
 
 #[test]
@@ -528,6 +533,44 @@ fn test_call_expresion_parsing() {
     test_infix_expression(&exp.arguments[2], "4", "*", "5");
 }
 
+#[test]
+fn test_call_expression_span() {
+    let input = "let x = 1 +
+    add(1, sub(2));";
+
+    let lex = Lexer::new(input.to_string());
+    let mut parser = Parser::new(lex);
+    let binding = parser.parse_program();
+    let program = get_program(&binding);
+
+    check_parse_errors(parser);
+
+    let statement = match &program.statements[0] {
+        AstNode::LetStatement(statement) => statement,
+        actual => panic!("Expected a let statement, got {:?}", actual),
+    };
+    let add = match statement.value.as_ref() {
+        AstNode::InfixExpression(infix) => match infix.right.as_ref() {
+            AstNode::CallExpression(call) => call.clone(),
+            actual => panic!("Expected a call expression, got {:?}", actual),
+        },
+        actual => panic!("Expected an infix expression, got {:?}", actual),
+    };
+    let sub = match &add.arguments[1] {
+        AstNode::CallExpression(call) => call.clone(),
+        actual => panic!("Expected a call expression, got {:?}", actual),
+    };
+
+    assert_eq!(Span { line: 2, column: 5 }, add.span);
+    assert_eq!(
+        Span {
+            line: 2,
+            column: 12
+        },
+        sub.span
+    );
+}
+
 #[test]
 fn test_string_literal_expression() {
     let input = r#""hello world""#;
@@ -541,7 +584,7 @@ fn test_string_literal_expression() {
 
     let exp = &program.statements[0];
 
-    test_string_literal(&exp, "hello world")
+    test_string_literal(exp, "hello world")
 }
 
 #[test]
@@ -558,13 +601,64 @@ fn test_parse_array() {
 
     let exp = &program.statements[0];
 
-    let elem = test_array(&exp, 3);
+    let elem = test_array(exp, 3);
 
     test_int_literal(&elem[0], "1");
     test_infix_expression(&elem[1], "2", "*", "2");
     test_infix_expression(&elem[2], "3", "+", "3");
 }
 
+#[test]
+fn test_parse_tuple() {
+    let tests = vec![
+        ("(1, 2 * 2, 3 + 3)", "(1, (2 * 2), (3 + 3))", 3),
+        ("(1, 2,)", "(1, 2)", 2),
+        ("(1,)", "(1,)", 1),
+        ("()", "()", 0),
+        ("((1, 2), [3])", "((1, 2), [3])", 2),
+    ];
+
+    for (input, expected, len) in tests {
+        let lex = Lexer::new(input.to_string());
+        let mut parser = Parser::new(lex);
+        let binding = parser.parse_program();
+        let program = get_program(&binding);
+
+        check_parse_errors(parser);
+        assert_eq!(1, program.statements.len());
+
+        match &program.statements[0] {
+            AstNode::TupleLiteral(tuple) => assert_eq!(len, tuple.elements.len()),
+            actual => panic!("Expected a tuple, got {:?}", actual),
+        }
+        assert_eq!(expected, program.string(), "{}", input);
+    }
+}
+
+#[test]
+fn test_parse_set() {
+    let tests = vec![
+        ("#{1, 2 * 2, (3,)}", "#{1, (2 * 2), (3,)}", 3),
+        ("#{}", "#{}", 0),
+    ];
+
+    for (input, expected, len) in tests {
+        let lex = Lexer::new(input.to_string());
+        let mut parser = Parser::new(lex);
+        let binding = parser.parse_program();
+        let program = get_program(&binding);
+
+        check_parse_errors(parser);
+        assert_eq!(1, program.statements.len());
+
+        match &program.statements[0] {
+            AstNode::SetLiteral(set) => assert_eq!(len, set.elements.len()),
+            actual => panic!("Expected a set, got {:?}", actual),
+        }
+        assert_eq!(expected, program.string(), "{}", input);
+    }
+}
+
 #[test]
 fn test_parsing_index_expreson() {
     let input = "myArray[1 + 1]";
@@ -622,6 +716,13 @@ fn test_parsing_hash_literals_string_keys() {
 
         test_int_literal(val, &expected_value.to_string());
     }
+
+    let keys: Vec<String> = dictionary_expression
+        .pairs
+        .iter()
+        .map(|(key, _)| key.string())
+        .collect();
+    assert_eq!(vec!["one", "two", "three"], keys);
 }
 
 #[test]
@@ -721,7 +822,7 @@ fn test_for_expression() {
 
     assert_eq!(1, for_expression.statements.statements.len());
     let statement = &for_expression.statements.statements[0];
-    test_identifier(&statement, "x")
+    test_identifier(statement, "x")
 }
 
 #[test]
@@ -764,7 +865,7 @@ fn test_while_expression() {
 
     assert_eq!(1, while_loop.statements.statements.len());
     let statement = &while_loop.statements.statements[0];
-    test_identifier(&statement, "x")
+    test_identifier(statement, "x")
 }
 
 #[test]
@@ -788,7 +889,94 @@ fn test_loop_expression() {
 
     assert_eq!(1, loop_exp.statements.statements.len());
     let statement = &loop_exp.statements.statements[0];
-    test_identifier(&statement, "x")
+    test_identifier(statement, "x")
+}
+
+#[test]
+fn test_try_expression() {
+    let input = "try { x } catch (e) { y } finally { z }";
+
+    let lex = Lexer::new(input.to_string());
+    let mut parser = Parser::new(lex);
+    let parse_program = parser.parse_program();
+    let program = get_program(&parse_program);
+
+    check_parse_errors(parser);
+    assert_eq!(1, program.statements.len());
+
+    let exp = &program.statements[0];
+
+    let try_expression = match exp.clone() {
+        AstNode::TryExpression(try_expression) => try_expression,
+        actual => panic!("Expected a try expression, got {:?}", actual),
+    };
+
+    assert_eq!(1, try_expression.block.statements.len());
+    test_identifier(&try_expression.block.statements[0], "x");
+
+    let (parameter, handler) = try_expression.catch.unwrap();
+    assert_eq!("e", parameter.token_literal());
+    assert_eq!(1, handler.statements.len());
+    test_identifier(&handler.statements[0], "y");
+
+    let finally = try_expression.finally.unwrap();
+    assert_eq!(1, finally.statements.len());
+    test_identifier(&finally.statements[0], "z");
+}
+
+#[test]
+fn test_try_expression_without_handlers() {
+    let input = "try { x }";
+
+    let lex = Lexer::new(input.to_string());
+    let mut parser = Parser::new(lex);
+    parser.parse_program();
+
+    assert_eq!(
+        vec!["expected catch or finally after try block".to_string()],
+        parser.errors()
+    );
+}
+
+#[test]
+fn test_throw_statement() {
+    let input = "throw x;";
+
+    let lex = Lexer::new(input.to_string());
+    let mut parser = Parser::new(lex);
+    let parse_program = parser.parse_program();
+    let program = get_program(&parse_program);
+
+    check_parse_errors(parser);
+    assert_eq!(1, program.statements.len());
+
+    let throw_statement = match &program.statements[0] {
+        AstNode::ThrowStatement(throw_statement) => throw_statement,
+        actual => panic!("Expected a throw statement, got {:?}", actual),
+    };
+
+    test_identifier(&throw_statement.value, "x");
+}
+
+#[test]
+fn test_propagation_expression() {
+    let tests = vec![
+        ("read(x)?", "(read(x)?)"),
+        ("a + b?", "(a + (b?))"),
+        ("-a?", "(-(a?))"),
+        ("a[0]?", "((a[0])?)"),
+    ];
+
+    for (input, expected) in tests {
+        let lex = Lexer::new(input.to_string());
+        let mut parser = Parser::new(lex);
+        let parse_program = parser.parse_program();
+        let program = get_program(&parse_program);
+
+        check_parse_errors(parser);
+        assert_eq!(1, program.statements.len());
+        assert_eq!(expected, program.statements[0].string());
+    }
 }
 
 //-------------------[Test helpers]-------------------//
@@ -846,7 +1034,7 @@ fn test_let_statement(test: &str, statement: &AstNode) {
 }
 
 fn test_literal_expression(exp: &AstNode, expected: &str) {
-    if expected.chars().next().unwrap().is_digit(10) {
+    if expected.chars().next().unwrap().is_ascii_digit() {
         test_int_literal(exp, expected);
     } else if expected == "true" || expected == "false" {
         test_boolean(exp, expected);
@@ -886,7 +1074,7 @@ fn test_infix_expression(
 
 fn check_parse_errors(par: Parser) {
     let errors = par.errors();
-    if errors.len() == 0 {
+    if errors.is_empty() {
         return;
     }
 
@@ -1,38 +1,41 @@
-use crate::evaluator::Evaluator;
-use crate::lexer::Lexer;
-use crate::object::environment::Environment;
+use crate::engine::{Engine, HalconError};
 use crate::object::Object;
-use crate::parser::Parser;
 use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 const PROMPT: &str = ">>";
+const SOURCE: &str = "<repl>";
+
+pub fn start(input: io::Stdin, mut output: io::Stdout, mut engine: Engine) {
+    // Ctrl-C stops the input being evaluated instead of the whole session
+    let interrupt = Arc::new(AtomicBool::new(false));
+    let handler_interrupt = interrupt.clone();
+    if let Err(error) = ctrlc::set_handler(move || handler_interrupt.store(true, Ordering::Relaxed))
+    {
+        eprintln!("could not install the Ctrl-C handler: {}", error);
+    }
+    engine.evaluator().set_interrupt_flag(interrupt);
 
-pub fn start(input: io::Stdin, mut output: io::Stdout) {
-    let mut env = Environment::new();
     loop {
         print!("{}", PROMPT);
         output.flush().unwrap();
         let mut scanned = String::new();
         let length = input.read_line(&mut scanned).unwrap();
-        if length == 1 {
+        if length <= 1 {
             break;
         }
 
-        let lex = Lexer::new(scanned);
-        let mut pars = Parser::new(lex);
-
-        let program = pars.parse_program();
-
-        if pars.errors().len() != 0 {
-            println!("Errors have been found: \n{:?}", pars.errors());
-            continue;
+        match engine.eval_str(&scanned) {
+            Ok(value) => println!("{}", value.inspect()),
+            Err(HalconError::Parse(errors) | HalconError::Resolve(errors)) => {
+                println!("Errors have been found: \n{:?}", errors)
+            }
+            Err(HalconError::Runtime(error)) => {
+                println!("{}", error.inspect());
+                print!("{}", error.trace(SOURCE));
+            }
+            Err(error) => println!("{}", error),
         }
-
-        let mut evaluator = Evaluator::new_env(env);
-        let evaluated = evaluator.eval(program);
-
-        env = evaluator.env;
-
-        println!("{}", evaluated.inspect());
     }
 }
@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ast::expressions::{FunctionLiteral, FunctionScope, Identifier, Resolution};
+use crate::ast::statements::{BlockStatement, LetStatement};
+use crate::ast::{AstNode, Program};
+
+#[cfg(test)]
+mod test;
+
+/// A variable declared in a scope
+#[derive(Clone, Copy)]
+struct Declaration {
+    /// the slot of the variable in the call frame
+    slot: usize,
+    /// if it was declared by a let statement or as a parameter, so it can't be declared again
+    strict: bool,
+}
+
+/// The variables declared by the program or by a function. Blocks don't have their own scope
+#[derive(Default)]
+struct Scope {
+    variables: HashMap<String, Declaration>,
+    /// the number of slots used by the variables
+    slots: usize,
+}
+
+/// The resolver runs between the `Parser` and the `Evaluator`. It binds every variable of a function
+/// to a slot of its call frame, so the evaluator finds it without looking up its name, and reports the
+/// variables that are used but never declared and the ones declared twice in the same scope.
+/// The variables of the program stay global and are looked up by name, so functions can use the ones
+/// declared after them
+pub struct Resolver<'a> {
+    /// if a name is defined before the program runs, e.g. a builtin or a variable of a previous program
+    defined: Box<dyn Fn(&str) -> bool + 'a>,
+    /// the scope of the program followed by the scopes of the functions being resolved
+    scopes: Vec<Scope>,
+    /// the global names used before they were declared, they have to be declared by the end of the program
+    pending: Vec<String>,
+    /// the errors found, one message per error
+    diagnostics: Vec<String>,
+}
+
+impl<'a> Resolver<'a> {
+    /**
+    Returns a Resolver that knows which names are defined before the program runs
+
+    # Arguments
+    * `defined` - returns if a name is defined before the program runs
+
+    # Examples
+    ```
+    let resolver = Resolver::new(|name| evaluator.defines(name));
+    ```
+    */
+    pub fn new(defined: impl Fn(&str) -> bool + 'a) -> Self {
+        Self {
+            defined: Box::new(defined),
+            scopes: vec![Scope::default()],
+            pending: vec![],
+            diagnostics: vec![],
+        }
+    }
+
+    /**
+    Annotates the variables and functions of a program with their slots and returns the errors found.
+    The program is annotated even if there are errors
+
+    # Arguments
+    * `program` - the program to resolve
+
+    # Examples
+    ```
+    let diagnostics = Resolver::new(|_| false).resolve(&mut program);
+    ```
+    */
+    pub fn resolve(mut self, program: &mut Program) -> Vec<String> {
+        self.statements(&mut program.statements);
+
+        for name in std::mem::take(&mut self.pending) {
+            if !self.scopes[0].variables.contains_key(&name) {
+                self.diagnostics
+                    .push(format!("variable {} is not defined", name));
+            }
+        }
+
+        self.diagnostics
+    }
+
+    fn statements(&mut self, statements: &mut [AstNode]) {
+        for statement in statements {
+            self.node(statement);
+        }
+    }
+
+    fn block(&mut self, block: &mut BlockStatement) {
+        self.statements(&mut block.statements);
+    }
+
+    /**
+    Resolves the variables used and declared by a node, in the order they are evaluated
+
+    # Arguments
+    * `node` - the node to resolve
+    */
+    fn node(&mut self, node: &mut AstNode) {
+        match node {
+            AstNode::Program(program) => self.statements(&mut program.statements),
+            AstNode::Identifier(id) => id.resolution = Some(self.use_variable(id.name())),
+            AstNode::PrefixExpression(prefix_expression) => self.node(&mut prefix_expression.right),
+            AstNode::InfixExpression(infix_expression) => {
+                self.node(&mut infix_expression.left);
+                self.node(&mut infix_expression.right);
+            }
+            AstNode::IfExpression(if_expression) => {
+                self.node(&mut if_expression.condition);
+                self.block(&mut if_expression.consequence);
+                for (condition, consequence) in &mut if_expression.elifs {
+                    self.node(condition);
+                    self.block(consequence);
+                }
+                if let Some(alternative) = &mut if_expression.alternative {
+                    self.block(alternative);
+                }
+            }
+            AstNode::TryExpression(try_expression) => {
+                self.block(&mut try_expression.block);
+                if let Some((parameter, handler)) = &mut try_expression.catch {
+                    self.catch_block(parameter, handler);
+                }
+                if let Some(finally) = &mut try_expression.finally {
+                    self.block(finally);
+                }
+            }
+            AstNode::FunctionLiteral(function_literal) => self.function(function_literal, None),
+            AstNode::ArrayLiteral(array_literal) => self.statements(&mut array_literal.elements),
+            AstNode::TupleLiteral(tuple_literal) => self.statements(&mut tuple_literal.elements),
+            AstNode::SetLiteral(set_literal) => self.statements(&mut set_literal.elements),
+            AstNode::DictLiteral(dict) => {
+                for (key, value) in &mut dict.pairs {
+                    self.node(key);
+                    self.node(value);
+                }
+            }
+            AstNode::IndexExpression(index_expression) => {
+                self.node(&mut index_expression.left);
+                self.node(&mut index_expression.index);
+            }
+            AstNode::PropagationExpression(propagation) => self.node(&mut propagation.value),
+            AstNode::CallExpression(call) => {
+                self.node(&mut call.function);
+                self.statements(&mut call.arguments);
+            }
+            AstNode::LetStatement(let_statement) => self.let_statement(let_statement, true),
+            AstNode::ReturnStatement(return_statement) => {
+                self.node(&mut return_statement.return_value)
+            }
+            AstNode::ThrowStatement(throw_statement) => self.node(&mut throw_statement.value),
+            AstNode::BlockStatement(block) => self.block(block),
+            AstNode::Assignation(assig) => {
+                self.node(&mut assig.value);
+                assig.name.resolution = Some(self.use_variable(assig.name.name()));
+            }
+            AstNode::PostIncrement(inc) => inc.resolution = Some(self.use_variable(inc.name())),
+            AstNode::PostDecrement(dec) => dec.resolution = Some(self.use_variable(dec.name())),
+            AstNode::ForLoop(for_loop) => {
+                self.let_statement(&mut for_loop.initialization, false);
+                self.node(&mut for_loop.condition);
+                self.block(&mut for_loop.statements);
+                self.node(&mut for_loop.step);
+            }
+            AstNode::WhileLoop(while_loop) => {
+                self.node(&mut while_loop.condition);
+                self.block(&mut while_loop.statements);
+            }
+            AstNode::Loop(loop_exp) => self.block(&mut loop_exp.statements),
+            AstNode::IntegerLiteral(_)
+            | AstNode::Boolean(_)
+            | AstNode::StringLiteral(_)
+            | AstNode::Break => {}
+        }
+    }
+
+    /**
+    Resolves a let statement. The value is resolved before the variable is declared, except that
+    a function can call itself by the name it is bound to
+
+    # Arguments
+    * `let_statement` - the let statement to resolve
+    * `strict` - if declaring the variable again in the same scope is an error, it is not for the initialization of a for loop
+    */
+    fn let_statement(&mut self, let_statement: &mut LetStatement, strict: bool) {
+        match &mut *let_statement.value {
+            AstNode::FunctionLiteral(function_literal) => {
+                self.function(function_literal, Some(let_statement.name.name()))
+            }
+            value => self.node(value),
+        }
+
+        self.declare(&mut let_statement.name, strict);
+    }
+
+    /**
+    Resolves a catch block with its parameter in a new scope around the block. The parameter takes
+    a new slot, and after the block its name refers again to the variable it shadows, if any
+
+    # Arguments
+    * `parameter` - the variable the caught error is bound to
+    * `handler` - the catch block
+    */
+    fn catch_block(&mut self, parameter: &mut Identifier, handler: &mut BlockStatement) {
+        let scope = self.scopes.last_mut().expect("there is no scope");
+        let shadowed = scope.variables.remove(parameter.name());
+        self.declare(parameter, false);
+        self.block(handler);
+
+        let scope = self.scopes.last_mut().expect("there is no scope");
+        match shadowed {
+            Some(declaration) => scope
+                .variables
+                .insert(parameter.name().to_string(), declaration),
+            None => scope.variables.remove(parameter.name()),
+        };
+    }
+
+    /**
+    Resolves a function literal in a new scope. The parameters take the first slots,
+    followed by the function itself if it is bound to a name
+
+    # Arguments
+    * `function_literal` - the function to resolve
+    * `name` - the name the function is bound to, if it is the value of a let statement
+    */
+    fn function(&mut self, function_literal: &mut FunctionLiteral, name: Option<&str>) {
+        let mut scope = Scope::default();
+        for parameter in function_literal.parameters.iter() {
+            let declaration = Declaration {
+                slot: scope.slots,
+                strict: true,
+            };
+            if scope
+                .variables
+                .insert(parameter.name().to_string(), declaration)
+                .is_some()
+            {
+                self.diagnostics.push(format!(
+                    "variable {} is already declared in this scope",
+                    parameter.name()
+                ));
+            }
+            scope.slots += 1;
+        }
+
+        let recursive = match name {
+            Some(name) if !scope.variables.contains_key(name) => {
+                let declaration = Declaration {
+                    slot: scope.slots,
+                    strict: false,
+                };
+                scope.variables.insert(name.to_string(), declaration);
+                scope.slots += 1;
+                Some(declaration.slot)
+            }
+            _ => None,
+        };
+
+        self.scopes.push(scope);
+        self.block(Rc::make_mut(&mut function_literal.body));
+        let scope = self
+            .scopes
+            .pop()
+            .expect("the scope of the function is missing");
+
+        function_literal.scope = Some(FunctionScope {
+            slots: scope.slots,
+            recursive,
+        });
+    }
+
+    /**
+    Declares a variable in the innermost scope and annotates it with its slot.
+    Declaring a variable again reuses its slot
+
+    # Arguments
+    * `name` - the declared variable
+    * `strict` - if declaring the variable again in the same scope is an error
+    */
+    fn declare(&mut self, name: &mut Identifier, strict: bool) {
+        let global = self.scopes.len() == 1;
+        let scope = self.scopes.last_mut().expect("there is no scope");
+
+        let slot = match scope.variables.get_mut(name.name()) {
+            Some(declaration) => {
+                if strict && declaration.strict {
+                    self.diagnostics.push(format!(
+                        "variable {} is already declared in this scope",
+                        name.name()
+                    ));
+                }
+                declaration.strict |= strict;
+                declaration.slot
+            }
+            None => {
+                let slot = scope.slots;
+                scope
+                    .variables
+                    .insert(name.name().to_string(), Declaration { slot, strict });
+                scope.slots += 1;
+                slot
+            }
+        };
+
+        name.resolution = Some(if global {
+            Resolution::Global
+        } else {
+            Resolution::Local { depth: 0, slot }
+        });
+    }
+
+    /**
+    Returns where a used variable is declared, searching from the innermost scope out.
+    Variables not declared in any function are global, if they are not defined yet they have to be
+    declared later in the program
+
+    # Arguments
+    * `name` - the name of the variable
+    */
+    fn use_variable(&mut self, name: &str) -> Resolution {
+        let innermost = self.scopes.len() - 1;
+        for (i, scope) in self.scopes.iter().enumerate().skip(1).rev() {
+            if let Some(declaration) = scope.variables.get(name) {
+                return Resolution::Local {
+                    depth: innermost - i,
+                    slot: declaration.slot,
+                };
+            }
+        }
+
+        if !self.scopes[0].variables.contains_key(name)
+            && !(self.defined)(name)
+            && !self.pending.iter().any(|pending| pending == name)
+        {
+            self.pending.push(name.to_string());
+        }
+        Resolution::Global
+    }
+}
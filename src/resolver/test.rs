@@ -0,0 +1,193 @@
+use super::*;
+use crate::ast::expressions::FunctionLiteral;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+
+#[test]
+fn test_resolve_slots() {
+    let (statements, diagnostics) = resolve("let f = fun(a, b) { let c = a + b; c }; f(1, 2)");
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+    let f = function(&statements[0]);
+    assert_eq!(
+        Some(FunctionScope {
+            slots: 4,
+            recursive: Some(2)
+        }),
+        f.scope
+    );
+
+    let c = match &f.body.statements[0] {
+        AstNode::LetStatement(let_statement) => let_statement,
+        other => panic!("Expected a let statement, got {:?}", other),
+    };
+    assert_eq!(Some(local(0, 3)), c.name.resolution);
+    match &*c.value {
+        AstNode::InfixExpression(infix) => {
+            assert_eq!(Some(local(0, 0)), resolution(&infix.left));
+            assert_eq!(Some(local(0, 1)), resolution(&infix.right));
+        }
+        other => panic!("Expected an infix expression, got {:?}", other),
+    }
+    assert_eq!(Some(local(0, 3)), resolution(&f.body.statements[1]));
+
+    match &statements[0] {
+        AstNode::LetStatement(let_statement) => {
+            assert_eq!(Some(Resolution::Global), let_statement.name.resolution)
+        }
+        other => panic!("Expected a let statement, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_resolve_closures() {
+    let (statements, diagnostics) =
+        resolve("let make = fun(a) { fun(b) { fun(c) { a + b + c } } };");
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+    let make = function(&statements[0]);
+    let middle = function(&make.body.statements[0]);
+    let inner = function(&middle.body.statements[0]);
+    assert_eq!(
+        Some(FunctionScope {
+            slots: 1,
+            recursive: None
+        }),
+        inner.scope
+    );
+
+    let (sum, c) = match &inner.body.statements[0] {
+        AstNode::InfixExpression(infix) => (&*infix.left, &*infix.right),
+        other => panic!("Expected an infix expression, got {:?}", other),
+    };
+    match sum {
+        AstNode::InfixExpression(infix) => {
+            assert_eq!(Some(local(2, 0)), resolution(&infix.left));
+            assert_eq!(Some(local(1, 0)), resolution(&infix.right));
+        }
+        other => panic!("Expected an infix expression, got {:?}", other),
+    }
+    assert_eq!(Some(local(0, 0)), resolution(c));
+}
+
+#[test]
+fn test_resolve_globals() {
+    let (statements, diagnostics) = resolve("let f = fun() { x + y }; let y = 2; len");
+    assert_eq!(vec!["variable x is not defined"], diagnostics);
+
+    let f = function(&statements[0]);
+    match &f.body.statements[0] {
+        AstNode::InfixExpression(infix) => {
+            assert_eq!(Some(Resolution::Global), resolution(&infix.left));
+            assert_eq!(Some(Resolution::Global), resolution(&infix.right));
+        }
+        other => panic!("Expected an infix expression, got {:?}", other),
+    }
+    assert_eq!(Some(Resolution::Global), resolution(&statements[2]));
+}
+
+#[test]
+fn test_undefined_variables() {
+    let tests = vec![
+        ("x + 1", vec!["variable x is not defined"]),
+        ("x; x = 2; x++", vec!["variable x is not defined"]),
+        (
+            "let f = fun() { let a = 1; a }; a",
+            vec!["variable a is not defined"],
+        ),
+        (
+            "let f = fun() { let g = fun() { h() }; let h = fun() { 1 }; g() }",
+            vec!["variable h is not defined"],
+        ),
+        (
+            "try { 1 } catch (e) { e }; fun() { try { 1 } catch (err) { err } }; err",
+            vec!["variable err is not defined"],
+        ),
+        (
+            "let r = try { 1 } catch (e) { e }; len(e)",
+            vec!["variable e is not defined"],
+        ),
+        (
+            "fun() { try { 1 } catch (e) { e }; e }",
+            vec!["variable e is not defined"],
+        ),
+        ("let e = 1; try { 1 } catch (e) { e }; e", vec![]),
+        ("let f = fun() { g() }; let g = fun() { f() };", vec![]),
+        ("x + 1; let x = 1;", vec![]),
+    ];
+
+    for (input, expected) in tests {
+        let (_, diagnostics) = resolve(input);
+
+        assert_eq!(expected, diagnostics, "{}", input);
+    }
+}
+
+#[test]
+fn test_redeclarations() {
+    let tests = vec![
+        (
+            "let x = 1; let x = 2;",
+            vec!["variable x is already declared in this scope"],
+        ),
+        (
+            "let x = 1; if (true) { let x = 2; }",
+            vec!["variable x is already declared in this scope"],
+        ),
+        (
+            "fun(a) { let a = 1; }",
+            vec!["variable a is already declared in this scope"],
+        ),
+        (
+            "fun(a, a) { a }",
+            vec!["variable a is already declared in this scope"],
+        ),
+        ("let x = 1; fun() { let x = 2; x }", vec![]),
+        ("let f = fun() { let f = 1; f }", vec![]),
+        (
+            "for (let i = 0; i < 2; i++) { }; for (let i = 0; i < 2; i++) { }",
+            vec![],
+        ),
+        (
+            "try { 1 } catch (e) { e }; try { 1 } catch (e) { e }",
+            vec![],
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let (_, diagnostics) = resolve(input);
+
+        assert_eq!(expected, diagnostics, "{}", input);
+    }
+}
+
+//-------------------[Test helpers]-------------------//
+
+fn resolve(input: &str) -> (Vec<AstNode>, Vec<String>) {
+    match Parser::new(Lexer::new(input.to_string())).parse_program() {
+        AstNode::Program(mut program) => {
+            let diagnostics = Resolver::new(|name| name == "len").resolve(&mut program);
+            (program.statements, diagnostics)
+        }
+        other => panic!("Expected a program, got {:?}", other),
+    }
+}
+
+fn function(node: &AstNode) -> &FunctionLiteral {
+    match node {
+        AstNode::LetStatement(let_statement) => function(&let_statement.value),
+        AstNode::FunctionLiteral(function_literal) => function_literal,
+        other => panic!("Expected a function, got {:?}", other),
+    }
+}
+
+fn resolution(node: &AstNode) -> Option<Resolution> {
+    match node {
+        AstNode::Identifier(id) => id.resolution,
+        other => panic!("Expected an identifier, got {:?}", other),
+    }
+}
+
+fn local(depth: usize, slot: usize) -> Resolution {
+    Resolution::Local { depth, slot }
+}
@@ -1,10 +1,15 @@
 use core::fmt::Display;
+use std::rc::Rc;
+
+use crate::bigint::BigInt;
 
 #[derive(Clone, Debug, PartialEq, Hash, Eq)]
 pub enum Token {
-    Id(String),
+    Id(Rc<str>),
     ConstInt(i128),
-    ConstStr(String),
+    /// an integer literal too big for an `i128`
+    ConstBigInt(Rc<BigInt>),
+    ConstStr(Rc<str>),
     ConstBool(bool),
 
     Int,
@@ -28,6 +33,8 @@ pub enum Token {
     And,
     Or,
     Not,
+    In,
+    NotIn,
 
     Inc,
     Dec,
@@ -49,10 +56,15 @@ pub enum Token {
     For,
     Loop,
     While,
+    Try,
+    Catch,
+    Finally,
+    Throw,
 
     Coma,
     Semicolon,
     Colon,
+    Question,
 
     Opar,
     Cpar,
@@ -60,16 +72,31 @@ pub enum Token {
     Cbrac,
     Okey,
     Ckey,
+    Oset,
 
     Eof,
     Invalid(String),
 }
 
+/// The position of a token in the source code. Lines and columns start at 1
+#[derive(Clone, Copy, Debug, Default, PartialEq, Hash, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
 impl Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Token::Id(x) => write!(f, "Id({})", x),
             Token::ConstInt(x) => write!(f, "ConstInt({})", x),
+            Token::ConstBigInt(x) => write!(f, "ConstBigInt({})", x),
             Token::ConstStr(x) => write!(f, "ConstStr({})", x),
             Token::ConstBool(x) => write!(f, "ConstBool({})", x),
             Token::Invalid(x) => write!(f, "Invalid({})", x),
@@ -91,6 +118,8 @@ impl Display for Token {
             Token::And => write!(f, "and"),
             Token::Or => write!(f, "or"),
             Token::Not => write!(f, "not"),
+            Token::In => write!(f, "in"),
+            Token::NotIn => write!(f, "not in"),
             Token::Inc => write!(f, "++"),
             Token::Dec => write!(f, "--"),
             Token::Assig => write!(f, "="),
@@ -109,15 +138,21 @@ impl Display for Token {
             Token::For => write!(f, "for"),
             Token::Loop => write!(f, "loop"),
             Token::While => write!(f, "while"),
+            Token::Try => write!(f, "try"),
+            Token::Catch => write!(f, "catch"),
+            Token::Finally => write!(f, "finally"),
+            Token::Throw => write!(f, "throw"),
             Token::Coma => write!(f, ","),
             Token::Semicolon => write!(f, ";"),
             Token::Colon => write!(f, ":"),
+            Token::Question => write!(f, "?"),
             Token::Opar => write!(f, "("),
             Token::Cpar => write!(f, ")"),
             Token::Obrac => write!(f, "["),
             Token::Cbrac => write!(f, "]"),
             Token::Okey => write!(f, "{{"),
             Token::Ckey => write!(f, "}}"),
+            Token::Oset => write!(f, "#{{"),
             Token::Eof => write!(f, "eof"),
         }
     }